@@ -0,0 +1,358 @@
+//! Write actions on jobs: retriggering, backfilling and cancelling them
+//! through the in-tree Taskcluster actions of their push, and classifying
+//! their failures on Treeherder.
+
+use crate::models::{FailureClassification, Job};
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ring::hmac;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+use url::Url;
+
+/// Something to do to each selected job.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobAction {
+    /// Run the job again `times` more times.
+    Retrigger {
+        times: u32,
+    },
+    /// Run the job on the `depth` pushes before this one.
+    Backfill {
+        depth: u32,
+    },
+    Cancel,
+    Classify(Classification),
+}
+
+/// How a failure is classified, and under which bug.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Classification {
+    pub classification: FailureClassification,
+    pub bug: Option<u64>,
+    /// Text of the note recording the classification.
+    pub comment: String,
+}
+
+impl JobAction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            JobAction::Retrigger { .. } => "retrigger",
+            JobAction::Backfill { .. } => "backfill",
+            JobAction::Cancel => "cancel",
+            JobAction::Classify(_) => "classify",
+        }
+    }
+
+    /// What the action does, e.g. `retrigger 3 times`.
+    pub fn describe(&self) -> String {
+        match self {
+            JobAction::Retrigger { times: 1 } => "retrigger once".to_string(),
+            JobAction::Retrigger { times } => format!("retrigger {} times", times),
+            JobAction::Backfill { depth } => format!("backfill on {} earlier pushes", depth),
+            JobAction::Cancel => "cancel".to_string(),
+            JobAction::Classify(Classification {
+                classification,
+                bug: Some(bug),
+                ..
+            }) => format!("classify as {} with bug {}", classification, bug),
+            JobAction::Classify(classification) => {
+                format!("classify as {}", classification.classification)
+            }
+        }
+    }
+
+    /// Whether the action runs as an in-tree Taskcluster action, rather
+    /// than on Treeherder.
+    pub fn is_taskcluster_action(&self) -> bool {
+        !matches!(self, JobAction::Classify(_))
+    }
+
+    /// Input of the in-tree action, keeping only the properties its
+    /// `schema` declares when it declares any.
+    pub fn input(&self, schema: &Value) -> Value {
+        let mut input = match self {
+            JobAction::Retrigger { times } => json!({ "times": times, "downstream": false }),
+            JobAction::Backfill { depth } => json!({ "depth": depth, "times": 1 }),
+            JobAction::Cancel | JobAction::Classify(_) => json!({}),
+        };
+        if let (Some(input), Some(properties)) = (
+            input.as_object_mut(),
+            schema.get("properties").and_then(Value::as_object),
+        ) {
+            input.retain(|key, _| properties.contains_key(key));
+        }
+        input
+    }
+}
+
+/// Credentials for the write actions, taken from the environment or
+/// command line. Only those the chosen action needs must be set.
+#[derive(Debug, Clone, Default)]
+pub struct Credentials {
+    pub taskcluster: Option<TaskclusterCredentials>,
+    pub treeherder: Option<TreeherderCredentials>,
+}
+
+impl Credentials {
+    /// Fails unless the credentials `action` needs are set.
+    pub fn check(&self, action: &JobAction) -> Result<()> {
+        if action.is_taskcluster_action() && self.taskcluster.is_none() {
+            anyhow::bail!(
+                "{} needs Taskcluster credentials: set TASKCLUSTER_CLIENT_ID and \
+                 TASKCLUSTER_ACCESS_TOKEN, or use --dry-run",
+                action.name()
+            );
+        }
+        if !action.is_taskcluster_action() && self.treeherder.is_none() {
+            anyhow::bail!(
+                "{} needs a Treeherder login: set TREEHERDER_ACCESS_TOKEN, TREEHERDER_ID_TOKEN \
+                 and TREEHERDER_ACCESS_TOKEN_EXPIRES_AT, or use --dry-run",
+                action.name()
+            );
+        }
+        Ok(())
+    }
+
+    pub fn taskcluster_authorization(&self, method: &str, url: &str) -> Result<String> {
+        self.taskcluster
+            .as_ref()
+            .context("No Taskcluster credentials")?
+            .authorization(method, url)
+    }
+}
+
+/// The tokens of a Treeherder login, as the Treeherder UI keeps them in the
+/// `userSession` entry of its local storage after signing in.
+#[derive(Debug, Clone)]
+pub struct TreeherderCredentials {
+    pub access_token: String,
+    pub id_token: String,
+    /// When the access token expires, in seconds since the epoch.
+    pub expires_at: u64,
+}
+
+impl TreeherderCredentials {
+    /// Headers of the `/api/auth/login/` request that exchanges the tokens
+    /// for a session.
+    pub fn login_headers(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("Authorization", format!("Bearer {}", self.access_token)),
+            ("Id-Token", self.id_token.clone()),
+            ("Access-Token-Expires-At", self.expires_at.to_string()),
+        ]
+    }
+}
+
+/// A Treeherder session. Writes are authenticated by its cookie rather
+/// than by the tokens, and must carry its CSRF token.
+#[derive(Debug, Clone)]
+pub struct TreeherderSession {
+    pub session_id: String,
+    pub csrf_token: String,
+}
+
+impl TreeherderSession {
+    /// Reads the `sessionid` and `csrftoken` cookies set by a login.
+    pub fn from_cookies<'a>(set_cookies: impl IntoIterator<Item = &'a str>) -> Result<Self> {
+        let mut session_id = None;
+        let mut csrf_token = None;
+        for set_cookie in set_cookies {
+            let cookie = set_cookie.split(';').next().unwrap_or_default();
+            match cookie.split_once('=') {
+                Some(("sessionid", value)) => session_id = Some(value.trim().to_string()),
+                Some(("csrftoken", value)) => csrf_token = Some(value.trim().to_string()),
+                _ => {}
+            }
+        }
+        Ok(Self {
+            session_id: session_id.context("Treeherder login set no session cookie")?,
+            csrf_token: csrf_token.context("Treeherder login set no CSRF cookie")?,
+        })
+    }
+
+    /// Headers of a write to the Treeherder at `treeherder`. Django checks
+    /// that HTTPS writes are referred from the same origin.
+    pub fn headers(&self, treeherder: &str) -> Vec<(&'static str, String)> {
+        vec![
+            (
+                "Cookie",
+                format!(
+                    "sessionid={}; csrftoken={}",
+                    self.session_id, self.csrf_token
+                ),
+            ),
+            ("X-CSRFToken", self.csrf_token.clone()),
+            ("Referer", format!("{}/", treeherder.trim_end_matches('/'))),
+        ]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TaskclusterCredentials {
+    pub client_id: String,
+    pub access_token: String,
+    /// Certificate of temporary credentials, as JSON.
+    pub certificate: Option<String>,
+}
+
+impl TaskclusterCredentials {
+    /// Hawk `Authorization` header for a `method` request to `url`.
+    pub fn authorization(&self, method: &str, url: &str) -> Result<String> {
+        let url = Url::parse(url)?;
+        let host = url.host_str().context("URL without host")?.to_lowercase();
+        let port = url.port_or_known_default().unwrap_or_default();
+        let resource = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        let ts = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let nonce: String = std::iter::repeat_with(fastrand::alphanumeric)
+            .take(8)
+            .collect();
+        let ext = match &self.certificate {
+            Some(certificate) => {
+                let certificate: Value = serde_json::from_str(certificate)
+                    .context("TASKCLUSTER_CERTIFICATE is not valid JSON")?;
+                BASE64.encode(json!({ "certificate": certificate }).to_string())
+            }
+            None => String::new(),
+        };
+
+        let normalized = format!(
+            "hawk.1.header\n{}\n{}\n{}\n{}\n{}\n{}\n\n{}\n",
+            ts,
+            nonce,
+            method.to_uppercase(),
+            resource,
+            host,
+            port,
+            ext
+        );
+        let key = hmac::Key::new(hmac::HMAC_SHA256, self.access_token.as_bytes());
+        let mac = BASE64.encode(hmac::sign(&key, normalized.as_bytes()).as_ref());
+
+        let mut header = format!(
+            "Hawk id=\"{}\", ts=\"{}\", nonce=\"{}\"",
+            self.client_id, ts, nonce
+        );
+        if !ext.is_empty() {
+            header.push_str(&format!(", ext=\"{}\"", ext));
+        }
+        header.push_str(&format!(", mac=\"{}\"", mac));
+        Ok(header)
+    }
+}
+
+/// The actions a decision task declares in its `public/actions.json`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct ActionsJson {
+    pub actions: Vec<ActionDefinition>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionDefinition {
+    pub name: String,
+    pub kind: String,
+    /// Tags of the tasks the action applies to; empty for push actions.
+    #[serde(default)]
+    pub context: Vec<Value>,
+    #[serde(default)]
+    pub schema: Value,
+    #[serde(default)]
+    pub hook_group_id: Option<String>,
+    #[serde(default)]
+    pub hook_id: Option<String>,
+    /// JSON-e template of the payload the hook is triggered with.
+    #[serde(default)]
+    pub hook_payload: Value,
+}
+
+impl ActionsJson {
+    /// The hook action called `name` that applies to single tasks.
+    pub fn task_action(&self, name: &str) -> Option<&ActionDefinition> {
+        self.actions.iter().find(|action| {
+            action.name == name && action.kind == "hook" && !action.context.is_empty()
+        })
+    }
+}
+
+/// Renders a hook payload template with `context`. Only the `$eval`s of
+/// plain variables that in-tree actions use are supported.
+pub fn render_hook_payload(template: &Value, context: &Value) -> Result<Value> {
+    Ok(match template {
+        Value::Object(object) => {
+            if let Some(expression) = object.get("$eval") {
+                let variable = expression
+                    .as_str()
+                    .context("Unsupported $eval in hook payload")?;
+                return context
+                    .get(variable)
+                    .cloned()
+                    .with_context(|| format!("Unknown variable '{}' in hook payload", variable));
+            }
+            if let Some(operator) = object.keys().find(|key| key.starts_with('$')) {
+                anyhow::bail!("Unsupported JSON-e operator '{}' in hook payload", operator);
+            }
+            let mut rendered = serde_json::Map::new();
+            for (key, value) in object {
+                rendered.insert(key.clone(), render_hook_payload(value, context)?);
+            }
+            Value::Object(rendered)
+        }
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| render_hook_payload(item, context))
+                .collect::<Result<_>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActionStatus {
+    /// Would be done without `--dry-run`.
+    Planned,
+    Done,
+    Failed,
+}
+
+/// What became of the action on one job.
+#[derive(Debug, Clone, Serialize)]
+pub struct ActionOutcome {
+    pub action: String,
+    pub job_id: u64,
+    pub job_type_name: String,
+    pub platform: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<String>,
+    pub status: ActionStatus,
+    /// The task that carries out a Taskcluster action, or why the action
+    /// failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl ActionOutcome {
+    pub fn new(action: &JobAction, job: &Job, status: ActionStatus) -> Self {
+        Self {
+            action: action.describe(),
+            job_id: job.id,
+            job_type_name: job.job_type_name.clone(),
+            platform: job.platform.clone(),
+            task_id: job.task_id.clone(),
+            status,
+            detail: None,
+        }
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
@@ -1,19 +1,57 @@
+use crate::actions::{
+    ActionsJson, Classification, Credentials, TreeherderCredentials, TreeherderSession,
+};
+use crate::http::HttpClient;
 use crate::models::*;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use regex::Regex;
-use reqwest::Client;
+use reqwest::header::SET_COOKIE;
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use url::Url;
 
-pub async fn fetch_lando_job_status(client: &Client, job_id: u64) -> Result<LandoJobResponse> {
-    let url = format!(
-        "https://api.lando.services.mozilla.com/landing_jobs/{}",
-        job_id
-    );
+pub const DEFAULT_TREEHERDER_URL: &str = "https://treeherder.mozilla.org";
+pub const DEFAULT_TASKCLUSTER_URL: &str = "https://firefox-ci-tc.services.mozilla.com";
+pub const DEFAULT_LANDO_URL: &str = "https://api.lando.services.mozilla.com";
+
+/// Base URLs of the services the tool talks to. Override these to use a
+/// staging instance (e.g. treeherder.allizom.org), a mirror or a local fake.
+#[derive(Debug, Clone)]
+pub struct Endpoints {
+    pub treeherder: String,
+    pub taskcluster: String,
+    pub lando: String,
+}
 
-    let response: LandoJobResponse = client.get(&url).send().await?.json().await?;
+impl Endpoints {
+    pub fn new(treeherder: &str, taskcluster: &str, lando: &str) -> Self {
+        Self {
+            treeherder: treeherder.trim_end_matches('/').to_string(),
+            taskcluster: taskcluster.trim_end_matches('/').to_string(),
+            lando: lando.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_TREEHERDER_URL,
+            DEFAULT_TASKCLUSTER_URL,
+            DEFAULT_LANDO_URL,
+        )
+    }
+}
+
+pub async fn fetch_lando_job_status(
+    client: &HttpClient,
+    endpoints: &Endpoints,
+    job_id: u64,
+) -> Result<LandoJobResponse> {
+    let url = format!("{}/landing_jobs/{}", endpoints.lando, job_id);
+
+    let response: LandoJobResponse = client.get_json(&url).await?;
 
     if response.id != job_id {
         anyhow::bail!(
@@ -26,8 +64,12 @@ pub async fn fetch_lando_job_status(client: &Client, job_id: u64) -> Result<Land
     Ok(response)
 }
 
-pub async fn fetch_commit_from_lando_job(client: &Client, job_id: u64) -> Result<String> {
-    let response = fetch_lando_job_status(client, job_id).await?;
+pub async fn fetch_commit_from_lando_job(
+    client: &HttpClient,
+    endpoints: &Endpoints,
+    job_id: u64,
+) -> Result<String> {
+    let response = fetch_lando_job_status(client, endpoints, job_id).await?;
 
     if response.status != "LANDED" {
         anyhow::bail!(
@@ -47,46 +89,257 @@ pub async fn fetch_commit_from_lando_job(client: &Client, job_id: u64) -> Result
     }
 }
 
-pub fn extract_revision(input: &str) -> Result<String> {
-    if input.starts_with("http") {
-        let url = Url::parse(input)?;
-        if let Some(revision) = url
-            .query_pairs()
-            .find(|(key, _)| key == "revision")
-            .map(|(_, value)| value.to_string())
-        {
-            Ok(revision)
-        } else {
-            anyhow::bail!("No revision found in URL")
-        }
+/// Digits of a full revision hash.
+pub const FULL_REVISION_LEN: usize = 40;
+/// Digits of the shortest revision hash accepted, as Treeherder and
+/// hg.mozilla.org abbreviate them.
+pub const SHORT_REVISION_LEN: usize = 12;
+
+/// What a revision argument points at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RevisionTarget {
+    /// A revision hash, full or abbreviated.
+    Revision(String),
+    /// A Lando landing job, from Treeherder's `landoCommitID` parameter.
+    LandoJob(u64),
+}
+
+/// A parsed revision argument, and the repository it names if it is a URL
+/// that does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevisionInput {
+    pub target: RevisionTarget,
+    pub repo: Option<String>,
+}
+
+/// Parses a revision hash or one of these URLs:
+///
+/// - Treeherder: `/jobs?repo=try&revision=...`, `/#/jobs?...`,
+///   `/push-health/push?repo=...&revision=...`, or `landoCommitID=` in place
+///   of `revision=`
+/// - hg.mozilla.org: `/try/rev/<hash>`, `/integration/autoland/pushloghtml?changeset=<hash>`
+///   (or `tochange=`)
+pub fn parse_revision_input(input: &str) -> Result<RevisionInput> {
+    if !input.starts_with("http") {
+        return Ok(RevisionInput {
+            target: RevisionTarget::Revision(check_revision(input)?),
+            repo: None,
+        });
+    }
+
+    let url = Url::parse(input)?;
+    let segments: Vec<&str> = url
+        .path_segments()
+        .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default();
+    // Treeherder's older URLs keep their parameters in the fragment.
+    let fragment_query = url
+        .fragment()
+        .and_then(|fragment| fragment.split_once('?'))
+        .map(|(_, query)| query)
+        .unwrap_or("");
+    let params: HashMap<String, String> = url
+        .query_pairs()
+        .chain(url::form_urlencoded::parse(fragment_query.as_bytes()))
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+
+    if let Some(i) = segments.iter().position(|&s| s == "rev") {
+        let revision = segments
+            .get(i + 1)
+            .ok_or_else(|| anyhow::anyhow!("No revision found in URL"))?;
+        return Ok(RevisionInput {
+            target: RevisionTarget::Revision(check_revision(revision)?),
+            repo: i.checked_sub(1).map(|r| segments[r].to_string()),
+        });
+    }
+
+    if let Some(i) = segments.iter().position(|&s| s == "pushloghtml") {
+        let revision = params
+            .get("changeset")
+            .or_else(|| params.get("tochange"))
+            .ok_or_else(|| anyhow::anyhow!("No changeset or tochange found in pushlog URL"))?;
+        return Ok(RevisionInput {
+            target: RevisionTarget::Revision(check_revision(revision)?),
+            repo: i.checked_sub(1).map(|r| segments[r].to_string()),
+        });
+    }
+
+    let target = if let Some(revision) = params.get("revision") {
+        RevisionTarget::Revision(check_revision(revision)?)
+    } else if let Some(job_id) = params.get("landoCommitID") {
+        RevisionTarget::LandoJob(
+            job_id
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Invalid landoCommitID '{}' in URL", job_id))?,
+        )
     } else {
-        Ok(input.to_string())
+        anyhow::bail!("No revision found in URL")
+    };
+    Ok(RevisionInput {
+        target,
+        repo: params.get("repo").cloned(),
+    })
+}
+
+pub(crate) fn check_revision(revision: &str) -> Result<String> {
+    let well_formed = (SHORT_REVISION_LEN..=FULL_REVISION_LEN).contains(&revision.len())
+        && revision.chars().all(|c| c.is_ascii_hexdigit());
+    if !well_formed {
+        anyhow::bail!(
+            "'{}' is not a revision: expected {} to {} hexadecimal digits",
+            revision,
+            SHORT_REVISION_LEN,
+            FULL_REVISION_LEN
+        );
     }
+    Ok(revision.to_ascii_lowercase())
 }
 
-pub async fn fetch_push_id(client: &Client, repo: &str, revision: &str) -> Result<u64> {
-    let url = format!(
-        "https://treeherder.mozilla.org/api/project/{}/push/?full=true&count=10&revision={}",
-        repo, revision
-    );
+/// Pushes requested per page; Treeherder serves at most 1000 at a time.
+pub const PUSH_PAGE_SIZE: usize = 1000;
+/// Jobs requested per page of `/api/jobs/`, the server's maximum.
+pub const JOB_PAGE_SIZE: usize = 2000;
+/// Similar jobs requested per page.
+pub const SIMILAR_JOBS_PAGE_SIZE: usize = 50;
+/// Jobs fetched from a single push before giving up, so that a runaway push
+/// cannot exhaust memory.
+pub const DEFAULT_MAX_JOBS: usize = 50_000;
+
+/// Fetches up to `max_pushes` pushes matching `filter` (a query string such
+/// as `revision=abc` or `author=me`), newest first. Pages are walked with
+/// `id__lt` until a short page comes back.
+pub async fn fetch_pushes(
+    client: &HttpClient,
+    endpoints: &Endpoints,
+    repo: &str,
+    filter: &str,
+    max_pushes: usize,
+) -> Result<Vec<PushResult>> {
+    let mut pushes: Vec<PushResult> = Vec::new();
+
+    while pushes.len() < max_pushes {
+        let count = PUSH_PAGE_SIZE.min(max_pushes - pushes.len());
+        let mut url = format!(
+            "{}/api/project/{}/push/?full=true&count={}",
+            endpoints.treeherder, repo, count
+        );
+        if !filter.is_empty() {
+            url.push('&');
+            url.push_str(filter);
+        }
+        if let Some(last) = pushes.last() {
+            url.push_str(&format!("&id__lt={}", last.id));
+        }
+
+        let response: PushResponse = client.get_json(&url).await?;
+        let page_len = response.results.len();
+        pushes.extend(response.results);
+        if page_len < count {
+            break;
+        }
+    }
 
-    let response: PushResponse = client.get(&url).send().await?.json().await?;
+    pushes.truncate(max_pushes);
+    Ok(pushes)
+}
 
-    response
-        .results
+pub async fn fetch_push_id(
+    client: &HttpClient,
+    endpoints: &Endpoints,
+    repo: &str,
+    revision: &str,
+) -> Result<u64> {
+    let filter = format!("revision={}", revision);
+    fetch_pushes(client, endpoints, repo, &filter, 1)
+        .await?
         .first()
         .map(|r| r.id)
         .ok_or_else(|| anyhow::anyhow!("No push found for revision"))
 }
 
-pub async fn fetch_jobs(client: &Client, push_id: u64) -> Result<Vec<Job>> {
-    let url = format!(
-        "https://treeherder.mozilla.org/api/jobs/?push_id={}",
-        push_id
-    );
+/// Fetches the jobs of a push, following the `next` link of each page (or
+/// requesting the next page number when the server omits it) until every
+/// job or `max_jobs` jobs have been read. `progress` is called with the
+/// number of jobs read so far after each page.
+pub async fn fetch_jobs(
+    client: &HttpClient,
+    endpoints: &Endpoints,
+    push_id: u64,
+    max_jobs: usize,
+    progress: impl Fn(usize),
+) -> Result<Vec<Job>> {
+    fetch_job_pages(client, endpoints, push_id, None, max_jobs, progress).await
+}
+
+/// Fetches the jobs of a push modified after `since`, a `last_modified`
+/// timestamp of an earlier fetch.
+pub async fn fetch_jobs_modified_since(
+    client: &HttpClient,
+    endpoints: &Endpoints,
+    push_id: u64,
+    since: &str,
+    max_jobs: usize,
+    progress: impl Fn(usize),
+) -> Result<Vec<Job>> {
+    fetch_job_pages(client, endpoints, push_id, Some(since), max_jobs, progress).await
+}
+
+async fn fetch_job_pages(
+    client: &HttpClient,
+    endpoints: &Endpoints,
+    push_id: u64,
+    modified_since: Option<&str>,
+    max_jobs: usize,
+    progress: impl Fn(usize),
+) -> Result<Vec<Job>> {
+    let page_size = JOB_PAGE_SIZE.min(max_jobs.max(1));
+    let page_url = |page: usize| {
+        let mut url = format!(
+            "{}/api/jobs/?push_id={}&count={}",
+            endpoints.treeherder, push_id, page_size
+        );
+        if let Some(since) = modified_since {
+            url.push_str("&last_modified__gt=");
+            url.extend(url::form_urlencoded::byte_serialize(since.as_bytes()));
+        }
+        if page > 1 {
+            url.push_str(&format!("&page={}", page));
+        }
+        url
+    };
+
+    let mut jobs = Vec::new();
+    let mut page = 1;
+    let mut next_url = Some(page_url(page));
+
+    while let Some(url) = next_url.take() {
+        let mut response: JobsResponse = client.get_json(&url).await?;
+        let page_len = response.results.len();
+        let next = response.next.take();
+        jobs.extend(parse_jobs(response));
+        jobs.truncate(max_jobs);
+        progress(jobs.len());
+
+        if jobs.len() >= max_jobs || page_len == 0 {
+            break;
+        }
+        next_url = match next {
+            Some(next) => Some(next),
+            None if page_len >= page_size => {
+                page += 1;
+                Some(page_url(page))
+            }
+            None => None,
+        };
+    }
 
-    let response: JobsResponse = client.get(&url).send().await?.json().await?;
+    Ok(jobs)
+}
 
+/// Turns a page of job rows into jobs, looking columns up by name in
+/// `job_property_names`.
+fn parse_jobs(response: JobsResponse) -> Vec<Job> {
     // Build field name → index mapping from job_property_names
     let field_map: HashMap<&str, usize> = response
         .job_property_names
@@ -96,7 +349,7 @@ pub async fn fetch_jobs(client: &Client, push_id: u64) -> Result<Vec<Job>> {
         .collect();
 
     let mut jobs = Vec::new();
-    for job_array in response.results {
+    for job_array in &response.results {
         // Helper to safely get field by name for this specific job_array
         let get_field = |field_name: &str| -> Option<&serde_json::Value> {
             field_map
@@ -125,10 +378,10 @@ pub async fn fetch_jobs(client: &Client, push_id: u64) -> Result<Vec<Job>> {
                 .map(|s| s.to_string()),
             get_field("result")
                 .and_then(|v| v.as_str())
-                .map(|s| s.to_string()),
+                .map(|s| JobResult::from(s.to_string())),
             get_field("state")
                 .and_then(|v| v.as_str())
-                .map(|s| s.to_string()),
+                .map(|s| JobState::from(s.to_string())),
         ) {
             let platform_option = get_field("platform_option")
                 .and_then(|v| v.as_str())
@@ -137,8 +390,18 @@ pub async fn fetch_jobs(client: &Client, push_id: u64) -> Result<Vec<Job>> {
 
             let duration = get_field("duration").and_then(|v| v.as_u64());
 
-            let failure_classification_id =
-                get_field("failure_classification_id").and_then(|v| v.as_u64());
+            let failure_classification = get_field("failure_classification_id")
+                .and_then(|v| v.as_u64())
+                .map(FailureClassification::from);
+
+            let text = |name: &str| {
+                get_field(name)
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string())
+            };
+            // Treeherder reports 0 for timestamps a job has not reached yet.
+            let timestamp =
+                |name: &str| get_field(name).and_then(|v| v.as_u64()).filter(|&t| t > 0);
 
             jobs.push(Job {
                 id,
@@ -148,70 +411,210 @@ pub async fn fetch_jobs(client: &Client, push_id: u64) -> Result<Vec<Job>> {
                 platform_option,
                 result,
                 state,
-                failure_classification_id,
+                failure_classification,
                 duration,
+                tier: get_field("tier").and_then(|v| v.as_u64()),
+                job_group_name: text("job_group_name").unwrap_or_default(),
+                job_group_symbol: text("job_group_symbol").unwrap_or_default(),
+                task_id: text("task_id"),
+                retry_id: get_field("retry_id").and_then(|v| v.as_u64()),
+                submit_timestamp: timestamp("submit_timestamp"),
+                start_timestamp: timestamp("start_timestamp"),
+                end_timestamp: timestamp("end_timestamp"),
+                who: text("who"),
+                last_modified: text("last_modified"),
+                retries: vec![],
             });
         }
     }
 
-    Ok(jobs)
+    jobs
+}
+
+pub async fn fetch_push_health(
+    client: &HttpClient,
+    endpoints: &Endpoints,
+    repo: &str,
+    revision: &str,
+) -> Result<PushHealth> {
+    let url = format!(
+        "{}/api/project/{}/push/health/?revision={}",
+        endpoints.treeherder, repo, revision
+    );
+    client.get_json(&url).await
+}
+
+/// ID of the decision task of a push, which is also the task group of its
+/// jobs.
+pub async fn fetch_decision_task_id(
+    client: &HttpClient,
+    endpoints: &Endpoints,
+    repo: &str,
+    push_id: u64,
+) -> Result<String> {
+    let url = format!(
+        "{}/api/project/{}/push/decisiontask/?push_ids={}",
+        endpoints.treeherder, repo, push_id
+    );
+    let mut tasks: HashMap<String, DecisionTask> = client.get_json(&url).await?;
+    tasks
+        .remove(&push_id.to_string())
+        .map(|task| task.id)
+        .ok_or_else(|| anyhow::anyhow!("No decision task found for push {}", push_id))
+}
+
+pub async fn fetch_actions_json(
+    client: &HttpClient,
+    endpoints: &Endpoints,
+    decision_task_id: &str,
+) -> Result<ActionsJson> {
+    let url = format!(
+        "{}/api/queue/v1/task/{}/artifacts/public/actions.json",
+        endpoints.taskcluster, decision_task_id
+    );
+    client.get_json(&url).await
 }
 
-pub async fn fetch_job_details(client: &Client, repo: &str, job_id: u64) -> Result<JobDetail> {
+/// Triggers a Taskcluster hook with `payload` and returns the ID of the
+/// task it created.
+pub async fn trigger_hook(
+    client: &HttpClient,
+    endpoints: &Endpoints,
+    credentials: &Credentials,
+    hook_group_id: &str,
+    hook_id: &str,
+    payload: &serde_json::Value,
+) -> Result<String> {
     let url = format!(
-        "https://treeherder.mozilla.org/api/project/{}/jobs/{}/",
-        repo, job_id
+        "{}/api/hooks/v1/hooks/{}/{}/trigger",
+        endpoints.taskcluster,
+        url::form_urlencoded::byte_serialize(hook_group_id.as_bytes()).collect::<String>(),
+        url::form_urlencoded::byte_serialize(hook_id.as_bytes()).collect::<String>()
     );
+    let authorization = credentials.taskcluster_authorization("POST", &url)?;
+    let response: HookTriggerResponse = client
+        .post_json(&url, payload, &[("Authorization", authorization)])
+        .await?;
+    Ok(response.status.task_id)
+}
 
-    let job_detail: JobDetail = client.get(&url).send().await?.json().await?;
+/// Logs in to Treeherder the way its UI does, exchanging the tokens of
+/// `credentials` for a session.
+pub async fn treeherder_login(
+    client: &HttpClient,
+    endpoints: &Endpoints,
+    credentials: &TreeherderCredentials,
+) -> Result<TreeherderSession> {
+    let url = format!("{}/api/auth/login/", endpoints.treeherder);
+    let headers = client
+        .get_response_headers(&url, &credentials.login_headers())
+        .await
+        .context("Could not log in to Treeherder")?;
+    TreeherderSession::from_cookies(
+        headers
+            .get_all(SET_COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok()),
+    )
+}
+
+/// Classifies the failure of a job on Treeherder, linking it to `bug` when
+/// given.
+pub async fn post_classification(
+    client: &HttpClient,
+    endpoints: &Endpoints,
+    repo: &str,
+    session: &TreeherderSession,
+    job_id: u64,
+    classification: &Classification,
+) -> Result<()> {
+    let headers = session.headers(&endpoints.treeherder);
+    let url = format!("{}/api/project/{}/note/", endpoints.treeherder, repo);
+    let note = serde_json::json!({
+        "job_id": job_id,
+        "failure_classification_id": classification.classification.id(),
+        "text": classification.comment,
+    });
+    client
+        .post_json::<serde_json::Value>(&url, &note, &headers)
+        .await?;
+
+    if let Some(bug_id) = classification.bug {
+        let url = format!("{}/api/project/{}/bug-job-map/", endpoints.treeherder, repo);
+        let bug_job_map = serde_json::json!({
+            "job_id": job_id,
+            "bug_id": bug_id,
+            "type": "annotation",
+        });
+        client
+            .post_json::<serde_json::Value>(&url, &bug_job_map, &headers)
+            .await?;
+    }
+    Ok(())
+}
+
+pub async fn fetch_job_details(
+    client: &HttpClient,
+    endpoints: &Endpoints,
+    repo: &str,
+    job_id: u64,
+) -> Result<JobDetail> {
+    let url = format!(
+        "{}/api/project/{}/jobs/{}/",
+        endpoints.treeherder, repo, job_id
+    );
+
+    let job_detail: JobDetail = client.get_json(&url).await?;
 
     Ok(job_detail)
 }
 
 pub async fn fetch_job_details_extended(
-    client: &Client,
+    client: &HttpClient,
+    endpoints: &Endpoints,
     repo: &str,
     job_id: u64,
 ) -> Result<JobDetailExtended> {
     let url = format!(
-        "https://treeherder.mozilla.org/api/project/{}/jobs/{}/",
-        repo, job_id
+        "{}/api/project/{}/jobs/{}/",
+        endpoints.treeherder, repo, job_id
     );
 
-    let job_detail: JobDetailExtended = client.get(&url).send().await?.json().await?;
+    let job_detail: JobDetailExtended = client.get_json(&url).await?;
 
     Ok(job_detail)
 }
 
 pub async fn fetch_taskcluster_artifacts(
-    client: &Client,
+    client: &HttpClient,
+    endpoints: &Endpoints,
     task_id: &str,
     retry_id: u64,
 ) -> Result<Vec<TaskclusterArtifact>> {
     let url = format!(
-        "https://firefox-ci-tc.services.mozilla.com/api/queue/v1/task/{}/runs/{}/artifacts",
-        task_id, retry_id
+        "{}/api/queue/v1/task/{}/runs/{}/artifacts",
+        endpoints.taskcluster, task_id, retry_id
     );
 
-    let response: TaskclusterArtifactsResponse = client.get(&url).send().await?.json().await?;
+    let response: TaskclusterArtifactsResponse = client.get_json(&url).await?;
 
     Ok(response.artifacts)
 }
 
 pub async fn download_artifact(
-    client: &Client,
+    client: &HttpClient,
+    endpoints: &Endpoints,
     task_id: &str,
     retry_id: u64,
     artifact_name: &str,
     output_dir: &Path,
 ) -> Result<PathBuf> {
     let url = format!(
-        "https://firefox-ci-tc.services.mozilla.com/api/queue/v1/task/{}/runs/{}/artifacts/{}",
-        task_id, retry_id, artifact_name
+        "{}/api/queue/v1/task/{}/runs/{}/artifacts/{}",
+        endpoints.taskcluster, task_id, retry_id, artifact_name
     );
 
-    let response = client.get(&url).send().await?;
-    let bytes = response.bytes().await?;
+    let bytes = client.get_bytes(&url).await?;
 
     let artifact_path = output_dir.join(artifact_name);
     if let Some(parent) = artifact_path.parent() {
@@ -224,22 +627,24 @@ pub async fn download_artifact(
 }
 
 pub async fn download_job_artifacts(
-    client: &Client,
+    client: &HttpClient,
+    endpoints: &Endpoints,
     repo: &str,
     job: &Job,
     output_dir: &Path,
     artifact_pattern: Option<&Regex>,
-) -> Result<Vec<String>> {
-    let job_detail = fetch_job_details_extended(client, repo, job.id).await?;
+) -> Result<(Vec<String>, Vec<FetchError>)> {
+    let job_detail = fetch_job_details_extended(client, endpoints, repo, job.id).await?;
 
     let (task_id, retry_id) = match (job_detail.task_id, job_detail.retry_id) {
         (Some(tid), Some(rid)) => (tid, rid),
-        _ => return Ok(vec![]),
+        _ => return Ok((vec![], vec![])),
     };
 
-    let artifacts = fetch_taskcluster_artifacts(client, &task_id, retry_id).await?;
+    let artifacts = fetch_taskcluster_artifacts(client, endpoints, &task_id, retry_id).await?;
 
     let mut downloaded = Vec::new();
+    let mut fetch_errors = Vec::new();
 
     let job_dir = output_dir.join(format!("job-{}", job.id));
     fs::create_dir_all(&job_dir)?;
@@ -251,22 +656,31 @@ pub async fn download_job_artifacts(
             }
         }
 
-        match download_artifact(client, &task_id, retry_id, &artifact.name, &job_dir).await {
+        match download_artifact(
+            client,
+            endpoints,
+            &task_id,
+            retry_id,
+            &artifact.name,
+            &job_dir,
+        )
+        .await
+        {
             Ok(path) => {
                 downloaded.push(path.display().to_string());
             }
             Err(e) => {
-                eprintln!("Failed to download {}: {}", artifact.name, e);
+                fetch_errors.push(FetchError::log(job, &artifact.name, &e));
             }
         }
     }
 
-    Ok(downloaded)
+    Ok((downloaded, fetch_errors))
 }
 
-pub async fn fetch_error_summary(client: &Client, log_url: &str) -> Result<Vec<ErrorLine>> {
+pub async fn fetch_error_summary(client: &HttpClient, log_url: &str) -> Result<Vec<ErrorLine>> {
     if log_url.contains("errorsummary") {
-        let response = client.get(log_url).send().await?.text().await?;
+        let response = client.get_text(log_url).await?;
 
         let mut errors = Vec::new();
         for line in response.lines() {
@@ -285,40 +699,173 @@ pub async fn fetch_error_summary(client: &Client, log_url: &str) -> Result<Vec<E
 }
 
 pub async fn fetch_job_details_with_errors(
-    client: &Client,
+    client: &HttpClient,
+    endpoints: &Endpoints,
     repo: &str,
     job: Job,
-) -> Result<(Job, Vec<ErrorLine>)> {
-    let job_detail = fetch_job_details(client, repo, job.id).await?;
+) -> Result<(JobWithLogs, Vec<FetchError>)> {
+    let job_detail = fetch_job_details(client, endpoints, repo, job.id).await?;
+    let (mut all_errors, mut fetch_errors) =
+        fetch_error_summaries(client, &job, &job_detail.logs).await;
+    add_bug_suggestions(
+        client,
+        endpoints,
+        repo,
+        &job,
+        &mut all_errors,
+        &mut fetch_errors,
+    )
+    .await;
+    let (classification_notes, classified_bugs) =
+        fetch_classification(client, endpoints, repo, &job, &mut fetch_errors).await;
+
+    let job_with_logs = JobWithLogs {
+        job,
+        errors: all_errors,
+        log_matches: vec![],
+        log_dir: None,
+        classification_notes,
+        classified_bugs,
+    };
 
-    let error_futures: Vec<_> = job_detail
-        .logs
+    Ok((job_with_logs, fetch_errors))
+}
+
+pub async fn fetch_classification_notes(
+    client: &HttpClient,
+    endpoints: &Endpoints,
+    repo: &str,
+    job_id: u64,
+) -> Result<Vec<ClassificationNote>> {
+    let url = format!(
+        "{}/api/project/{}/note/?job_id={}",
+        endpoints.treeherder, repo, job_id
+    );
+    client.get_json(&url).await
+}
+
+pub async fn fetch_classified_bugs(
+    client: &HttpClient,
+    endpoints: &Endpoints,
+    repo: &str,
+    job_id: u64,
+) -> Result<Vec<ClassifiedBug>> {
+    let url = format!(
+        "{}/api/project/{}/bug-job-map/?job_id={}",
+        endpoints.treeherder, repo, job_id
+    );
+    client.get_json(&url).await
+}
+
+/// The notes and bugs `job` was classified with, newest first, recording in
+/// `fetch_errors` those that could not be fetched. Nothing is fetched for
+/// jobs nobody has classified.
+async fn fetch_classification(
+    client: &HttpClient,
+    endpoints: &Endpoints,
+    repo: &str,
+    job: &Job,
+    fetch_errors: &mut Vec<FetchError>,
+) -> (Vec<ClassificationNote>, Vec<ClassifiedBug>) {
+    if job
+        .failure_classification
+        .is_none_or(|c| c == FailureClassification::NotClassified)
+    {
+        return (vec![], vec![]);
+    }
+
+    let (notes, bugs) = futures::join!(
+        fetch_classification_notes(client, endpoints, repo, job.id),
+        fetch_classified_bugs(client, endpoints, repo, job.id)
+    );
+    let mut notes = notes.unwrap_or_else(|e| {
+        fetch_errors.push(FetchError::log(job, "note", &e));
+        vec![]
+    });
+    let mut bugs = bugs.unwrap_or_else(|e| {
+        fetch_errors.push(FetchError::log(job, "bug-job-map", &e));
+        vec![]
+    });
+    notes.sort_by(|a, b| b.created.cmp(&a.created));
+    bugs.sort_by(|a, b| b.created.cmp(&a.created));
+    (notes, bugs)
+}
+
+pub async fn fetch_bug_suggestions(
+    client: &HttpClient,
+    endpoints: &Endpoints,
+    repo: &str,
+    job_id: u64,
+) -> Result<Vec<BugSuggestions>> {
+    let url = format!(
+        "{}/api/project/{}/jobs/{}/bug_suggestions/",
+        endpoints.treeherder, repo, job_id
+    );
+    client.get_json(&url).await
+}
+
+/// Attaches Treeherder's bug suggestions to the `errors` of `job`, or
+/// records in `fetch_errors` that they could not be fetched. Nothing is
+/// fetched for jobs without errors.
+async fn add_bug_suggestions(
+    client: &HttpClient,
+    endpoints: &Endpoints,
+    repo: &str,
+    job: &Job,
+    errors: &mut [ErrorLine],
+    fetch_errors: &mut Vec<FetchError>,
+) {
+    if errors.is_empty() {
+        return;
+    }
+    match fetch_bug_suggestions(client, endpoints, repo, job.id).await {
+        Ok(suggestions) => {
+            for error in errors {
+                error.add_bug_suggestions(&suggestions);
+            }
+        }
+        Err(e) => fetch_errors.push(FetchError::log(job, "bug_suggestions", &e)),
+    }
+}
+
+/// Fetches the failures from every errorsummary log of a job, along with the
+/// logs that could not be fetched.
+async fn fetch_error_summaries(
+    client: &HttpClient,
+    job: &Job,
+    logs: &[LogReference],
+) -> (Vec<ErrorLine>, Vec<FetchError>) {
+    let error_logs: Vec<_> = logs
         .iter()
         .filter(|log_ref| log_ref.name.contains("error") || log_ref.name.contains("summary"))
-        .map(|log_ref| fetch_error_summary(client, &log_ref.url))
         .collect();
 
-    let error_results = futures::future::join_all(error_futures).await;
+    let error_results = futures::future::join_all(
+        error_logs
+            .iter()
+            .map(|log_ref| fetch_error_summary(client, &log_ref.url)),
+    )
+    .await;
 
     let mut all_errors = Vec::new();
-    for result in error_results {
+    let mut fetch_errors = Vec::new();
+    for (log_ref, result) in error_logs.iter().zip(error_results) {
         match result {
             Ok(errors) => all_errors.extend(errors),
-            Err(e) => eprintln!("Failed to fetch error summary: {}", e),
+            Err(e) => fetch_errors.push(FetchError::log(job, &log_ref.name, &e)),
         }
     }
 
-    Ok((job, all_errors))
+    (all_errors, fetch_errors)
 }
 
 pub async fn fetch_and_save_log(
-    client: &Client,
+    client: &HttpClient,
     log_url: &str,
     log_name: &str,
     job_dir: &Path,
 ) -> Result<PathBuf> {
-    let response = client.get(log_url).send().await?;
-    let content = response.text().await?;
+    let content = client.get_bytes(log_url).await?;
 
     let log_path = job_dir.join(format!("{}.log", log_name));
     fs::write(&log_path, content)?;
@@ -327,29 +874,29 @@ pub async fn fetch_and_save_log(
 }
 
 pub async fn fetch_job_with_full_logs(
-    client: &Client,
+    client: &HttpClient,
+    endpoints: &Endpoints,
     repo: &str,
     job: Job,
     temp_dir: &Path,
     pattern: Option<&Regex>,
-) -> Result<JobWithLogs> {
-    let job_detail = fetch_job_details(client, repo, job.id).await?;
+) -> Result<(JobWithLogs, Vec<FetchError>)> {
+    let job_detail = fetch_job_details(client, endpoints, repo, job.id).await?;
 
     let job_dir = temp_dir.join(format!("job_{}", job.id));
     fs::create_dir_all(&job_dir)?;
 
-    let error_futures: Vec<_> = job_detail
-        .logs
-        .iter()
-        .filter(|log_ref| log_ref.name.contains("error") || log_ref.name.contains("summary"))
-        .map(|log_ref| fetch_error_summary(client, &log_ref.url))
-        .collect();
-
-    let error_results = futures::future::join_all(error_futures).await;
-    let mut all_errors = Vec::new();
-    for errors in error_results.into_iter().flatten() {
-        all_errors.extend(errors);
-    }
+    let (mut all_errors, mut fetch_errors) =
+        fetch_error_summaries(client, &job, &job_detail.logs).await;
+    add_bug_suggestions(
+        client,
+        endpoints,
+        repo,
+        &job,
+        &mut all_errors,
+        &mut fetch_errors,
+    )
+    .await;
 
     let log_futures: Vec<_> = job_detail
         .logs
@@ -360,52 +907,57 @@ pub async fn fetch_job_with_full_logs(
     let log_results = futures::future::join_all(log_futures).await;
 
     let mut log_matches = Vec::new();
-    if let Some(regex) = pattern {
-        for (log_ref, log_path) in job_detail
-            .logs
-            .iter()
-            .zip(log_results.iter().filter_map(|r| r.as_ref().ok()))
-        {
-            if let Ok(matches) = search_log_file(log_path, regex, &log_ref.name) {
-                log_matches.extend(matches);
+    for (log_ref, result) in job_detail.logs.iter().zip(log_results) {
+        match result {
+            Ok(log_path) => {
+                if let Some(regex) = pattern {
+                    if let Ok(matches) = search_log_file(&log_path, regex, &log_ref.name) {
+                        log_matches.extend(matches);
+                    }
+                }
             }
+            Err(e) => fetch_errors.push(FetchError::log(&job, &log_ref.name, &e)),
         }
     }
 
-    Ok(JobWithLogs {
+    let (classification_notes, classified_bugs) =
+        fetch_classification(client, endpoints, repo, &job, &mut fetch_errors).await;
+
+    let job_with_logs = JobWithLogs {
         job,
         errors: all_errors,
         log_matches,
         log_dir: Some(job_dir.to_string_lossy().to_string()),
-    })
+        classification_notes,
+        classified_bugs,
+    };
+
+    Ok((job_with_logs, fetch_errors))
 }
 
-pub async fn fetch_job_perf_data(client: &Client, repo: &str, job: &Job) -> Result<JobPerfData> {
-    let job_detail = fetch_job_details_extended(client, repo, job.id).await?;
+pub async fn fetch_job_perf_data(
+    client: &HttpClient,
+    endpoints: &Endpoints,
+    repo: &str,
+    job: &Job,
+) -> Result<JobPerfData> {
+    let job_detail = fetch_job_details_extended(client, endpoints, repo, job.id).await?;
 
     let perf_data = if let (Some(task_id), Some(retry_id)) =
         (job_detail.task_id, job_detail.retry_id)
     {
         let perf_url = format!(
-            "https://firefox-ci-tc.services.mozilla.com/api/queue/v1/task/{}/runs/{}/artifacts/public/test_info/perfherder-data-resource-usage.json",
-            task_id, retry_id
+            "{}/api/queue/v1/task/{}/runs/{}/artifacts/public/test_info/perfherder-data-resource-usage.json",
+            endpoints.taskcluster, task_id, retry_id
         );
 
-        match client.get(&perf_url).send().await {
-            Ok(response) => {
-                if let Ok(text) = response.text().await {
-                    if let Ok(redirect_info) = serde_json::from_str::<serde_json::Value>(&text) {
-                        if let Some(url) = redirect_info.get("url").and_then(|u| u.as_str()) {
-                            if let Ok(perf_response) = client.get(url).send().await {
-                                (perf_response.json::<PerfherderData>().await).ok()
-                            } else {
-                                None
-                            }
-                        } else {
-                            serde_json::from_str::<PerfherderData>(&text).ok()
-                        }
+        match client.get_text(&perf_url).await {
+            Ok(text) => {
+                if let Ok(redirect_info) = serde_json::from_str::<serde_json::Value>(&text) {
+                    if let Some(url) = redirect_info.get("url").and_then(|u| u.as_str()) {
+                        client.get_json::<PerfherderData>(url).await.ok()
                     } else {
-                        None
+                        serde_json::from_str::<PerfherderData>(&text).ok()
                     }
                 } else {
                     None
@@ -426,35 +978,47 @@ pub async fn fetch_job_perf_data(client: &Client, repo: &str, job: &Job) -> Resu
 }
 
 pub async fn fetch_similar_jobs(
-    client: &Client,
+    client: &HttpClient,
+    endpoints: &Endpoints,
     repo: &str,
     job_id: u64,
     count: usize,
 ) -> Result<SimilarJobHistory> {
-    let url = format!(
-        "https://treeherder.mozilla.org/api/project/{}/jobs/{}/similar_jobs/?count={}",
-        repo, job_id, count
-    );
+    let mut results: Vec<SimilarJob> = Vec::new();
+    let mut repository = repo.to_string();
+
+    while results.len() < count {
+        let page_size = SIMILAR_JOBS_PAGE_SIZE.min(count - results.len());
+        let url = format!(
+            "{}/api/project/{}/jobs/{}/similar_jobs/?count={}&offset={}",
+            endpoints.treeherder,
+            repo,
+            job_id,
+            page_size,
+            results.len()
+        );
 
-    let response: SimilarJobsResponse = client.get(&url).send().await?.json().await?;
+        let response: SimilarJobsResponse = client.get_json(&url).await?;
+        let page_len = response.results.len();
+        repository = response.meta.repository;
+        results.extend(response.results);
+        if page_len < page_size {
+            break;
+        }
+    }
+    results.truncate(count);
 
-    let job_type_name = response
-        .results
+    let job_type_name = results
         .first()
         .map(|j| j.job_type_name.clone())
         .unwrap_or_default();
 
-    let pass_count = response
-        .results
-        .iter()
-        .filter(|j| j.result == "success")
-        .count();
-    let fail_count = response
-        .results
+    let pass_count = results
         .iter()
-        .filter(|j| j.result == "testfailed" || j.result == "busted")
+        .filter(|j| j.result == JobResult::Success)
         .count();
-    let total = response.results.len();
+    let fail_count = results.iter().filter(|j| j.result.is_failure()).count();
+    let total = results.len();
     let pass_rate = if total > 0 {
         (pass_count as f64 / total as f64) * 100.0
     } else {
@@ -464,12 +1028,12 @@ pub async fn fetch_similar_jobs(
     Ok(SimilarJobHistory {
         job_id,
         job_type_name,
-        repo: response.meta.repository,
+        repo: repository,
         total_jobs: total,
         pass_count,
         fail_count,
         pass_rate,
-        jobs: response.results,
+        jobs: results,
     })
 }
 
@@ -1,4 +1,4 @@
-use crate::models::{CachedPushMetadata, Job, JobWithLogs, LogMatch};
+use crate::models::{CachedPushMetadata, FetchError, Job, JobWithLogs, LogMatch};
 use anyhow::Result;
 use regex::Regex;
 use std::fs;
@@ -22,14 +22,16 @@ pub fn search_cached_logs(
     cache_dir: &Path,
     jobs: &[Job],
     pattern: Option<&Regex>,
-) -> Result<Vec<JobWithLogs>> {
+) -> Result<(Vec<JobWithLogs>, Vec<FetchError>)> {
     let mut results = Vec::new();
+    let mut fetch_errors = Vec::new();
 
     for job in jobs {
         let job_dir = cache_dir.join(format!("job_{}", job.id));
 
         if !job_dir.exists() {
-            eprintln!("Warning: Job directory not found: {}", job_dir.display());
+            let error = anyhow::anyhow!("Job directory not found: {}", job_dir.display());
+            fetch_errors.push(FetchError::job(job, &error));
             continue;
         }
 
@@ -58,10 +60,12 @@ pub fn search_cached_logs(
             errors: vec![],
             log_matches,
             log_dir: Some(job_dir.to_string_lossy().to_string()),
+            classification_notes: vec![],
+            classified_bugs: vec![],
         });
     }
 
-    Ok(results)
+    Ok((results, fetch_errors))
 }
 
 fn search_log_file(log_path: &PathBuf, pattern: &Regex, log_name: &str) -> Result<Vec<LogMatch>> {
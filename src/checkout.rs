@@ -0,0 +1,65 @@
+//! Finding the commit checked out in a local Git or Mercurial clone, so that
+//! the revision does not have to be copied from the terminal.
+
+use anyhow::Result;
+use std::path::Path;
+use std::process::Command;
+
+/// Git-cinnabar's answer for a commit it has no Mercurial hash for.
+const NULL_REVISION: &str = "0000000000000000000000000000000000000000";
+
+/// The commit checked out in a clone and who made it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkout {
+    /// Mercurial hash of the commit when it is known, since that is what
+    /// Treeherder lists for Mozilla repositories; the Git hash otherwise.
+    pub revision: String,
+    /// Email address the clone commits with, used to find the pushes of
+    /// its author.
+    pub author: Option<String>,
+}
+
+impl Checkout {
+    /// Reads the working directory commit of the clone `dir` belongs to.
+    /// In Git clones made with git-cinnabar, the commit is mapped to its
+    /// Mercurial hash.
+    pub fn detect(dir: &Path) -> Result<Self> {
+        if let Some(git_revision) = run(dir, "git", &["rev-parse", "HEAD"]) {
+            let revision = run(dir, "git", &["cinnabar", "git2hg", &git_revision])
+                .filter(|hg_revision| hg_revision != NULL_REVISION)
+                .unwrap_or(git_revision);
+            return Ok(Self {
+                revision,
+                author: run(dir, "git", &["config", "user.email"]),
+            });
+        }
+
+        if let Some(revision) = run(dir, "hg", &["log", "-r", ".", "-T", "{node}"]) {
+            let author = run(dir, "hg", &["config", "ui.username"]).map(|username| {
+                match (username.find('<'), username.rfind('>')) {
+                    (Some(start), Some(end)) if start < end => username[start + 1..end].to_string(),
+                    _ => username,
+                }
+            });
+            return Ok(Self { revision, author });
+        }
+
+        anyhow::bail!(
+            "{} is not in a Git or Mercurial checkout; pass a revision or Treeherder URL",
+            dir.display()
+        )
+    }
+}
+
+/// Standard output of `program` run in `dir`, trimmed, if it ran and
+/// succeeded with some output.
+fn run(dir: &Path, program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    let stdout = stdout.trim();
+    (output.status.success() && !stdout.is_empty()).then(|| stdout.to_string())
+}
@@ -1,11 +1,22 @@
-use clap::{Parser, ValueEnum};
-
-#[derive(Debug, Clone, ValueEnum)]
-pub enum MatchFilter {
-    Failure,
-    Success,
-    All,
-}
+use crate::config::{apply_settings, requested_profile, Config};
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use std::path::Path;
+use treeherder_cli::actions::{TaskclusterCredentials, TreeherderCredentials};
+use treeherder_cli::api::{
+    parse_revision_input, DEFAULT_LANDO_URL, DEFAULT_MAX_JOBS, DEFAULT_TASKCLUSTER_URL,
+    DEFAULT_TREEHERDER_URL,
+};
+use treeherder_cli::http::{DEFAULT_MAX_CONNECTIONS_PER_HOST, DEFAULT_MAX_RETRIES};
+use treeherder_cli::models::{FailureClassification, JobResult};
+use treeherder_cli::notify::{MatrixAccount, DEFAULT_MATRIX_HOMESERVER};
+use treeherder_cli::{
+    Classification, Credentials, Expr, JobFilter, MatchFilter, NotifyTarget, PushQuery, PushRange,
+    WatchCondition,
+};
 
 #[derive(Debug, Clone, ValueEnum)]
 pub enum GroupBy {
@@ -15,101 +26,788 @@ pub enum GroupBy {
 #[derive(Parser, Debug)]
 #[command(
     name = "treeherder-cli",
-    about = "Fetch and summarize Treeherder test results for Firefox developers"
+    about = "Fetch and summarize Treeherder test results for Firefox developers",
+    after_help = "Exit codes: 0 no failures, 1 error, 2 failures found, 3 jobs still pending, \
+                  4 new failures in compare"
 )]
-pub struct Args {
+pub struct Cli {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// `treeherder-cli <REV>` without a subcommand is the same as `summary`.
+    #[command(flatten)]
+    pub summary: SummaryArgs,
+}
+
+impl Cli {
+    /// Like `Cli::parse`, but takes defaults from the config file and the
+    /// selected profile, and rejects the top-level summary options when a
+    /// subcommand is given, since they would otherwise be silently ignored.
+    pub fn parse_checked() -> anyhow::Result<Self> {
+        let config = Config::load()?;
+        let settings = config.settings(requested_profile(std::env::args_os()).as_deref())?;
+        let mut command = apply_settings(Self::command(), &settings)?;
+        let matches = command
+            .try_get_matches_from_mut(std::env::args_os())
+            .unwrap_or_else(|e| exit_with(e));
+
+        if let Some((name, _)) = matches.subcommand() {
+            let summary_only = SummaryArgs::augment_args(clap::Command::new("summary"));
+            let conflicting = summary_only
+                .get_arguments()
+                .find(|arg| {
+                    matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine)
+                })
+                .map(|arg| match arg.get_long() {
+                    Some(long) => format!("--{}", long),
+                    None => arg.get_id().to_string().to_uppercase(),
+                });
+
+            if let Some(flag) = conflicting {
+                exit_with(command.error(
+                    ErrorKind::ArgumentConflict,
+                    format!("'{}' cannot be used before the '{}' subcommand", flag, name),
+                ));
+            }
+        }
+
+        let mut cli = Self::from_arg_matches(&matches).unwrap_or_else(|e| exit_with(e));
+        cli.global.repo_given = given_on_command_line(&matches, "repo");
+        Ok(cli)
+    }
+}
+
+fn given_on_command_line(matches: &ArgMatches, id: &str) -> bool {
+    matches.value_source(id) == Some(ValueSource::CommandLine)
+        || matches
+            .subcommand()
+            .is_some_and(|(_, sub)| given_on_command_line(sub, id))
+}
+
+/// Like `clap::Error::exit`, but usage errors exit with 1 like any other
+/// error, since 2 means failed jobs were found.
+fn exit_with(error: clap::Error) -> ! {
+    if !error.use_stderr() {
+        error.exit();
+    }
+    let _ = error.print();
+    std::process::exit(1)
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Summarize the failures of a push (default)
+    Summary(SummaryArgs),
+    /// Compare with another revision to show new failures
+    Compare(CompareArgs),
+    /// Show history for a job ID using similar_jobs API
+    History(HistoryArgs),
+    /// Show performance/resource usage data for jobs
+    Perf(PerfArgs),
+    /// Download job artifacts
+    Artifacts(ArtifactsArgs),
+    /// Fetch all logs for each job and search them
+    Logs(LogsArgs),
+    /// Poll until all jobs of one or more pushes complete, then summarize
+    Watch(WatchArgs),
+    /// Query logs previously stored with `logs --cache-dir`
+    Cache(CacheArgs),
+    /// Summarize a range of pushes, e.g. all of an author's recent pushes
+    Pushes(PushesArgs),
+    /// Show Treeherder's push health triage of the failures
+    Health(HealthArgs),
+    /// Run the selected jobs again (needs Taskcluster credentials)
+    Retrigger(RetriggerArgs),
+    /// Run the selected jobs on earlier pushes (needs Taskcluster credentials)
+    Backfill(BackfillArgs),
+    /// Cancel the selected jobs (needs Taskcluster credentials)
+    Cancel(CancelArgs),
+    /// Classify the failures of the selected jobs (needs a Treeherder token)
+    Classify(ClassifyArgs),
+}
+
+impl Command {
+    /// The revisions or URLs the command was given.
+    pub fn revision_inputs(&self) -> Vec<&str> {
+        let revision = match self {
+            Command::Summary(args) => &args.revision,
+            Command::Perf(args) => &args.revision,
+            Command::Artifacts(args) => &args.revision,
+            Command::Logs(args) => &args.revision,
+            Command::Watch(args) => return args.inputs.iter().map(String::as_str).collect(),
+            Command::Health(args) => &args.revision,
+            Command::Retrigger(args) => &args.action.revision,
+            Command::Backfill(args) => &args.action.revision,
+            Command::Cancel(args) => &args.action.revision,
+            Command::Classify(args) => &args.action.revision,
+            Command::Compare(args) => return vec![&args.input, &args.compare],
+            Command::History(_) | Command::Cache(_) | Command::Pushes(_) => return vec![],
+        };
+        revision.input.as_deref().into_iter().collect()
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct GlobalArgs {
     #[arg(
-        help = "Treeherder URL or revision hash (not needed with --use-cache)",
-        conflicts_with = "lando_job_id"
+        long,
+        global = true,
+        default_value = "try",
+        help = "Repository name [default: the one in the revision URL, else try]"
     )]
-    pub input: Option<String>,
-    #[arg(long, default_value = "try", help = "Repository name")]
     pub repo: String,
+    /// Whether `--repo` was given on the command line rather than defaulted.
+    #[arg(skip)]
+    pub repo_given: bool,
+    #[arg(long, global = true, help = "Output results in JSON format")]
+    pub json: bool,
     #[arg(
         long,
-        default_value_t = true,
-        help = "Show stack traces in error summaries"
+        global = true,
+        env = "TREEHERDER_URL",
+        default_value = DEFAULT_TREEHERDER_URL,
+        help = "Base URL of the Treeherder instance"
     )]
-    pub show_stack_traces: bool,
+    pub treeherder_url: String,
     #[arg(
         long,
-        help = "Only show jobs matching this regex pattern (applied to job_type_name)"
+        global = true,
+        env = "TASKCLUSTER_ROOT_URL",
+        default_value = DEFAULT_TASKCLUSTER_URL,
+        help = "Root URL of the Taskcluster deployment"
     )]
-    pub filter: Option<String>,
-    #[arg(long, help = "Fetch all logs for each job")]
-    pub fetch_logs: bool,
+    pub taskcluster_url: String,
     #[arg(
         long,
-        value_enum,
-        default_value = "failure",
-        help = "Filter which jobs to apply pattern matching on"
+        global = true,
+        env = "LANDO_URL",
+        default_value = DEFAULT_LANDO_URL,
+        help = "Base URL of the Lando API"
     )]
-    pub match_filter: MatchFilter,
+    pub lando_url: String,
     #[arg(
         long,
-        help = "Regex pattern to search for in logs (only used with --fetch-logs)"
+        global = true,
+        default_value_t = DEFAULT_MAX_RETRIES,
+        help = "Retries for requests failing with a network error, 429 or 5xx"
     )]
-    pub pattern: Option<String>,
+    pub max_retries: u32,
     #[arg(
         long,
-        help = "Directory to store/read cached logs (persistent storage, not temp)"
+        global = true,
+        default_value_t = DEFAULT_MAX_CONNECTIONS_PER_HOST,
+        help = "Maximum concurrent requests to a single host"
     )]
-    pub cache_dir: Option<String>,
+    pub max_connections_per_host: usize,
     #[arg(
         long,
-        help = "Use cached logs without downloading (requires --cache-dir)"
+        global = true,
+        default_value_t = DEFAULT_MAX_JOBS,
+        help = "Stop reading the jobs of a push after this many"
     )]
-    pub use_cache: bool,
-    #[arg(long, help = "Include jobs classified as intermittent")]
-    pub include_intermittent: bool,
-    #[arg(long, help = "Output results in JSON format")]
-    pub json: bool,
-    #[arg(long, help = "Poll until all jobs complete")]
-    pub watch: bool,
+    pub max_jobs: usize,
     #[arg(
         long,
-        default_value = "300",
-        help = "Polling interval in seconds (requires --watch)"
+        global = true,
+        env = "TREEHERDER_PROFILE",
+        help = "Profile of the config file to take defaults from"
+    )]
+    pub profile: Option<String>,
+    #[arg(
+        long,
+        global = true,
+        help = "Exit with an error when some jobs or logs could not be fetched"
     )]
-    pub watch_interval: u64,
+    pub fail_on_incomplete: bool,
+}
+
+impl GlobalArgs {
+    /// Switches to the repository the revision URLs of `command` name, if
+    /// any. It is an error for them to disagree with each other, or with
+    /// `--repo` when it was given on the command line.
+    pub fn take_repo_from(&mut self, command: &Command) -> anyhow::Result<()> {
+        let mut repos = command
+            .revision_inputs()
+            .into_iter()
+            .filter_map(|input| parse_revision_input(input).ok()?.repo);
+        let Some(repo) = repos.next() else {
+            return Ok(());
+        };
+        if let Some(other) = repos.find(|other| *other != repo) {
+            anyhow::bail!(
+                "The revisions are in different repositories: '{}' and '{}'",
+                repo,
+                other
+            );
+        }
+        if self.repo_given && self.repo != repo {
+            anyhow::bail!(
+                "The revision URL is in repository '{}', but --repo {} was given",
+                repo,
+                self.repo
+            );
+        }
+        self.repo = repo;
+        Ok(())
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct RevisionArgs {
+    #[arg(
+        help = "Treeherder URL, revision hash, or a local checkout directory such as '.' \
+                [default: the current directory]",
+        conflicts_with = "lando_job_id"
+    )]
+    pub input: Option<String>,
     #[arg(
         long,
-        help = "Send desktop notification when jobs complete (requires --watch)"
+        help = "Use a Lando job ID to fetch the commit hash (alternative to INPUT)",
+        conflicts_with = "input"
     )]
-    pub notify: bool,
+    pub lando_job_id: Option<u64>,
+}
+
+impl RevisionArgs {
+    /// The push these arguments identify, with the default job filter.
+    pub fn push(&self) -> anyhow::Result<PushQuery> {
+        Ok(match (&self.input, self.lando_job_id) {
+            (_, Some(lando_job_id)) => PushQuery::lando_job(lando_job_id),
+            (Some(input), None) if Path::new(input).is_dir() => PushQuery::checkout(input),
+            (Some(input), None) => PushQuery::new(input.clone()),
+            (None, None) => PushQuery::checkout(std::env::current_dir()?),
+        })
+    }
+
+    pub fn query(&self, filters: &JobFilterArgs) -> anyhow::Result<PushQuery> {
+        Ok(self.push()?.with_filter(filters.to_filter()))
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct JobFilterArgs {
+    #[arg(long, help = "Only show jobs whose job_type_name contains this text")]
+    pub filter: Option<String>,
+    #[arg(
+        long,
+        value_enum,
+        default_value = "failure",
+        help = "Filter which jobs to apply pattern matching on"
+    )]
+    pub match_filter: MatchFilter,
+    #[arg(
+        long,
+        value_delimiter = ',',
+        conflicts_with = "match_filter",
+        value_parser = PossibleValuesParser::new(JobResult::NAMES).map(JobResult::from),
+        help = "Only show jobs with one of these results, e.g. exception,retry"
+    )]
+    pub result: Vec<JobResult>,
+    #[arg(long, help = "Include jobs classified as intermittent")]
+    pub include_intermittent: bool,
     #[arg(long, help = "Only show jobs matching this platform regex pattern")]
     pub platform: Option<String>,
     #[arg(long, help = "Only show jobs that took longer than N seconds")]
     pub duration_min: Option<u64>,
+    #[arg(
+        long = "tier",
+        value_delimiter = ',',
+        value_parser = clap::value_parser!(u64).range(1..=3),
+        help = "Only show jobs of these tiers, e.g. 1 or 1,2"
+    )]
+    pub tiers: Vec<u64>,
+    #[arg(
+        long,
+        help = "Only show jobs in this group, by symbol (M) or name (Mochitests)"
+    )]
+    pub group: Option<String>,
+    #[arg(
+        long,
+        help = "Only show jobs whose symbol matches this glob, e.g. 'bc*'"
+    )]
+    pub symbol: Option<String>,
+    #[arg(
+        long = "classification",
+        id = "classification",
+        value_delimiter = ',',
+        conflicts_with = "include_intermittent",
+        value_parser = PossibleValuesParser::new(FailureClassification::NAMES)
+            .map(|name| FailureClassification::from_name(&name).expect("a possible value")),
+        help = "Only show jobs classified as one of these, e.g. intermittent,infra"
+    )]
+    pub classifications: Vec<FailureClassification>,
+    #[arg(
+        long = "where",
+        id = "where",
+        value_name = "EXPR",
+        value_parser = Expr::parse,
+        conflicts_with_all = ["match_filter", "result", "include_intermittent", "classification"],
+        help = "Only show jobs matching this expression, whatever their result unless it \
+                says otherwise, e.g. 'platform ~ \"linux.*\" and result in (testfailed, busted)'"
+    )]
+    pub where_expr: Option<Expr>,
+}
+
+impl JobFilterArgs {
+    /// With `--where`, the expression alone decides which results and
+    /// classifications are kept.
+    pub fn to_filter(&self) -> JobFilter {
+        let expression_only = self.where_expr.is_some();
+        JobFilter {
+            match_filter: if expression_only {
+                MatchFilter::All
+            } else {
+                self.match_filter
+            },
+            results: self.result.clone(),
+            name: self.filter.clone(),
+            platform: self.platform.clone(),
+            duration_min: self.duration_min,
+            tiers: self.tiers.clone(),
+            group: self.group.clone(),
+            symbol: self.symbol.clone(),
+            classifications: self.classifications.clone(),
+            include_intermittent: self.include_intermittent || expression_only,
+            expression: self.where_expr.clone(),
+        }
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ReportArgs {
+    #[arg(
+        long,
+        default_value_t = true,
+        help = "Show stack traces in error summaries"
+    )]
+    pub show_stack_traces: bool,
     #[arg(
         long,
         value_enum,
         help = "Group failures by test name across platforms"
     )]
     pub group_by: Option<GroupBy>,
-    #[arg(long, help = "Compare with another revision to show new failures")]
-    pub compare: Option<String>,
-    #[arg(long, help = "Download job artifacts")]
-    pub download_artifacts: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct SummaryArgs {
+    #[command(flatten)]
+    pub revision: RevisionArgs,
+    #[command(flatten)]
+    pub filters: JobFilterArgs,
+    #[command(flatten)]
+    pub report: ReportArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct CompareArgs {
+    #[arg(help = "Treeherder URL or revision hash to check")]
+    pub input: String,
+    #[arg(help = "Treeherder URL or revision hash to compare against")]
+    pub compare: String,
+    #[arg(long, help = "Include jobs classified as intermittent")]
+    pub include_intermittent: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct HistoryArgs {
+    #[arg(help = "Job ID whose similar jobs to fetch")]
+    pub job_id: u64,
+    #[arg(long, default_value = "50", help = "Number of similar jobs to fetch")]
+    pub count: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct PerfArgs {
+    #[command(flatten)]
+    pub revision: RevisionArgs,
+    #[command(flatten)]
+    pub filters: JobFilterArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct ArtifactsArgs {
+    #[command(flatten)]
+    pub revision: RevisionArgs,
+    #[command(flatten)]
+    pub filters: JobFilterArgs,
     #[arg(
         long,
         help = "Regex pattern to filter artifacts (e.g., 'screenshot|errorsummary')"
     )]
     pub artifact_pattern: Option<String>,
-    #[arg(long, help = "Show performance/resource usage data for jobs")]
-    pub perf: bool,
-    #[arg(long, help = "Show history for a job ID using similar_jobs API")]
-    pub similar_history: Option<u64>,
     #[arg(
         long,
-        default_value = "50",
-        help = "Number of similar jobs to fetch for --similar-history"
+        help = "Directory to download artifacts into (default: artifacts-<revision>)"
     )]
-    pub similar_count: usize,
+    pub output_dir: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct LogsArgs {
+    #[command(flatten)]
+    pub revision: RevisionArgs,
+    #[command(flatten)]
+    pub filters: JobFilterArgs,
+    #[command(flatten)]
+    pub report: ReportArgs,
+    #[arg(long, help = "Regex pattern to search for in logs")]
+    pub pattern: Option<String>,
     #[arg(
         long,
-        help = "Use a Lando job ID to fetch the commit hash (alternative to INPUT)",
-        conflicts_with = "input"
+        help = "Directory to store cached logs (persistent storage, not temp)"
     )]
-    pub lando_job_id: Option<u64>,
+    pub cache_dir: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct WatchArgs {
+    #[arg(
+        value_name = "INPUT",
+        help = "Treeherder URLs, revision hashes or local checkout directories of the pushes \
+                to watch [default: the current directory]"
+    )]
+    pub inputs: Vec<String>,
+    #[arg(
+        long = "lando-job-id",
+        id = "lando_job_id",
+        value_delimiter = ',',
+        help = "Lando job IDs whose pushes to watch once they land"
+    )]
+    pub lando_job_ids: Vec<u64>,
+    #[command(flatten)]
+    pub filters: JobFilterArgs,
+    #[command(flatten)]
+    pub report: ReportArgs,
+    #[arg(
+        long,
+        default_value = "300",
+        help = "Longest polling interval in seconds, used while no job is running"
+    )]
+    pub interval: u64,
+    #[arg(
+        long,
+        default_value = "30",
+        help = "Shortest polling interval in seconds, used while jobs are starting and \
+                completing"
+    )]
+    pub min_interval: u64,
+    #[arg(
+        long,
+        value_name = "TARGET",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "desktop",
+        value_parser = NotifyTarget::parse,
+        help = "Notify when jobs fail and when the watch ends: desktop (the default), bell, \
+                osc9, osc777, webhook:URL, matrix:ROOM_ID or command:CMD; repeat for several"
+    )]
+    pub notify: Vec<NotifyTarget>,
+    #[arg(
+        long,
+        env = "MATRIX_HOMESERVER",
+        default_value = DEFAULT_MATRIX_HOMESERVER,
+        help = "Matrix homeserver of --notify=matrix:ROOM_ID"
+    )]
+    pub matrix_homeserver: String,
+    #[arg(
+        long,
+        env = "MATRIX_ACCESS_TOKEN",
+        hide_env_values = true,
+        help = "Access token of the Matrix account sending --notify=matrix:ROOM_ID messages"
+    )]
+    pub matrix_access_token: Option<String>,
+    #[arg(
+        long,
+        value_name = "CONDITION",
+        value_delimiter = ',',
+        default_value = "all-complete",
+        value_parser = WatchCondition::parse,
+        help = "Stop watching once any of these holds: all-complete, filtered-complete \
+                (only jobs matching the filters), tier1-complete, first-failure or failures>=N"
+    )]
+    pub until: Vec<WatchCondition>,
+}
+
+impl WatchArgs {
+    /// The pushes to watch, with the job filter.
+    pub fn queries(&self) -> anyhow::Result<Vec<PushQuery>> {
+        let mut pushes: Vec<PushQuery> = self
+            .inputs
+            .iter()
+            .map(|input| {
+                if Path::new(input).is_dir() {
+                    PushQuery::checkout(input)
+                } else {
+                    PushQuery::new(input.clone())
+                }
+            })
+            .collect();
+        pushes.extend(
+            self.lando_job_ids
+                .iter()
+                .map(|id| PushQuery::lando_job(*id)),
+        );
+        if pushes.is_empty() {
+            pushes.push(PushQuery::checkout(std::env::current_dir()?));
+        }
+        Ok(pushes
+            .into_iter()
+            .map(|push| push.with_filter(self.filters.to_filter()))
+            .collect())
+    }
+
+    pub fn matrix_account(&self) -> MatrixAccount {
+        MatrixAccount {
+            homeserver: self.matrix_homeserver.clone(),
+            access_token: self.matrix_access_token.clone(),
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct HealthArgs {
+    #[command(flatten)]
+    pub revision: RevisionArgs,
+}
+
+/// Options shared by the commands that act on jobs.
+#[derive(Args, Debug)]
+pub struct ActionArgs {
+    #[command(flatten)]
+    pub revision: RevisionArgs,
+    #[command(flatten)]
+    pub filters: JobFilterArgs,
+    #[arg(long, help = "Only print what would be done")]
+    pub dry_run: bool,
+    #[arg(
+        long,
+        env = "TASKCLUSTER_CLIENT_ID",
+        hide_env_values = true,
+        help = "Taskcluster client ID for retrigger, backfill and cancel"
+    )]
+    pub taskcluster_client_id: Option<String>,
+    #[arg(
+        long,
+        env = "TASKCLUSTER_ACCESS_TOKEN",
+        hide_env_values = true,
+        help = "Access token of the Taskcluster client"
+    )]
+    pub taskcluster_access_token: Option<String>,
+    #[arg(
+        long,
+        env = "TASKCLUSTER_CERTIFICATE",
+        hide_env_values = true,
+        help = "Certificate of temporary Taskcluster credentials"
+    )]
+    pub taskcluster_certificate: Option<String>,
+    #[arg(
+        long,
+        env = "TREEHERDER_ACCESS_TOKEN",
+        hide_env_values = true,
+        help = "Access token of a Treeherder login, for classify: accessToken of the \
+                userSession the Treeherder UI keeps in local storage"
+    )]
+    pub treeherder_access_token: Option<String>,
+    #[arg(
+        long,
+        env = "TREEHERDER_ID_TOKEN",
+        hide_env_values = true,
+        help = "ID token of the Treeherder login: idToken of its userSession"
+    )]
+    pub treeherder_id_token: Option<String>,
+    #[arg(
+        long,
+        env = "TREEHERDER_ACCESS_TOKEN_EXPIRES_AT",
+        value_name = "SECONDS",
+        help = "When the Treeherder access token expires: accessTokenExpiresAt of its \
+                userSession"
+    )]
+    pub treeherder_access_token_expires_at: Option<u64>,
+}
+
+impl ActionArgs {
+    pub fn credentials(&self) -> Credentials {
+        let taskcluster = match (&self.taskcluster_client_id, &self.taskcluster_access_token) {
+            (Some(client_id), Some(access_token)) => Some(TaskclusterCredentials {
+                client_id: client_id.clone(),
+                access_token: access_token.clone(),
+                certificate: self.taskcluster_certificate.clone(),
+            }),
+            _ => None,
+        };
+        let treeherder = match (
+            &self.treeherder_access_token,
+            &self.treeherder_id_token,
+            self.treeherder_access_token_expires_at,
+        ) {
+            (Some(access_token), Some(id_token), Some(expires_at)) => Some(TreeherderCredentials {
+                access_token: access_token.clone(),
+                id_token: id_token.clone(),
+                expires_at,
+            }),
+            _ => None,
+        };
+        Credentials {
+            taskcluster,
+            treeherder,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct RetriggerArgs {
+    #[command(flatten)]
+    pub action: ActionArgs,
+    #[arg(
+        long,
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..=100),
+        help = "Number of times to run each job again"
+    )]
+    pub times: u32,
+}
+
+#[derive(Args, Debug)]
+pub struct BackfillArgs {
+    #[command(flatten)]
+    pub action: ActionArgs,
+    #[arg(
+        long,
+        default_value_t = 5,
+        value_parser = clap::value_parser!(u32).range(1..=50),
+        help = "Number of earlier pushes to run each job on"
+    )]
+    pub depth: u32,
+}
+
+#[derive(Args, Debug)]
+pub struct CancelArgs {
+    #[command(flatten)]
+    pub action: ActionArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct ClassifyArgs {
+    #[command(flatten)]
+    pub action: ActionArgs,
+    #[arg(
+        long = "as",
+        id = "classify_as",
+        default_value = "intermittent",
+        value_parser = PossibleValuesParser::new(FailureClassification::NAMES)
+            .map(|name| FailureClassification::from_name(&name).expect("a possible value")),
+        help = "Classification to give the failures"
+    )]
+    pub classification: FailureClassification,
+    #[arg(long, help = "Bugzilla bug the failures are classified under")]
+    pub bug: Option<u64>,
+    #[arg(
+        long,
+        default_value = "",
+        help = "Comment recorded with the classification"
+    )]
+    pub comment: String,
+}
+
+impl ClassifyArgs {
+    pub fn classification(&self) -> Classification {
+        Classification {
+            classification: self.classification,
+            bug: self.bug,
+            comment: self.comment.clone(),
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct CacheArgs {
+    #[arg(long, help = "Directory holding logs cached by `logs --cache-dir`")]
+    pub cache_dir: String,
+    #[command(flatten)]
+    pub filters: JobFilterArgs,
+    #[command(flatten)]
+    pub report: ReportArgs,
+    #[arg(long, help = "Regex pattern to search for in cached logs")]
+    pub pattern: Option<String>,
+}
+
+/// Pushes listed when no range bound is given.
+const DEFAULT_LAST_PUSHES: usize = 10;
+/// Pushes listed at most within a date or revision range.
+const DEFAULT_RANGE_PUSHES: usize = 100;
+
+#[derive(Args, Debug)]
+pub struct PushesArgs {
+    #[arg(long, help = "Only list pushes by this author, e.g. me@mozilla.com")]
+    pub author: Option<String>,
+    #[arg(
+        long,
+        help = "Number of most recent pushes to list [default: 10, or 100 within a date or \
+                revision range]"
+    )]
+    pub last: Option<usize>,
+    #[arg(
+        long,
+        value_name = "YYYY-MM-DD",
+        value_parser = parse_date,
+        help = "Only list pushes made on or after this day"
+    )]
+    pub from_date: Option<String>,
+    #[arg(
+        long,
+        value_name = "YYYY-MM-DD",
+        value_parser = parse_date,
+        help = "Only list pushes made on or before this day"
+    )]
+    pub to_date: Option<String>,
+    #[arg(
+        long = "fromchange",
+        value_name = "REV",
+        help = "Only list pushes from this revision on"
+    )]
+    pub from_change: Option<String>,
+    #[arg(
+        long = "tochange",
+        value_name = "REV",
+        help = "Only list pushes up to this revision"
+    )]
+    pub to_change: Option<String>,
+    #[command(flatten)]
+    pub filters: JobFilterArgs,
+}
+
+impl PushesArgs {
+    pub fn range(&self) -> PushRange {
+        let bounded = self.from_date.is_some()
+            || self.to_date.is_some()
+            || self.from_change.is_some()
+            || self.to_change.is_some();
+        PushRange {
+            author: self.author.clone(),
+            from_date: self.from_date.clone(),
+            to_date: self.to_date.clone(),
+            from_change: self.from_change.clone(),
+            to_change: self.to_change.clone(),
+            limit: self.last.unwrap_or(if bounded {
+                DEFAULT_RANGE_PUSHES
+            } else {
+                DEFAULT_LAST_PUSHES
+            }),
+        }
+    }
+}
+
+fn parse_date(value: &str) -> Result<String, String> {
+    let well_formed = value.len() == 10
+        && value.char_indices().all(|(i, c)| match i {
+            4 | 7 => c == '-',
+            _ => c.is_ascii_digit(),
+        });
+    if well_formed {
+        Ok(value.to_string())
+    } else {
+        Err(format!("expected a date as YYYY-MM-DD, got '{}'", value))
+    }
 }
@@ -0,0 +1,587 @@
+use crate::actions::{
+    render_hook_payload, ActionDefinition, ActionOutcome, ActionStatus, Credentials, JobAction,
+    TreeherderSession,
+};
+use crate::api::*;
+use crate::checkout::Checkout;
+use crate::http::HttpClient;
+use crate::models::*;
+use crate::query::{
+    JobFilter, PushJobs, PushQuery, PushRange, PushReport, PushRollup, RevisionSpec,
+};
+use anyhow::{Context, Result};
+use futures::stream::{self, StreamExt};
+use regex::Regex;
+use std::path::Path;
+
+/// Recent pushes searched for the checked out commit when it is not the tip
+/// of a push.
+const CHECKOUT_SEARCH_PUSHES: usize = 50;
+
+/// Entry point for embedding the tool: wraps an HTTP client, the service
+/// endpoints and the repository every request is made against.
+///
+/// The `fetch_*` methods that fan out over a list of jobs call `progress`
+/// once per job as it finishes, so callers can drive a progress bar.
+#[derive(Debug, Clone)]
+pub struct TreeherderClient {
+    http: HttpClient,
+    endpoints: Endpoints,
+    repo: String,
+    max_jobs: usize,
+}
+
+impl TreeherderClient {
+    pub fn new(repo: impl Into<String>) -> Self {
+        Self::with_endpoints(repo, Endpoints::default())
+    }
+
+    pub fn with_endpoints(repo: impl Into<String>, endpoints: Endpoints) -> Self {
+        Self {
+            http: HttpClient::default(),
+            endpoints,
+            repo: repo.into(),
+            max_jobs: DEFAULT_MAX_JOBS,
+        }
+    }
+
+    /// Replaces the default request layer, e.g. to change the retry policy
+    /// or the per-host connection cap.
+    pub fn with_http_client(mut self, http: HttpClient) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Caps how many jobs are read from a single push.
+    pub fn with_max_jobs(mut self, max_jobs: usize) -> Self {
+        self.max_jobs = max_jobs;
+        self
+    }
+
+    pub fn http(&self) -> &HttpClient {
+        &self.http
+    }
+
+    pub fn endpoints(&self) -> &Endpoints {
+        &self.endpoints
+    }
+
+    pub fn repo(&self) -> &str {
+        &self.repo
+    }
+
+    pub fn max_jobs(&self) -> usize {
+        self.max_jobs
+    }
+
+    /// The full revision hash `revision` identifies. Abbreviated hashes are
+    /// expanded by looking up their push.
+    pub async fn resolve_revision(&self, revision: &RevisionSpec) -> Result<String> {
+        match revision {
+            RevisionSpec::Input(input) => {
+                let parsed = parse_revision_input(input)?;
+                if let Some(repo) = parsed.repo.filter(|repo| *repo != self.repo) {
+                    anyhow::bail!("{} is in repository '{}', not '{}'", input, repo, self.repo);
+                }
+                match parsed.target {
+                    RevisionTarget::Revision(revision) if revision.len() == FULL_REVISION_LEN => {
+                        Ok(revision)
+                    }
+                    RevisionTarget::Revision(short) => self.expand_revision(&short).await,
+                    RevisionTarget::LandoJob(job_id) => {
+                        fetch_commit_from_lando_job(&self.http, &self.endpoints, job_id).await
+                    }
+                }
+            }
+            RevisionSpec::LandoJob(job_id) => {
+                fetch_commit_from_lando_job(&self.http, &self.endpoints, *job_id).await
+            }
+            RevisionSpec::Checkout(dir) => {
+                self.checkout_push_revision(&Checkout::detect(dir)?).await
+            }
+        }
+    }
+
+    /// The full hash of the commit `short` abbreviates, from the push that
+    /// contains it.
+    async fn expand_revision(&self, short: &str) -> Result<String> {
+        let filter = format!("revision={}", short);
+        let pushes = fetch_pushes(&self.http, &self.endpoints, &self.repo, &filter, 1).await?;
+        pushes
+            .iter()
+            .flat_map(|push| {
+                std::iter::once(&push.revision).chain(push.revisions.iter().map(|c| &c.revision))
+            })
+            .find(|revision| revision.starts_with(short))
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("No push found for revision {}", short))
+    }
+
+    /// The revision of the push holding the checked out commit: the commit
+    /// itself when it was pushed as the tip, or else the most recent push of
+    /// the checkout's author that contains it, such as the try push `mach
+    /// try` makes by adding a commit on top.
+    async fn checkout_push_revision(&self, checkout: &Checkout) -> Result<String> {
+        let filter = format!("revision={}", checkout.revision);
+        let tips = fetch_pushes(&self.http, &self.endpoints, &self.repo, &filter, 1).await?;
+        if let Some(push) = tips.into_iter().next() {
+            return Ok(push.revision);
+        }
+
+        let recent = self
+            .pushes(&PushRange {
+                author: checkout.author.clone(),
+                limit: CHECKOUT_SEARCH_PUSHES,
+                ..PushRange::default()
+            })
+            .await?;
+        recent
+            .into_iter()
+            .find(|push| {
+                push.revisions
+                    .iter()
+                    .any(|commit| commit.revision == checkout.revision)
+            })
+            .map(|push| push.revision)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No {} push found containing the checked out revision {}",
+                    self.repo,
+                    checkout.revision
+                )
+            })
+    }
+
+    pub async fn lando_job_status(&self, job_id: u64) -> Result<LandoJobResponse> {
+        fetch_lando_job_status(&self.http, &self.endpoints, job_id).await
+    }
+
+    pub async fn push_id(&self, revision: &str) -> Result<u64> {
+        fetch_push_id(&self.http, &self.endpoints, &self.repo, revision).await
+    }
+
+    /// Treeherder's own triage of the failures of a push.
+    pub async fn push_health(&self, revision: &str) -> Result<PushHealth> {
+        fetch_push_health(&self.http, &self.endpoints, &self.repo, revision).await
+    }
+
+    /// Carries out `action` on each of `jobs` of push `push_id`. With
+    /// `dry_run`, only checks that the push offers the action and reports
+    /// what would be done. Failures on single jobs are reported in their
+    /// outcome rather than stopping the others.
+    pub async fn act(
+        &self,
+        push_id: u64,
+        action: &JobAction,
+        jobs: &[Job],
+        credentials: &Credentials,
+        dry_run: bool,
+    ) -> Result<Vec<ActionOutcome>> {
+        if !dry_run {
+            credentials.check(action)?;
+        }
+        let hook = if action.is_taskcluster_action() {
+            let decision_task_id =
+                fetch_decision_task_id(&self.http, &self.endpoints, &self.repo, push_id).await?;
+            let actions =
+                fetch_actions_json(&self.http, &self.endpoints, &decision_task_id).await?;
+            let definition = actions.task_action(action.name()).cloned().ok_or_else(|| {
+                anyhow::anyhow!(
+                    "The decision task of push {} offers no '{}' action",
+                    push_id,
+                    action.name()
+                )
+            })?;
+            Some((decision_task_id, definition))
+        } else {
+            None
+        };
+        // Treeherder writes are made in a session, started once for all jobs.
+        let session = match &credentials.treeherder {
+            Some(login) if !dry_run && !action.is_taskcluster_action() => {
+                Some(treeherder_login(&self.http, &self.endpoints, login).await?)
+            }
+            _ => None,
+        };
+
+        let hook = &hook;
+        let session = &session;
+        let outcomes = stream::iter(jobs)
+            .map(|job| async move {
+                let result = match (hook, &job.task_id) {
+                    (Some(_), None) => Err(anyhow::anyhow!("Job has no Taskcluster task")),
+                    _ if dry_run => return ActionOutcome::new(action, job, ActionStatus::Planned),
+                    (Some((decision_task_id, definition)), Some(task_id)) => {
+                        self.trigger_action(
+                            action,
+                            definition,
+                            decision_task_id,
+                            task_id,
+                            credentials,
+                        )
+                        .await
+                    }
+                    (None, _) => self.classify(action, job, session.as_ref()).await,
+                };
+                match result {
+                    Ok(detail) => {
+                        ActionOutcome::new(action, job, ActionStatus::Done).with_detail(detail)
+                    }
+                    Err(e) => ActionOutcome::new(action, job, ActionStatus::Failed)
+                        .with_detail(format!("{:#}", e)),
+                }
+            })
+            .buffered(5)
+            .collect()
+            .await;
+        Ok(outcomes)
+    }
+
+    /// Triggers the in-tree action `definition` on task `task_id` and
+    /// describes the task carrying it out.
+    async fn trigger_action(
+        &self,
+        action: &JobAction,
+        definition: &ActionDefinition,
+        decision_task_id: &str,
+        task_id: &str,
+        credentials: &Credentials,
+    ) -> Result<String> {
+        let (Some(hook_group_id), Some(hook_id)) = (&definition.hook_group_id, &definition.hook_id)
+        else {
+            anyhow::bail!("The '{}' action names no hook", definition.name);
+        };
+        let context = serde_json::json!({
+            "taskGroupId": decision_task_id,
+            "taskId": task_id,
+            "input": action.input(&definition.schema),
+        });
+        let payload = render_hook_payload(&definition.hook_payload, &context)?;
+        let action_task_id = trigger_hook(
+            &self.http,
+            &self.endpoints,
+            credentials,
+            hook_group_id,
+            hook_id,
+            &payload,
+        )
+        .await?;
+        Ok(format!("action task {}", action_task_id))
+    }
+
+    async fn classify(
+        &self,
+        action: &JobAction,
+        job: &Job,
+        session: Option<&TreeherderSession>,
+    ) -> Result<String> {
+        let JobAction::Classify(classification) = action else {
+            anyhow::bail!("{} is not a Treeherder action", action.name());
+        };
+        let session = session.context("Not logged in to Treeherder")?;
+        post_classification(
+            &self.http,
+            &self.endpoints,
+            &self.repo,
+            session,
+            job.id,
+            classification,
+        )
+        .await?;
+        Ok(format!("classified as {}", classification.classification))
+    }
+
+    /// Lists the pushes of `range`, newest first.
+    pub async fn pushes(&self, range: &PushRange) -> Result<Vec<PushResult>> {
+        let range = PushRange {
+            from_change: range
+                .from_change
+                .as_deref()
+                .map(check_revision)
+                .transpose()?,
+            to_change: range.to_change.as_deref().map(check_revision).transpose()?,
+            ..range.clone()
+        };
+        fetch_pushes(
+            &self.http,
+            &self.endpoints,
+            &self.repo,
+            &range.query_string(),
+            range.limit,
+        )
+        .await
+    }
+
+    /// Fetches every job of a push, up to the job cap. `progress` is called
+    /// with the number of jobs read so far after each page.
+    pub async fn jobs(&self, push_id: u64, progress: impl Fn(usize)) -> Result<Vec<Job>> {
+        fetch_jobs(
+            &self.http,
+            &self.endpoints,
+            push_id,
+            self.max_jobs,
+            progress,
+        )
+        .await
+    }
+
+    /// The jobs of a push modified after `since`, a `last_modified` of a
+    /// job fetched earlier.
+    pub async fn jobs_modified_since(
+        &self,
+        push_id: u64,
+        since: &str,
+        progress: impl Fn(usize),
+    ) -> Result<Vec<Job>> {
+        fetch_jobs_modified_since(
+            &self.http,
+            &self.endpoints,
+            push_id,
+            since,
+            self.max_jobs,
+            progress,
+        )
+        .await
+    }
+
+    pub async fn similar_jobs(&self, job_id: u64, count: usize) -> Result<SimilarJobHistory> {
+        fetch_similar_jobs(&self.http, &self.endpoints, &self.repo, job_id, count).await
+    }
+
+    /// Fetches the errorsummary failures of each job.
+    pub async fn fetch_errors(
+        &self,
+        jobs: Vec<Job>,
+        progress: impl Fn() + Sync,
+    ) -> Fetched<JobWithLogs> {
+        let progress = &progress;
+        let results = stream::iter(jobs)
+            .map(|job| async move {
+                let result = fetch_job_details_with_errors(
+                    &self.http,
+                    &self.endpoints,
+                    &self.repo,
+                    job.clone(),
+                )
+                .await;
+                progress();
+                (job, result)
+            })
+            .buffer_unordered(10)
+            .collect::<Vec<_>>()
+            .await;
+        Fetched::from_results(results)
+    }
+
+    /// Downloads every log of each job into `log_dir/job_<id>` and searches
+    /// them for `pattern`.
+    pub async fn fetch_logs(
+        &self,
+        jobs: Vec<Job>,
+        log_dir: &Path,
+        pattern: Option<&Regex>,
+        progress: impl Fn() + Sync,
+    ) -> Fetched<JobWithLogs> {
+        let progress = &progress;
+        let results = stream::iter(jobs)
+            .map(|job| async move {
+                let result = fetch_job_with_full_logs(
+                    &self.http,
+                    &self.endpoints,
+                    &self.repo,
+                    job.clone(),
+                    log_dir,
+                    pattern,
+                )
+                .await;
+                progress();
+                (job, result)
+            })
+            .buffer_unordered(5)
+            .collect::<Vec<_>>()
+            .await;
+        Fetched::from_results(results)
+    }
+
+    pub async fn fetch_perf(
+        &self,
+        jobs: Vec<Job>,
+        progress: impl Fn() + Sync,
+    ) -> Fetched<JobPerfData> {
+        let progress = &progress;
+        let results = stream::iter(jobs)
+            .map(|job| async move {
+                let result = fetch_job_perf_data(&self.http, &self.endpoints, &self.repo, &job)
+                    .await
+                    .map(|perf_data| (perf_data, vec![]));
+                progress();
+                (job, result)
+            })
+            .buffer_unordered(5)
+            .collect::<Vec<_>>()
+            .await;
+        Fetched::from_results(results)
+    }
+
+    /// Downloads the artifacts of each job matching `pattern` into
+    /// `output_dir/job-<id>`. Each item lists the paths written for one job.
+    pub async fn download_artifacts(
+        &self,
+        jobs: Vec<Job>,
+        output_dir: &Path,
+        pattern: Option<&Regex>,
+        progress: impl Fn() + Sync,
+    ) -> Fetched<Vec<String>> {
+        let progress = &progress;
+        let results = stream::iter(jobs)
+            .map(|job| async move {
+                let result = download_job_artifacts(
+                    &self.http,
+                    &self.endpoints,
+                    &self.repo,
+                    &job,
+                    output_dir,
+                    pattern,
+                )
+                .await;
+                progress();
+                (job, result)
+            })
+            .buffer_unordered(3)
+            .collect::<Vec<_>>()
+            .await;
+        Fetched::from_results(results)
+    }
+
+    /// Resolves the push of `query` and returns the jobs matching its
+    /// filter. `progress` is called as pages of jobs come in, before
+    /// filtering.
+    pub async fn filtered_jobs(
+        &self,
+        query: &PushQuery,
+        progress: impl Fn(usize),
+    ) -> Result<PushJobs> {
+        let revision = self.resolve_revision(&query.revision).await?;
+        let push_id = self.push_id(&revision).await?;
+        self.push_jobs(revision, push_id, &query.filter, progress)
+            .await
+    }
+
+    async fn push_jobs(
+        &self,
+        revision: String,
+        push_id: u64,
+        filter: &JobFilter,
+        progress: impl Fn(usize),
+    ) -> Result<PushJobs> {
+        let all_jobs = self.jobs(push_id, progress).await?;
+        let pending_jobs = filter.pending(&all_jobs)?;
+        Ok(PushJobs {
+            revision,
+            push_id,
+            jobs: filter.apply(all_jobs)?,
+            pending_jobs,
+        })
+    }
+
+    pub async fn report(&self, query: &PushQuery) -> Result<PushReport> {
+        let push_jobs = self.filtered_jobs(query, |_| {}).await?;
+        Ok(self.push_report(push_jobs).await)
+    }
+
+    async fn push_report(&self, push_jobs: PushJobs) -> PushReport {
+        let fetched = self.fetch_errors(push_jobs.jobs, || {}).await;
+        PushReport {
+            revision: push_jobs.revision,
+            push_id: push_jobs.push_id,
+            jobs: fetched.items,
+            fetch_errors: fetched.fetch_errors,
+            pending_jobs: push_jobs.pending_jobs,
+        }
+    }
+
+    /// Reports on each of `pushes`, listed newest first as by
+    /// [`Self::pushes`], with `filter`, and compares each with the push after
+    /// it in the list. `progress` is called once per push as its report
+    /// completes. A push whose jobs cannot be fetched is listed with its
+    /// error, and the push before it is not compared with it.
+    pub async fn rollup(
+        &self,
+        pushes: &[PushResult],
+        filter: &JobFilter,
+        progress: impl Fn(),
+    ) -> Vec<PushRollup> {
+        let reports: Vec<Result<PushReport>> = stream::iter(pushes)
+            .map(|push| async {
+                let report = self
+                    .push_jobs(push.revision.clone(), push.id, filter, |_| {})
+                    .await;
+                let report = match report {
+                    Ok(push_jobs) => Ok(self.push_report(push_jobs).await),
+                    Err(e) => Err(e),
+                };
+                progress();
+                report
+            })
+            .buffered(3)
+            .collect()
+            .await;
+
+        pushes
+            .iter()
+            .zip(&reports)
+            .enumerate()
+            .map(|(i, (push, report))| match report {
+                Ok(report) => {
+                    let previous = reports.get(i + 1).and_then(|r| r.as_ref().ok());
+                    PushRollup::new(push, report, previous)
+                }
+                Err(e) => PushRollup::failed(push, e),
+            })
+            .collect()
+    }
+
+    /// Reports on both pushes and lists the failures new in `query` relative
+    /// to `baseline`.
+    pub async fn compare(
+        &self,
+        query: &PushQuery,
+        baseline: &PushQuery,
+    ) -> Result<ComparisonResult> {
+        let report = self.report(query).await?;
+        let baseline_report = self.report(baseline).await?;
+        Ok(report.compare(&baseline_report))
+    }
+}
+
+/// A job and what fetching it produced: the item and the logs that failed,
+/// or the error that prevented fetching the job at all.
+type JobOutcome<T> = (Job, Result<(T, Vec<FetchError>)>);
+
+/// What a fan-out over jobs retrieved, along with every job, log or
+/// artifact it could not.
+#[derive(Debug, Clone)]
+pub struct Fetched<T> {
+    pub items: Vec<T>,
+    pub fetch_errors: Vec<FetchError>,
+}
+
+impl<T> Fetched<T> {
+    fn from_results(results: Vec<JobOutcome<T>>) -> Self {
+        let mut items = Vec::new();
+        let mut fetch_errors = Vec::new();
+        for (job, result) in results {
+            match result {
+                Ok((item, errors)) => {
+                    items.push(item);
+                    fetch_errors.extend(errors);
+                }
+                Err(e) => fetch_errors.push(FetchError::job(&job, &e)),
+            }
+        }
+        Self {
+            items,
+            fetch_errors,
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashSet};
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+/// Settings read from `~/.config/treeherder-cli/config.toml`. Top-level keys
+/// apply to every invocation; `[profile.<name>]` tables are layered on top
+/// when selected with `--profile`. Keys are option names, e.g.
+/// `include-intermittent = true`.
+#[derive(Deserialize, Debug, Default)]
+pub struct Config {
+    #[serde(default)]
+    profile: BTreeMap<String, toml::Table>,
+    #[serde(flatten)]
+    defaults: toml::Table,
+}
+
+impl Config {
+    /// `$TREEHERDER_CLI_CONFIG`, or `config.toml` under
+    /// `$XDG_CONFIG_HOME/treeherder-cli` (`~/.config` when unset).
+    pub fn path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os("TREEHERDER_CLI_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_home.join("treeherder-cli").join("config.toml"))
+    }
+
+    /// Loads the config file; a missing file is an empty config.
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path().filter(|path| path.exists()) else {
+            return Ok(Self::default());
+        };
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    /// The settings to apply, as option name and value: the top-level ones,
+    /// overridden by those of `profile`.
+    pub fn settings(&self, profile: Option<&str>) -> Result<Vec<(String, String)>> {
+        let mut merged = self.defaults.clone();
+        if let Some(name) = profile {
+            let overrides = self
+                .profile
+                .get(name)
+                .ok_or_else(|| anyhow::anyhow!("Profile '{}' not found in config file", name))?;
+            merged.extend(overrides.clone());
+        }
+
+        merged
+            .into_iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    toml::Value::String(s) => s,
+                    toml::Value::Integer(i) => i.to_string(),
+                    toml::Value::Float(f) => f.to_string(),
+                    toml::Value::Boolean(b) => b.to_string(),
+                    other => {
+                        anyhow::bail!("Unsupported value for '{}' in config file: {}", key, other)
+                    }
+                };
+                Ok((key.replace('-', "_"), value))
+            })
+            .collect()
+    }
+}
+
+/// The profile named by `--profile` in `args`, or `$TREEHERDER_PROFILE`. It
+/// has to be known before the command line is parsed, since the profile
+/// provides defaults for that parse.
+pub fn requested_profile(args: impl IntoIterator<Item = OsString>) -> Option<String> {
+    let mut args = args
+        .into_iter()
+        .map(|arg| arg.to_string_lossy().into_owned());
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        if arg == "--profile" {
+            return args.next();
+        }
+        if let Some(name) = arg.strip_prefix("--profile=") {
+            return Some(name.to_string());
+        }
+    }
+    std::env::var("TREEHERDER_PROFILE").ok()
+}
+
+/// Makes each setting the default value of the option of the same name, in
+/// `command` and all its subcommands, so that flags given on the command
+/// line still win. Fails on settings that match no option.
+pub fn apply_settings(
+    command: clap::Command,
+    settings: &[(String, String)],
+) -> Result<clap::Command> {
+    let mut used = HashSet::new();
+    let command = set_defaults(command, settings, &mut used);
+    if let Some((key, _)) = settings.iter().find(|(key, _)| !used.contains(key)) {
+        anyhow::bail!("Unknown option '{}' in config file", key.replace('_', "-"));
+    }
+    Ok(command)
+}
+
+fn set_defaults(
+    mut command: clap::Command,
+    settings: &[(String, String)],
+    used: &mut HashSet<String>,
+) -> clap::Command {
+    for (key, value) in settings {
+        let configurable = command
+            .get_arguments()
+            .any(|arg| arg.get_id() == key.as_str() && arg.get_long().is_some());
+        if configurable && key != "profile" {
+            command = command.mut_arg(key, |arg| arg.default_value(value.clone()).required(false));
+            used.insert(key.clone());
+        }
+    }
+
+    let subcommands: Vec<String> = command
+        .get_subcommands()
+        .map(|sub| sub.get_name().to_string())
+        .collect();
+    for name in subcommands {
+        command = command.mut_subcommand(name, |sub| set_defaults(sub, settings, used));
+    }
+    command
+}
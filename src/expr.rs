@@ -0,0 +1,499 @@
+//! The `--where` expression language for selecting jobs, e.g.
+//!
+//! ```text
+//! platform ~ "linux.*" and result in (testfailed, busted) and duration > 1800
+//! ```
+//!
+//! An expression is made of comparisons joined with `and`, `or`, `not` and
+//! parentheses. A comparison is a field, an operator and a value:
+//!
+//! | Operator            | Meaning                                 |
+//! |---------------------|-----------------------------------------|
+//! | `=`, `!=`           | equal, not equal                        |
+//! | `~`, `!~`           | matches, does not match a regex         |
+//! | `<`, `<=`, `>`, `>=`| numeric comparison                      |
+//! | `in (a, b, ...)`    | equal to one of the values              |
+//!
+//! Values are bare words (`testfailed`, `linux1804-64`, `3600`) or quoted
+//! strings, which regexes usually need. A comparison on a field the job has
+//! no value for, such as the duration of a running job, is false.
+//! Classifications are named as for `--classification`, e.g.
+//! `fixed-by-commit`.
+
+use crate::models::{FailureClassification, Job};
+use anyhow::{bail, Result};
+use regex::Regex;
+use std::fmt;
+use std::str::FromStr;
+
+/// A parsed `--where` expression.
+#[derive(Debug, Clone)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Field, Comparison),
+}
+
+/// A job attribute that expressions can refer to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Id,
+    Name,
+    Symbol,
+    Platform,
+    Option,
+    Result,
+    State,
+    Classification,
+    ClassificationId,
+    Duration,
+    Tier,
+    Group,
+    GroupSymbol,
+    TaskId,
+    RetryId,
+    Who,
+    SubmitTimestamp,
+    StartTimestamp,
+    EndTimestamp,
+}
+
+#[derive(Debug, Clone)]
+pub enum Comparison {
+    Eq(Value),
+    Ne(Value),
+    Matches(Regex),
+    NotMatches(Regex),
+    Lt(f64),
+    Le(f64),
+    Gt(f64),
+    Ge(f64),
+    In(Vec<Value>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Text(String),
+    Number(f64),
+}
+
+enum FieldValue {
+    Text(String),
+    Number(f64),
+}
+
+impl Field {
+    /// Every field name, aliases included.
+    pub const NAMES: [&'static str; 23] = [
+        "id",
+        "name",
+        "job_type_name",
+        "symbol",
+        "job_type_symbol",
+        "platform",
+        "option",
+        "result",
+        "state",
+        "classification",
+        "classification_id",
+        "duration",
+        "tier",
+        "group",
+        "job_group_name",
+        "group_symbol",
+        "job_group_symbol",
+        "task_id",
+        "retry_id",
+        "who",
+        "submit_timestamp",
+        "start_timestamp",
+        "end_timestamp",
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "id" => Field::Id,
+            "name" | "job_type_name" => Field::Name,
+            "symbol" | "job_type_symbol" => Field::Symbol,
+            "platform" => Field::Platform,
+            "option" => Field::Option,
+            "result" => Field::Result,
+            "state" => Field::State,
+            "classification" => Field::Classification,
+            "classification_id" => Field::ClassificationId,
+            "duration" => Field::Duration,
+            "tier" => Field::Tier,
+            "group" | "job_group_name" => Field::Group,
+            "group_symbol" | "job_group_symbol" => Field::GroupSymbol,
+            "task_id" => Field::TaskId,
+            "retry_id" => Field::RetryId,
+            "who" => Field::Who,
+            "submit_timestamp" => Field::SubmitTimestamp,
+            "start_timestamp" => Field::StartTimestamp,
+            "end_timestamp" => Field::EndTimestamp,
+            _ => return None,
+        })
+    }
+
+    fn is_numeric(self) -> bool {
+        matches!(
+            self,
+            Field::Id
+                | Field::ClassificationId
+                | Field::Duration
+                | Field::Tier
+                | Field::RetryId
+                | Field::SubmitTimestamp
+                | Field::StartTimestamp
+                | Field::EndTimestamp
+        )
+    }
+
+    fn value(self, job: &Job) -> Option<FieldValue> {
+        let text = |s: &str| Some(FieldValue::Text(s.to_string()));
+        let number = |n: Option<u64>| n.map(|n| FieldValue::Number(n as f64));
+        match self {
+            Field::Id => Some(FieldValue::Number(job.id as f64)),
+            Field::Name => text(&job.job_type_name),
+            Field::Symbol => text(&job.job_type_symbol),
+            Field::Platform => text(&job.platform),
+            Field::Option => text(&job.platform_option),
+            Field::Result => text(job.result.as_str()),
+            Field::State => text(job.state.as_str()),
+            Field::Classification => job.failure_classification.and_then(|c| match c.name() {
+                Some(name) => text(name),
+                None => text(&c.to_string()),
+            }),
+            Field::ClassificationId => job
+                .failure_classification
+                .map(|c| FieldValue::Number(c.id() as f64)),
+            Field::Duration => number(job.duration),
+            Field::Tier => number(job.tier),
+            Field::Group => text(&job.job_group_name),
+            Field::GroupSymbol => text(&job.job_group_symbol),
+            Field::TaskId => job.task_id.as_deref().and_then(text),
+            Field::RetryId => number(job.retry_id),
+            Field::Who => job.who.as_deref().and_then(text),
+            Field::SubmitTimestamp => number(job.submit_timestamp),
+            Field::StartTimestamp => number(job.start_timestamp),
+            Field::EndTimestamp => number(job.end_timestamp),
+        }
+    }
+}
+
+impl Expr {
+    pub fn parse(input: &str) -> Result<Self> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+        if let Some(token) = parser.peek() {
+            bail!("Unexpected {} after the end of the expression", token);
+        }
+        Ok(expr)
+    }
+
+    pub fn matches(&self, job: &Job) -> bool {
+        match self {
+            Expr::And(a, b) => a.matches(job) && b.matches(job),
+            Expr::Or(a, b) => a.matches(job) || b.matches(job),
+            Expr::Not(e) => !e.matches(job),
+            Expr::Compare(field, comparison) => match field.value(job) {
+                Some(value) => comparison.holds(&value),
+                None => false,
+            },
+        }
+    }
+}
+
+impl FromStr for Expr {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl Comparison {
+    fn holds(&self, value: &FieldValue) -> bool {
+        let number = match value {
+            FieldValue::Number(n) => Some(*n),
+            FieldValue::Text(_) => None,
+        };
+        match self {
+            Comparison::Eq(expected) => value.equals(expected),
+            Comparison::Ne(expected) => !value.equals(expected),
+            Comparison::Matches(regex) => value.text().is_some_and(|t| regex.is_match(t)),
+            Comparison::NotMatches(regex) => value.text().is_some_and(|t| !regex.is_match(t)),
+            Comparison::Lt(bound) => number.is_some_and(|n| n < *bound),
+            Comparison::Le(bound) => number.is_some_and(|n| n <= *bound),
+            Comparison::Gt(bound) => number.is_some_and(|n| n > *bound),
+            Comparison::Ge(bound) => number.is_some_and(|n| n >= *bound),
+            Comparison::In(values) => values.iter().any(|v| value.equals(v)),
+        }
+    }
+}
+
+impl FieldValue {
+    fn text(&self) -> Option<&str> {
+        match self {
+            FieldValue::Text(t) => Some(t),
+            FieldValue::Number(_) => None,
+        }
+    }
+
+    fn equals(&self, expected: &Value) -> bool {
+        match (self, expected) {
+            (FieldValue::Text(a), Value::Text(b)) => a == b,
+            (FieldValue::Number(a), Value::Number(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(w) => write!(f, "'{}'", w),
+            Token::Quoted(q) => write!(f, "\"{}\"", q),
+            Token::Op(op) => write!(f, "'{}'", op),
+            Token::LParen => f.write_str("'('"),
+            Token::RParen => f.write_str("')'"),
+            Token::Comma => f.write_str("','"),
+        }
+    }
+}
+
+const OPERATORS: [&str; 9] = ["!=", "!~", "<=", ">=", "==", "=", "~", "<", ">"];
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '(' || c == ')' || c == ',' {
+            chars.next();
+            tokens.push(match c {
+                '(' => Token::LParen,
+                ')' => Token::RParen,
+                _ => Token::Comma,
+            });
+        } else if c == '"' || c == '\'' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, escaped)) if escaped == c || escaped == '\\' => text.push(escaped),
+                        Some((_, other)) => {
+                            text.push('\\');
+                            text.push(other);
+                        }
+                        None => bail!("Unterminated string starting at column {}", start + 1),
+                    },
+                    Some((_, ch)) if ch == c => break,
+                    Some((_, ch)) => text.push(ch),
+                    None => bail!("Unterminated string starting at column {}", start + 1),
+                }
+            }
+            tokens.push(Token::Quoted(text));
+        } else if let Some(op) = OPERATORS.iter().find(|op| input[start..].starts_with(**op)) {
+            for _ in 0..op.len() {
+                chars.next();
+            }
+            tokens.push(Token::Op(if *op == "==" { "=" } else { op }));
+        } else if is_word_char(c) {
+            let mut word = String::new();
+            while let Some(&(_, ch)) = chars.peek() {
+                if !is_word_char(ch) {
+                    break;
+                }
+                word.push(ch);
+                chars.next();
+            }
+            tokens.push(Token::Word(word));
+        } else {
+            bail!("Unexpected character '{}' at column {}", c, start + 1);
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/' | ':' | '*')
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Word(w)) if w.eq_ignore_ascii_case(keyword) => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<()> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => bail!("Expected {} but found {}", expected, token),
+            None => bail!("Expected {} at the end of the expression", expected),
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut expr = self.and()?;
+        while self.keyword("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut expr = self.not()?;
+        while self.keyword("and") {
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr> {
+        if self.keyword("not") {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        if self.peek() == Some(&Token::LParen) {
+            self.pos += 1;
+            let expr = self.or()?;
+            self.expect(Token::RParen)?;
+            return Ok(expr);
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr> {
+        let field = match self.next() {
+            Some(Token::Word(name)) => Field::from_name(&name).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unknown field '{}', expected one of: {}",
+                    name,
+                    Field::NAMES.join(", ")
+                )
+            })?,
+            Some(token) => bail!("Expected a field name but found {}", token),
+            None => bail!("Expected a field name at the end of the expression"),
+        };
+
+        if self.keyword("in") {
+            self.expect(Token::LParen)?;
+            let mut values = vec![self.value(field)?];
+            while self.peek() == Some(&Token::Comma) {
+                self.pos += 1;
+                values.push(self.value(field)?);
+            }
+            self.expect(Token::RParen)?;
+            return Ok(Expr::Compare(field, Comparison::In(values)));
+        }
+
+        let op = match self.next() {
+            Some(Token::Op(op)) => op,
+            Some(token) => bail!("Expected an operator after the field but found {}", token),
+            None => bail!("Expected an operator at the end of the expression"),
+        };
+
+        let comparison = match op {
+            "=" => Comparison::Eq(self.value(field)?),
+            "!=" => Comparison::Ne(self.value(field)?),
+            "~" | "!~" => {
+                if field.is_numeric() {
+                    bail!("'{}' cannot be used on a numeric field", op);
+                }
+                let regex = Regex::new(&self.text()?)?;
+                if op == "~" {
+                    Comparison::Matches(regex)
+                } else {
+                    Comparison::NotMatches(regex)
+                }
+            }
+            _ => {
+                if !field.is_numeric() {
+                    let numeric: Vec<&str> = Field::NAMES
+                        .into_iter()
+                        .filter(|name| Field::from_name(name).is_some_and(Field::is_numeric))
+                        .collect();
+                    bail!("'{}' can only be used on {}", op, numeric.join(", "));
+                }
+                let Value::Number(bound) = self.value(field)? else {
+                    unreachable!("numeric fields only take numbers");
+                };
+                match op {
+                    "<" => Comparison::Lt(bound),
+                    "<=" => Comparison::Le(bound),
+                    ">" => Comparison::Gt(bound),
+                    _ => Comparison::Ge(bound),
+                }
+            }
+        };
+        Ok(Expr::Compare(field, comparison))
+    }
+
+    fn text(&mut self) -> Result<String> {
+        match self.next() {
+            Some(Token::Word(w)) | Some(Token::Quoted(w)) => Ok(w),
+            Some(token) => bail!("Expected a value but found {}", token),
+            None => bail!("Expected a value at the end of the expression"),
+        }
+    }
+
+    fn value(&mut self, field: Field) -> Result<Value> {
+        let text = self.text()?;
+        if field.is_numeric() {
+            let number = text
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Expected a number but found '{}'", text))?;
+            Ok(Value::Number(number))
+        } else if field == Field::Classification {
+            Ok(Value::Text(classification_name(text)))
+        } else {
+            Ok(Value::Text(text))
+        }
+    }
+}
+
+/// The name of the classification displayed as `text`, e.g. `fixed-by-commit`
+/// for `fixed by commit`, or `text` itself.
+fn classification_name(text: String) -> String {
+    (1..=FailureClassification::NAMES.len() as u64)
+        .map(FailureClassification::from)
+        .find(|classification| classification.to_string() == text)
+        .and_then(FailureClassification::name)
+        .map_or(text, str::to_string)
+}
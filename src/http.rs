@@ -0,0 +1,228 @@
+use anyhow::{Context, Result};
+use reqwest::header::{HeaderMap, AUTHORIZATION, RETRY_AFTER};
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use url::Url;
+
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+pub const DEFAULT_MAX_CONNECTIONS_PER_HOST: usize = 8;
+
+/// How failed requests are retried: transport errors, 429 and 5xx responses
+/// are retried up to `max_retries` times with exponential backoff and
+/// jitter, or after the delay given by a `Retry-After` header, waiting no
+/// longer than `max_delay` either way.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before retry number `attempt` (starting at 0): half of the
+    /// exponential step, plus a random share of the other half.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let step = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        step / 2 + (step / 2).mul_f64(fastrand::f64())
+    }
+}
+
+/// Shared request layer used for every call to Treeherder, Taskcluster and
+/// Lando. Requests to the same host share a concurrency cap, and a response
+/// is only returned once its body has been read in full.
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    inner: Client,
+    policy: RetryPolicy,
+    max_per_host: usize,
+    hosts: Arc<Mutex<HashMap<String, Arc<Semaphore>>>>,
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new(RetryPolicy::default(), DEFAULT_MAX_CONNECTIONS_PER_HOST)
+    }
+}
+
+impl HttpClient {
+    pub fn new(policy: RetryPolicy, max_per_host: usize) -> Self {
+        Self {
+            inner: Client::new(),
+            policy,
+            max_per_host: max_per_host.max(1),
+            hosts: Arc::default(),
+        }
+    }
+
+    pub fn inner(&self) -> &Client {
+        &self.inner
+    }
+
+    pub async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
+        let bytes = self.get_bytes(url).await?;
+        serde_json::from_slice(&bytes).with_context(|| format!("Invalid JSON returned by {}", url))
+    }
+
+    pub async fn get_text(&self, url: &str) -> Result<String> {
+        let bytes = self.get_bytes(url).await?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    pub async fn get_bytes(&self, url: &str) -> Result<Vec<u8>> {
+        let mut attempt = 0;
+
+        loop {
+            // The permit is only held for the request itself, so that other
+            // requests to the host can go ahead while this one waits.
+            let permit = self.host_permit(url).await;
+            let (error, retry_after) = match self.inner.get(url).send().await {
+                Ok(response) if response.status().is_success() => match response.bytes().await {
+                    Ok(bytes) => return Ok(bytes.to_vec()),
+                    Err(e) => (anyhow::Error::new(e), None),
+                },
+                Ok(response) => {
+                    let status = response.status();
+                    let error = anyhow::anyhow!("{} returned {}", url, status);
+                    if !is_retryable(status) {
+                        return Err(error);
+                    }
+                    (error, retry_after(&response))
+                }
+                Err(e) => (anyhow::Error::new(e), None),
+            };
+            drop(permit);
+
+            if attempt >= self.policy.max_retries {
+                return Err(error.context(format!(
+                    "Request to {} failed after {} attempts",
+                    url,
+                    attempt + 1
+                )));
+            }
+
+            let delay = retry_after
+                .map(|delay| delay.min(self.policy.max_delay))
+                .unwrap_or_else(|| self.policy.backoff(attempt));
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Sends `body` as JSON with `headers`. Failures are not retried, since
+    /// the request may have taken effect.
+    pub async fn post_json<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        body: &serde_json::Value,
+        headers: &[(&str, String)],
+    ) -> Result<T> {
+        let mut request = self.inner.post(url).json(body);
+        for (name, value) in headers {
+            request = request.header(*name, value);
+        }
+        let (_, bytes) = self.send(url, request).await?;
+        serde_json::from_slice(&bytes).with_context(|| format!("Invalid JSON returned by {}", url))
+    }
+
+    /// Sends `body` as JSON with `method` and returns the response body.
+    /// Failures are not retried, since the request may have taken effect.
+    pub async fn send_json(
+        &self,
+        method: Method,
+        url: &str,
+        body: &impl Serialize,
+        authorization: Option<&str>,
+    ) -> Result<Vec<u8>> {
+        let mut request = self.inner.request(method, url).json(body);
+        if let Some(authorization) = authorization {
+            request = request.header(AUTHORIZATION, authorization);
+        }
+        let (_, bytes) = self.send(url, request).await?;
+        Ok(bytes)
+    }
+
+    /// Sends a GET with `headers` and returns the headers of the response,
+    /// e.g. the cookies a login sets. Failures are not retried.
+    pub async fn get_response_headers(
+        &self,
+        url: &str,
+        headers: &[(&str, String)],
+    ) -> Result<HeaderMap> {
+        let mut request = self.inner.get(url);
+        for (name, value) in headers {
+            request = request.header(*name, value);
+        }
+        let (headers, _) = self.send(url, request).await?;
+        Ok(headers)
+    }
+
+    /// Sends `request` once, failing unless it succeeds.
+    async fn send(&self, url: &str, request: RequestBuilder) -> Result<(HeaderMap, Vec<u8>)> {
+        let _permit = self.host_permit(url).await;
+        let response = request.send().await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let bytes = response.bytes().await?;
+        if !status.is_success() {
+            anyhow::bail!(
+                "{} returned {}: {}",
+                url,
+                status,
+                String::from_utf8_lossy(&bytes).trim()
+            );
+        }
+        Ok((headers, bytes.to_vec()))
+    }
+
+    async fn host_permit(&self, url: &str) -> Option<OwnedSemaphorePermit> {
+        let parsed = Url::parse(url).ok()?;
+        let host = format!(
+            "{}:{}",
+            parsed.host_str()?,
+            parsed.port_or_known_default().unwrap_or_default()
+        );
+        let semaphore = Arc::clone(
+            self.hosts
+                .lock()
+                .unwrap()
+                .entry(host)
+                .or_insert_with(|| Arc::new(Semaphore::new(self.max_per_host))),
+        );
+        semaphore.acquire_owned().await.ok()
+    }
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Parses `Retry-After`, given either in seconds or as an HTTP date.
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
@@ -0,0 +1,46 @@
+//! Fetch, filter, group and compare Treeherder test results.
+//!
+//! This is the library behind the `treeherder-cli` binary. Start from
+//! [`TreeherderClient`] and a [`PushQuery`]:
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! use treeherder_cli::{PushQuery, TreeherderClient};
+//!
+//! let client = TreeherderClient::new("try");
+//! let report = client.report(&PushQuery::new("a13b9fc22101")).await?;
+//! for failure in report.grouped_failures() {
+//!     println!("{} on {:?}", failure.test_name, failure.platforms);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod actions;
+pub mod api;
+pub mod cache;
+pub mod checkout;
+pub mod client;
+pub mod expr;
+pub mod http;
+pub mod models;
+pub mod notify;
+pub mod output;
+pub mod query;
+pub mod util;
+pub mod watch;
+
+pub use actions::{ActionOutcome, ActionStatus, Classification, Credentials, JobAction};
+pub use api::Endpoints;
+pub use checkout::Checkout;
+pub use client::{Fetched, TreeherderClient};
+pub use expr::Expr;
+pub use http::{HttpClient, RetryPolicy};
+pub use notify::{Notification, Notifier, NotifyTarget};
+pub use query::{
+    JobFilter, MatchFilter, PushColor, PushJobs, PushQuery, PushRange, PushReport, PushRollup,
+    PushStatus, RevisionSpec,
+};
+pub use watch::{
+    JobChange, JobList, JobTracker, PollSchedule, WatchCondition, WatchEvent, WatchEventKind,
+};
@@ -1,29 +1,55 @@
-mod api;
-mod cache;
 mod cli;
-mod models;
-mod output;
-mod util;
+mod config;
 
 use anyhow::Result;
-use api::*;
-use cache::*;
-use clap::Parser;
-use cli::{Args, MatchFilter};
-use futures::stream::{self, StreamExt};
-use indicatif::{ProgressBar, ProgressStyle};
-use models::*;
-use output::*;
+use cli::{
+    ActionArgs, ArtifactsArgs, CacheArgs, Cli, Command, CompareArgs, HealthArgs, HistoryArgs,
+    JobFilterArgs, LogsArgs, PerfArgs, PushesArgs, ReportArgs, RevisionArgs, SummaryArgs,
+    WatchArgs,
+};
+use futures::future::{join_all, try_join_all};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use regex::Regex;
-use reqwest::Client;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::process::ExitCode;
+use std::time::Duration;
 use tempfile::TempDir;
-use util::*;
+use treeherder_cli::cache::*;
+use treeherder_cli::models::*;
+use treeherder_cli::notify::{Notification, NotifiedFailure, Notifier};
+use treeherder_cli::output::*;
+use treeherder_cli::util::*;
+use treeherder_cli::{
+    ActionStatus, Endpoints, HttpClient, JobAction, JobChange, JobFilter, JobList, JobTracker,
+    PollSchedule, PushJobs, PushQuery, PushReport, PushStatus, RetryPolicy, RevisionSpec,
+    TreeherderClient, WatchEvent, WatchEventKind,
+};
+
+/// What a run found, reported through the process exit code so that
+/// scripts can branch on it. Errors of the tool itself exit with 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Green = 0,
+    Failures = 2,
+    Pending = 3,
+    NewRegressions = 4,
+}
+
+impl From<PushStatus> for Outcome {
+    fn from(status: PushStatus) -> Self {
+        match status {
+            PushStatus::Green => Outcome::Green,
+            PushStatus::Failed => Outcome::Failures,
+            PushStatus::Pending => Outcome::Pending,
+        }
+    }
+}
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> ExitCode {
     let version_checker =
         moz_cli_version_check::VersionChecker::new("treeherder-cli", env!("CARGO_PKG_VERSION"));
     version_checker.check_async();
@@ -32,729 +58,992 @@ async fn main() -> Result<()> {
 
     version_checker.print_warning();
 
-    result
+    match result {
+        Ok(outcome) => ExitCode::from(outcome as u8),
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            ExitCode::FAILURE
+        }
+    }
 }
 
-async fn run() -> Result<()> {
-    let mut args = Args::parse();
+/// State shared by every subcommand.
+struct Context {
+    client: TreeherderClient,
+    json: bool,
+    fail_on_incomplete: bool,
+}
 
-    if !args.json && is_running_under_coding_agent() {
-        args.json = true;
-    }
+async fn run() -> Result<Outcome> {
+    let Cli {
+        mut global,
+        command,
+        summary,
+    } = Cli::parse_checked()?;
 
-    if !args.use_cache
-        && args.input.is_none()
-        && args.similar_history.is_none()
-        && args.lando_job_id.is_none()
-    {
-        anyhow::bail!(
-            "INPUT or --lando-job-id is required when not using --use-cache or --similar-history"
-        );
+    if !global.json && is_running_under_coding_agent() {
+        global.json = true;
     }
 
-    if args.notify && !args.watch {
-        anyhow::bail!("--notify requires --watch to be enabled");
-    }
+    let command = command.unwrap_or(Command::Summary(summary));
+    global.take_repo_from(&command)?;
 
-    if args.watch && args.use_cache {
-        anyhow::bail!("--watch cannot be used with --use-cache");
+    let endpoints = Endpoints::new(
+        &global.treeherder_url,
+        &global.taskcluster_url,
+        &global.lando_url,
+    );
+    let retry_policy = RetryPolicy {
+        max_retries: global.max_retries,
+        ..RetryPolicy::default()
+    };
+    let http = HttpClient::new(retry_policy, global.max_connections_per_host);
+    let ctx = Context {
+        client: TreeherderClient::with_endpoints(global.repo, endpoints)
+            .with_http_client(http)
+            .with_max_jobs(global.max_jobs),
+        json: global.json,
+        fail_on_incomplete: global.fail_on_incomplete,
+    };
+
+    match command {
+        Command::Summary(args) => run_summary(&ctx, args).await,
+        Command::Compare(args) => run_compare(&ctx, args).await,
+        Command::History(args) => run_history(&ctx, args).await,
+        Command::Perf(args) => run_perf(&ctx, args).await,
+        Command::Artifacts(args) => run_artifacts(&ctx, args).await,
+        Command::Logs(args) => run_logs(&ctx, args).await,
+        Command::Watch(args) => run_watch(&ctx, args).await,
+        Command::Cache(args) => run_cache(&ctx, args),
+        Command::Pushes(args) => run_pushes(&ctx, args).await,
+        Command::Health(args) => run_health(&ctx, args).await,
+        Command::Retrigger(args) => {
+            let action = JobAction::Retrigger { times: args.times };
+            run_action(&ctx, action, args.action).await
+        }
+        Command::Backfill(args) => {
+            let action = JobAction::Backfill { depth: args.depth };
+            run_action(&ctx, action, args.action).await
+        }
+        Command::Cancel(args) => run_action(&ctx, JobAction::Cancel, args.action).await,
+        Command::Classify(args) => {
+            let action = JobAction::Classify(args.classification());
+            run_action(&ctx, action, args.action).await
+        }
     }
+}
 
-    if args.compare.is_some() && args.use_cache {
-        anyhow::bail!("--compare cannot be used with --use-cache");
+fn spinner() -> ProgressBar {
+    let pb = ProgressBar::new_spinner();
+    pb.set_style(
+        ProgressStyle::default_spinner()
+            .template("{spinner:.green} {msg}")
+            .unwrap(),
+    );
+    pb
+}
+
+fn progress_bar(len: usize, message: &'static str) -> ProgressBar {
+    let pb = ProgressBar::new(len as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{bar:40.cyan/blue} {pos}/{len} {msg}")
+            .unwrap()
+            .progress_chars("=>-"),
+    );
+    pb.set_message(message);
+    pb
+}
+
+fn compile_pattern(pattern: Option<&String>) -> Result<Option<Regex>> {
+    Ok(match pattern {
+        Some(pattern_str) => Some(Regex::new(pattern_str)?),
+        None => None,
+    })
+}
+
+/// Resolves the push of `query` and returns its filtered jobs, showing the
+/// number of jobs read so far on `pb`.
+async fn fetch_filtered_jobs(
+    ctx: &Context,
+    query: &PushQuery,
+    pb: &ProgressBar,
+) -> Result<PushJobs> {
+    let fetched = Cell::new(0);
+    let push_jobs = ctx
+        .client
+        .filtered_jobs(query, |count| {
+            fetched.set(count);
+            pb.set_message(format!("Fetching jobs ({} so far)", count));
+        })
+        .await?;
+    warn_if_truncated(ctx, push_jobs.push_id, fetched.get());
+    Ok(push_jobs)
+}
+
+/// Fetches every job of `push_id`, showing the number read so far on `pb`.
+async fn fetch_all_jobs(ctx: &Context, push_id: u64, pb: &ProgressBar) -> Result<Vec<Job>> {
+    let jobs = ctx
+        .client
+        .jobs(push_id, |count| {
+            pb.set_message(format!("Fetching jobs ({} so far)", count))
+        })
+        .await?;
+    warn_if_truncated(ctx, push_id, jobs.len());
+    Ok(jobs)
+}
+
+fn warn_if_truncated(ctx: &Context, push_id: u64, fetched: usize) {
+    if fetched >= ctx.client.max_jobs() {
+        eprintln!(
+            "Warning: stopped after {} jobs of push {}; raise --max-jobs to fetch the rest",
+            fetched, push_id
+        );
     }
+}
 
-    if args.compare.is_some() && args.watch {
-        anyhow::bail!("--compare cannot be used with --watch");
+/// Resolves the revision, fetches its jobs and applies `filters`. Prints a
+/// message when no job matches.
+async fn load_filtered_jobs(
+    ctx: &Context,
+    revision_args: &RevisionArgs,
+    filters: &JobFilterArgs,
+) -> Result<PushJobs> {
+    let query = revision_args.query(filters)?;
+
+    let pb = spinner();
+    pb.set_message("Fetching push and jobs");
+    let push_jobs = fetch_filtered_jobs(ctx, &query, &pb).await?;
+
+    if push_jobs.jobs.is_empty() {
+        pb.finish_with_message("No jobs found matching criteria");
+        println!("No jobs found matching the specified criteria");
+    } else {
+        pb.finish_with_message(format!(
+            "Found {} jobs matching criteria",
+            push_jobs.jobs.len()
+        ));
     }
 
-    if let Some(job_id) = args.similar_history {
-        let client = Client::new();
-        let pb = ProgressBar::new_spinner();
-        pb.set_style(
-            ProgressStyle::default_spinner()
-                .template("{spinner:.green} {msg}")
-                .unwrap(),
-        );
-        pb.set_message(format!("Fetching similar jobs for job {}", job_id));
+    Ok(push_jobs)
+}
 
-        let history = fetch_similar_jobs(&client, &args.repo, job_id, args.similar_count).await?;
+async fn fetch_report(ctx: &Context, push_jobs: PushJobs, message: &'static str) -> PushReport {
+    let pb_jobs = progress_bar(push_jobs.jobs.len(), message);
+    let fetched = ctx
+        .client
+        .fetch_errors(push_jobs.jobs, || pb_jobs.inc(1))
+        .await;
+    pb_jobs.finish_with_message("Completed fetching job details");
+    PushReport {
+        revision: push_jobs.revision,
+        push_id: push_jobs.push_id,
+        jobs: fetched.items,
+        fetch_errors: fetched.fetch_errors,
+        pending_jobs: push_jobs.pending_jobs,
+    }
+}
 
-        pb.finish_with_message("Similar jobs fetched");
+/// With `--fail-on-incomplete`, turns an already printed report that is
+/// missing jobs or logs into an error.
+fn ensure_complete(ctx: &Context, fetch_errors: &[FetchError]) -> Result<()> {
+    if ctx.fail_on_incomplete && !fetch_errors.is_empty() {
+        anyhow::bail!(
+            "Report is incomplete: {} jobs, logs or artifacts could not be retrieved",
+            fetch_errors.len()
+        );
+    }
+    Ok(())
+}
 
-        if args.json {
-            let json_output = format_similar_history_json(&history)?;
+fn print_jobs_report(
+    ctx: &Context,
+    push_report: &PushReport,
+    report: &ReportArgs,
+    fetch_logs: bool,
+) -> Result<Outcome> {
+    let PushReport {
+        revision,
+        push_id,
+        jobs,
+        fetch_errors,
+        ..
+    } = push_report;
+
+    if report.group_by.is_some() {
+        let grouped = push_report.grouped_failures();
+        if ctx.json {
+            let json_output =
+                format_grouped_json_output(revision, *push_id, &grouped, fetch_errors)?;
             println!("{}", json_output);
         } else {
-            let markdown_output = format_similar_history_markdown(&history);
-            println!("{}", markdown_output);
+            let summary =
+                format_grouped_markdown_summary(revision, *push_id, &grouped, fetch_errors);
+            println!("{}", summary);
         }
+    } else if ctx.json {
+        let json_output = format_json_output(revision, *push_id, jobs, fetch_errors)?;
+        println!("{}", json_output);
+    } else {
+        let summary = format_markdown_summary(
+            revision,
+            *push_id,
+            jobs,
+            fetch_errors,
+            report.show_stack_traces,
+            fetch_logs,
+        );
+        println!("{}", summary);
+    }
 
-        return Ok(());
+    ensure_complete(ctx, fetch_errors)?;
+    Ok(push_report.status().into())
+}
+
+async fn run_summary(ctx: &Context, args: SummaryArgs) -> Result<Outcome> {
+    let push_jobs = load_filtered_jobs(ctx, &args.revision, &args.filters).await?;
+    if push_jobs.jobs.is_empty() {
+        return Ok(push_jobs.status().into());
     }
 
-    if args.use_cache {
-        let cache_dir = args
-            .cache_dir
-            .ok_or_else(|| anyhow::anyhow!("--use-cache requires --cache-dir to be specified"))?;
-        let cache_path = PathBuf::from(&cache_dir);
+    let push_report = fetch_report(ctx, push_jobs, "Fetching job details").await;
+    print_jobs_report(ctx, &push_report, &args.report, false)
+}
 
-        if !cache_path.exists() {
-            anyhow::bail!("Cache directory does not exist: {}", cache_path.display());
-        }
+/// Carries out `action` on the jobs selected by `args`, or with
+/// `--dry-run` lists what would be done.
+async fn run_action(ctx: &Context, action: JobAction, args: ActionArgs) -> Result<Outcome> {
+    let credentials = args.credentials();
+    if !args.dry_run {
+        credentials.check(&action)?;
+    }
 
-        println!("Loading cached data from: {}", cache_path.display());
+    let push_jobs = load_filtered_jobs(ctx, &args.revision, &args.filters).await?;
+    if push_jobs.jobs.is_empty() {
+        return Ok(Outcome::Green);
+    }
 
-        let metadata = load_cache_metadata(&cache_path)?;
-        println!(
-            "Push ID: {}, Revision: {}",
-            metadata.push_id, metadata.revision
+    let pb = spinner();
+    pb.set_message(format!(
+        "{}: {} jobs",
+        action.describe(),
+        push_jobs.jobs.len()
+    ));
+    let outcomes = ctx
+        .client
+        .act(
+            push_jobs.push_id,
+            &action,
+            &push_jobs.jobs,
+            &credentials,
+            args.dry_run,
+        )
+        .await?;
+    pb.finish_and_clear();
+
+    if ctx.json {
+        let json_output = format_actions_json(
+            &push_jobs.revision,
+            push_jobs.push_id,
+            args.dry_run,
+            &outcomes,
+        )?;
+        println!("{}", json_output);
+    } else {
+        let markdown_output = format_actions_markdown(
+            &push_jobs.revision,
+            push_jobs.push_id,
+            args.dry_run,
+            &outcomes,
         );
-        println!("Cached jobs: {}", metadata.jobs.len());
-
-        let mut filtered_jobs = metadata.jobs.clone();
-
-        filtered_jobs = match args.match_filter {
-            MatchFilter::Failure => filtered_jobs
-                .into_iter()
-                .filter(|job| job.result == "testfailed" || job.result == "busted")
-                .collect(),
-            MatchFilter::Success => filtered_jobs
-                .into_iter()
-                .filter(|job| job.result == "success")
-                .collect(),
-            MatchFilter::All => filtered_jobs,
-        };
+        println!("{}", markdown_output);
+    }
 
-        if let Some(filter_pattern) = &args.filter {
-            filtered_jobs.retain(|job| job.job_type_name.contains(filter_pattern));
-        }
+    let failed = outcomes
+        .iter()
+        .filter(|outcome| outcome.status == ActionStatus::Failed)
+        .count();
+    if failed > 0 {
+        anyhow::bail!("{} of {} actions failed", failed, outcomes.len());
+    }
+    Ok(Outcome::Green)
+}
 
-        if let Some(platform_pattern) = &args.platform {
-            let platform_regex = Regex::new(platform_pattern)?;
-            filtered_jobs.retain(|job| platform_regex.is_match(&job.platform));
-        }
+async fn run_history(ctx: &Context, args: HistoryArgs) -> Result<Outcome> {
+    let pb = spinner();
+    pb.set_message(format!("Fetching similar jobs for job {}", args.job_id));
 
-        if let Some(min_duration) = args.duration_min {
-            filtered_jobs.retain(|job| job.duration.is_some_and(|d| d >= min_duration));
-        }
+    let history = ctx.client.similar_jobs(args.job_id, args.count).await?;
 
-        if !args.include_intermittent {
-            filtered_jobs.retain(|job| job.failure_classification_id != Some(4));
-        }
+    pb.finish_with_message("Similar jobs fetched");
 
-        println!("Jobs matching filter: {}", filtered_jobs.len());
+    if ctx.json {
+        let json_output = format_similar_history_json(&history)?;
+        println!("{}", json_output);
+    } else {
+        let markdown_output = format_similar_history_markdown(&history);
+        println!("{}", markdown_output);
+    }
 
-        let pattern = if let Some(pattern_str) = &args.pattern {
-            Some(Regex::new(pattern_str)?)
-        } else {
-            None
-        };
+    Ok(Outcome::Green)
+}
 
-        let jobs_with_logs = search_cached_logs(&cache_path, &filtered_jobs, pattern.as_ref())?;
-
-        if args.group_by.is_some() {
-            let grouped = group_failures_by_test(&jobs_with_logs);
-            if args.json {
-                let json_output =
-                    format_grouped_json_output(&metadata.revision, metadata.push_id, &grouped)?;
-                println!("{}", json_output);
-            } else {
-                let summary =
-                    format_grouped_markdown_summary(&metadata.revision, metadata.push_id, &grouped);
-                println!("{}", summary);
-            }
-        } else if args.json {
-            let json_output =
-                format_json_output(&metadata.revision, metadata.push_id, &jobs_with_logs)?;
-            println!("{}", json_output);
-        } else {
-            let summary = format_markdown_summary(
-                &metadata.revision,
-                metadata.push_id,
-                &jobs_with_logs,
-                args.show_stack_traces,
-                true,
-            );
-            println!("{}", summary);
-        }
+fn run_cache(ctx: &Context, args: CacheArgs) -> Result<Outcome> {
+    let cache_path = PathBuf::from(&args.cache_dir);
 
-        return Ok(());
+    if !cache_path.exists() {
+        anyhow::bail!("Cache directory does not exist: {}", cache_path.display());
     }
 
-    let client = Client::new();
+    println!("Loading cached data from: {}", cache_path.display());
 
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.green} {msg}")
-            .unwrap(),
+    let metadata = load_cache_metadata(&cache_path)?;
+    println!(
+        "Push ID: {}, Revision: {}",
+        metadata.push_id, metadata.revision
     );
+    println!("Cached jobs: {}", metadata.jobs.len());
+
+    let filter = args.filters.to_filter();
+    let pending_jobs = filter.pending(&metadata.jobs)?;
+    let filtered_jobs = filter.apply(metadata.jobs)?;
+
+    println!("Jobs matching filter: {}", filtered_jobs.len());
+
+    let pattern = compile_pattern(args.pattern.as_ref())?;
+
+    let (jobs, fetch_errors) = search_cached_logs(&cache_path, &filtered_jobs, pattern.as_ref())?;
+
+    let push_report = PushReport {
+        revision: metadata.revision,
+        push_id: metadata.push_id,
+        jobs,
+        fetch_errors,
+        pending_jobs,
+    };
+    print_jobs_report(ctx, &push_report, &args.report, true)
+}
+
+async fn run_compare(ctx: &Context, args: CompareArgs) -> Result<Outcome> {
+    let filter = JobFilter {
+        include_intermittent: args.include_intermittent,
+        ..JobFilter::default()
+    };
+    let query = PushQuery::new(args.input).with_filter(filter.clone());
+    let baseline = PushQuery::new(args.compare).with_filter(filter);
+
+    let pb = spinner();
+    pb.set_message("Comparison mode: fetching both revisions");
+    let comparison_result = ctx.client.compare(&query, &baseline).await?;
+    pb.finish_with_message("Comparison complete");
+
+    if ctx.json {
+        let json_output = format_comparison_json(&comparison_result)?;
+        println!("{}", json_output);
+    } else {
+        let markdown_output = format_comparison_markdown(&comparison_result);
+        println!("{}", markdown_output);
+    }
+
+    ensure_complete(ctx, &comparison_result.fetch_errors)?;
+    if comparison_result.new_failures.is_empty() {
+        Ok(Outcome::Green)
+    } else {
+        Ok(Outcome::NewRegressions)
+    }
+}
+
+async fn run_health(ctx: &Context, args: HealthArgs) -> Result<Outcome> {
+    let query = args.revision.push()?;
+    let pb = spinner();
+    pb.set_message("Resolving revision");
+    let revision = ctx.client.resolve_revision(&query.revision).await?;
+    pb.set_message("Fetching push health");
+    let health = ctx.client.push_health(&revision).await?;
+    pb.finish_with_message("Push health fetched");
+
+    if ctx.json {
+        println!("{}", format_push_health_json(&health)?);
+    } else {
+        println!("{}", format_push_health_markdown(&health));
+    }
+
+    Ok(match health.result.as_str() {
+        "pass" => Outcome::Green,
+        "fail" => Outcome::Failures,
+        _ => Outcome::Pending,
+    })
+}
+
+async fn run_pushes(ctx: &Context, args: PushesArgs) -> Result<Outcome> {
+    let pb = spinner();
+    pb.set_message("Fetching pushes");
+    let pushes = ctx.client.pushes(&args.range()).await?;
+    pb.finish_and_clear();
+
+    let pb_pushes = progress_bar(pushes.len(), "Fetching push jobs and errors");
+    let rollups = ctx
+        .client
+        .rollup(&pushes, &args.filters.to_filter(), || pb_pushes.inc(1))
+        .await;
+    pb_pushes.finish_with_message("Completed fetching pushes");
+
+    if ctx.json {
+        println!("{}", format_push_rollup_json(ctx.client.repo(), &rollups)?);
+    } else {
+        println!(
+            "{}",
+            format_push_rollup_markdown(ctx.client.repo(), &rollups)
+        );
+    }
+
+    let fetch_errors: Vec<FetchError> = rollups
+        .iter()
+        .flat_map(|rollup| rollup.fetch_errors.iter().cloned())
+        .collect();
+    ensure_complete(ctx, &fetch_errors)?;
+    let failed_pushes = rollups
+        .iter()
+        .filter(|rollup| rollup.error.is_some())
+        .count();
+    if ctx.fail_on_incomplete && failed_pushes > 0 {
+        anyhow::bail!(
+            "Report is incomplete: the jobs of {} pushes could not be retrieved",
+            failed_pushes
+        );
+    }
+
+    let statuses: Vec<PushStatus> = rollups.iter().filter_map(|rollup| rollup.status).collect();
+    Ok(if statuses.contains(&PushStatus::Failed) {
+        Outcome::Failures
+    } else if statuses.contains(&PushStatus::Pending) {
+        Outcome::Pending
+    } else {
+        Outcome::Green
+    })
+}
+
+async fn run_watch(ctx: &Context, args: WatchArgs) -> Result<Outcome> {
+    let queries = args.queries()?;
+    let notifiers = args
+        .notify
+        .iter()
+        .map(|target| target.notifier(ctx.client.http(), &args.matrix_account()))
+        .collect::<Result<Vec<_>>>()?;
+    let dashboard = MultiProgress::new();
+
+    // Revisions are resolved up front so that a wrong one fails right away,
+    // except those of Lando jobs, which are only known once they land.
+    let revisions = try_join_all(queries.iter().map(|query| async move {
+        match &query.revision {
+            RevisionSpec::LandoJob(_) => Ok(None),
+            revision => ctx.client.resolve_revision(revision).await.map(Some),
+        }
+    }))
+    .await?;
+
+    let results =
+        join_all(queries.iter().zip(revisions).map(|(query, revision)| {
+            watch_push(ctx, &args, query, revision, &notifiers, &dashboard)
+        }))
+        .await;
+
+    let mut outcomes = Vec::new();
+    let mut errors = Vec::new();
+    for result in results {
+        match result {
+            Ok(outcome) => outcomes.push(outcome),
+            Err(e) => errors.push(e),
+        }
+    }
+    if errors.len() > 1 {
+        for e in &errors {
+            eprintln!("Error: {:?}", e);
+        }
+        anyhow::bail!("{} of {} watches failed", errors.len(), queries.len());
+    }
+    if let Some(e) = errors.pop() {
+        return Err(e);
+    }
+    Ok(if outcomes.contains(&Outcome::Failures) {
+        Outcome::Failures
+    } else if outcomes.contains(&Outcome::Pending) {
+        Outcome::Pending
+    } else {
+        Outcome::Green
+    })
+}
 
-    // If using --lando-job-id with --watch, wait for the job to land first
-    if let Some(lando_job_id) = args.lando_job_id {
-        if args.watch {
-            pb.set_message(format!("Waiting for Lando job {} to land...", lando_job_id));
+/// The first 12 characters of a revision, as Treeherder shows it.
+fn short_revision(revision: &str) -> &str {
+    &revision[..revision.len().min(12)]
+}
+
+/// Watches one push, with a line of `dashboard` showing its progress, and
+/// prints its report once the watch ends. `revision` is unset for a Lando
+/// job that may not have landed yet.
+async fn watch_push(
+    ctx: &Context,
+    args: &WatchArgs,
+    query: &PushQuery,
+    revision: Option<String>,
+    notifiers: &[Box<dyn Notifier>],
+    dashboard: &MultiProgress,
+) -> Result<Outcome> {
+    let watch_pb = dashboard.add(spinner());
+
+    let revision = match (revision, &query.revision) {
+        (Some(revision), _) => revision,
+        (None, RevisionSpec::LandoJob(lando_job_id)) => {
+            let lando_job_id = *lando_job_id;
+            watch_pb.set_message(format!("Waiting for Lando job {} to land...", lando_job_id));
 
             loop {
-                match fetch_lando_job_status(&client, lando_job_id).await {
+                match ctx.client.lando_job_status(lando_job_id).await {
                     Ok(status) if status.status == "LANDED" => {
-                        pb.set_message(format!("Lando job {} has landed!", lando_job_id));
+                        watch_pb.set_message(format!("Lando job {} has landed!", lando_job_id));
                         break;
                     }
                     Ok(status) => {
-                        pb.set_message(format!(
+                        watch_pb.set_message(format!(
                             "Lando job {} status: {}. Checking again in {} seconds...",
-                            lando_job_id, status.status, args.watch_interval
+                            lando_job_id, status.status, args.interval
                         ));
                     }
                     Err(e) => {
-                        pb.set_message(format!(
+                        watch_pb.set_message(format!(
                             "Error checking Lando job {}: {}. Retrying in {} seconds...",
-                            lando_job_id, e, args.watch_interval
+                            lando_job_id, e, args.interval
                         ));
                     }
                 }
 
-                tokio::time::sleep(tokio::time::Duration::from_secs(args.watch_interval)).await;
+                tokio::time::sleep(Duration::from_secs(args.interval)).await;
             }
+            ctx.client.resolve_revision(&query.revision).await?
         }
-    }
-
-    pb.set_message("Extracting revision from input");
-    let revision = if let Some(lando_job_id) = args.lando_job_id {
-        pb.set_message(format!(
-            "Fetching commit hash from Lando job {}",
-            lando_job_id
-        ));
-        fetch_commit_from_lando_job(&client, lando_job_id).await?
-    } else {
-        let input = args.input.as_ref().unwrap();
-        extract_revision(input)?
+        (None, revision) => ctx.client.resolve_revision(revision).await?,
     };
+    let label = short_revision(&revision).to_string();
 
-    pb.set_message("Fetching push ID");
-    let push_id = fetch_push_id(&client, &args.repo, &revision).await?;
-
-    if let Some(compare_revision_input) = &args.compare {
-        pb.set_message("Comparison mode: fetching both revisions");
-
-        let compare_revision = extract_revision(compare_revision_input)?;
-        let compare_push_id = fetch_push_id(&client, &args.repo, &compare_revision).await?;
-
-        pb.set_message("Fetching jobs for base revision");
-        let base_jobs = fetch_jobs(&client, push_id).await?;
+    watch_pb.set_message(format!("{}: fetching push ID", label));
+    let push_id = ctx.client.push_id(&revision).await?;
 
-        pb.set_message("Fetching jobs for comparison revision");
-        let compare_jobs = fetch_jobs(&client, compare_push_id).await?;
-
-        let base_failed: Vec<_> = base_jobs
-            .into_iter()
-            .filter(|job| job.result == "testfailed" || job.result == "busted")
-            .collect();
-
-        let compare_failed: Vec<_> = compare_jobs
-            .into_iter()
-            .filter(|job| job.result == "testfailed" || job.result == "busted")
-            .collect();
+    let event = |kind: WatchEventKind| WatchEvent {
+        kind,
+        revision: revision.clone(),
+        push_id,
+    };
+    let emit = |event: &WatchEvent| -> Result<()> {
+        if ctx.json {
+            println!("{}", serde_json::to_string(event)?);
+        } else if let Some(text) = format_watch_event_markdown(event) {
+            dashboard.suspend(|| println!("{}", text));
+        }
+        Ok(())
+    };
 
-        let base_filtered: Vec<_> = if args.include_intermittent {
-            base_failed
-        } else {
-            base_failed
-                .into_iter()
-                .filter(|job| job.failure_classification_id != Some(4))
-                .collect()
-        };
+    // Jobs are followed whatever their result; the filter's results and
+    // classifications only decide which failures are reported.
+    let watched_filter = query.filter.any_result();
+    let mut tracker = JobTracker::new();
+    let mut failures: HashMap<u64, JobWithLogs> = HashMap::new();
+    let mut fetch_errors = Vec::new();
+    let mut passed = 0;
+    let mut failed = 0;
+    let mut job_list = JobList::new();
+    let mut schedule = PollSchedule::new(
+        Duration::from_secs(args.min_interval),
+        Duration::from_secs(args.interval),
+    );
 
-        let compare_filtered: Vec<_> = if args.include_intermittent {
-            compare_failed
-        } else {
-            compare_failed
-                .into_iter()
-                .filter(|job| job.failure_classification_id != Some(4))
-                .collect()
+    let (all_jobs, until) = loop {
+        // After the first poll, only the jobs modified since are fetched.
+        let modified = match job_list.last_modified().map(str::to_string) {
+            Some(since) => {
+                ctx.client
+                    .jobs_modified_since(push_id, &since, |_| {})
+                    .await?
+            }
+            None => fetch_all_jobs(ctx, push_id, &watch_pb).await?,
         };
-
-        pb.set_message("Fetching error details for base revision");
-        let client_arc = Arc::new(client);
-
-        let pb_base = ProgressBar::new(base_filtered.len() as u64);
-        pb_base.set_style(
-            ProgressStyle::default_bar()
-                .template("{bar:40.cyan/blue} {pos}/{len} {msg}")
-                .unwrap()
-                .progress_chars("=>-"),
-        );
-        pb_base.set_message("Fetching base job errors");
-        let pb_base = Arc::new(pb_base);
-
-        let base_jobs_with_errors: Vec<_> = stream::iter(base_filtered)
-            .map(|job| {
-                let client = Arc::clone(&client_arc);
-                let repo = args.repo.clone();
-                let pb = Arc::clone(&pb_base);
-                async move {
-                    let result = fetch_job_details_with_errors(&client, &repo, job).await;
-                    pb.inc(1);
-                    result
-                }
-            })
-            .buffer_unordered(10)
-            .collect::<Vec<_>>()
-            .await
-            .into_iter()
-            .filter_map(|result| result.ok())
+        job_list.merge(modified);
+        let all_jobs = job_list.jobs();
+        let watched = watched_filter.apply(all_jobs.clone())?;
+        let reported: HashSet<u64> = query
+            .filter
+            .apply(all_jobs.clone())?
+            .iter()
+            .map(|job| job.id)
             .collect();
 
-        pb_base.finish_with_message("Done fetching base errors");
-
-        let pb_compare = ProgressBar::new(compare_filtered.len() as u64);
-        pb_compare.set_style(
-            ProgressStyle::default_bar()
-                .template("{bar:40.cyan/blue} {pos}/{len} {msg}")
-                .unwrap()
-                .progress_chars("=>-"),
-        );
-        pb_compare.set_message("Fetching comparison job errors");
-        let pb_compare = Arc::new(pb_compare);
-
-        let compare_jobs_with_errors: Vec<_> = stream::iter(compare_filtered)
-            .map(|job| {
-                let client = Arc::clone(&client_arc);
-                let repo = args.repo.clone();
-                let pb = Arc::clone(&pb_compare);
-                async move {
-                    let result = fetch_job_details_with_errors(&client, &repo, job).await;
-                    pb.inc(1);
-                    result
+        let mut newly_failed = Vec::new();
+        let changes = tracker.update(&watched);
+        let changed = !changes.is_empty();
+        for change in changes {
+            match change {
+                JobChange::Started(job) => emit(&event(WatchEventKind::JobStarted { job }))?,
+                JobChange::Completed(job)
+                    if job.result.is_failure() && reported.contains(&job.id) =>
+                {
+                    newly_failed.push(job)
                 }
-            })
-            .buffer_unordered(10)
-            .collect::<Vec<_>>()
-            .await
-            .into_iter()
-            .filter_map(|result| result.ok())
-            .collect();
-
-        pb_compare.finish_with_message("Done fetching comparison errors");
-        pb.finish_with_message("Comparison complete");
-
-        let base_with_logs: Vec<_> = base_jobs_with_errors
-            .into_iter()
-            .map(|(job, errors)| JobWithLogs {
-                job,
-                errors,
-                log_matches: vec![],
-                log_dir: None,
-            })
-            .collect();
-
-        let compare_with_logs: Vec<_> = compare_jobs_with_errors
-            .into_iter()
-            .map(|(job, errors)| JobWithLogs {
-                job,
-                errors,
-                log_matches: vec![],
-                log_dir: None,
-            })
-            .collect();
-
-        let comparison_result = compare_failures(
-            &base_with_logs,
-            &compare_with_logs,
-            &revision,
-            &compare_revision,
-            push_id,
-            compare_push_id,
-        );
-
-        if args.json {
-            let json_output = format_comparison_json(&comparison_result)?;
-            println!("{}", json_output);
-        } else {
-            let markdown_output = format_comparison_markdown(&comparison_result);
-            println!("{}", markdown_output);
+                JobChange::Completed(job) if job.result == JobResult::Success => {
+                    passed += 1;
+                    emit(&event(WatchEventKind::JobPassed { job }))?
+                }
+                JobChange::Completed(_) => {}
+            }
         }
 
-        return Ok(());
-    }
-
-    pb.set_message("Fetching jobs");
-    let mut all_jobs = fetch_jobs(&client, push_id).await?;
-
-    if args.watch {
-        pb.finish_with_message("Watch mode: monitoring job progress");
-
-        let watch_pb = ProgressBar::new_spinner();
-        watch_pb.set_style(
-            ProgressStyle::default_spinner()
-                .template("{spinner:.green} {msg}")
-                .unwrap(),
-        );
-
-        while !are_all_jobs_complete(&all_jobs) {
-            let (completed, running, pending) = count_job_states(&all_jobs);
+        failed += newly_failed.len();
+        if !newly_failed.is_empty() {
             watch_pb.set_message(format!(
-                "Jobs: {} completed, {} running, {} pending",
-                completed, running, pending
+                "{}: fetching failures of {} jobs",
+                label,
+                newly_failed.len()
             ));
+            let fetched = ctx.client.fetch_errors(newly_failed.clone(), || {}).await;
+            let mut first_event = None;
+            let mut notified_failures = Vec::new();
+            for job in &newly_failed {
+                let details = fetched.items.iter().find(|item| item.job.id == job.id);
+                let job_fetch_errors: Vec<FetchError> = fetched
+                    .fetch_errors
+                    .iter()
+                    .filter(|e| e.job_id == job.id)
+                    .cloned()
+                    .collect();
+                let failed_event = event(WatchEventKind::JobFailed {
+                    job: job.clone(),
+                    errors: details.map(|d| d.errors.clone()).unwrap_or_default(),
+                    fetch_errors: job_fetch_errors.clone(),
+                });
+                emit(&failed_event)?;
+                first_event.get_or_insert(failed_event);
+                notified_failures.push(match details {
+                    Some(details) => NotifiedFailure::from(details),
+                    None => NotifiedFailure::from(job),
+                });
+                if let Some(details) = details {
+                    failures.insert(job.id, details.clone());
+                }
+                fetch_errors.extend(job_fetch_errors);
+            }
 
-            tokio::time::sleep(tokio::time::Duration::from_secs(args.watch_interval)).await;
-            all_jobs = fetch_jobs(&client, push_id).await?;
+            // One notification per poll, however many jobs failed in it.
+            if let Some(event) = first_event {
+                let first = &newly_failed[0];
+                let message = match newly_failed.len() {
+                    1 => format!(
+                        "{} {} failed on {}",
+                        first.symbol_notation(),
+                        first.job_type_name,
+                        label
+                    ),
+                    count => format!(
+                        "{} jobs failed on {}, including {} {}",
+                        count,
+                        label,
+                        first.symbol_notation(),
+                        first.job_type_name
+                    ),
+                };
+                let notification = Notification {
+                    title: "Treeherder Jobs Failed".to_string(),
+                    message,
+                    event,
+                    failures: notified_failures,
+                };
+                send_notifications(notifiers, &notification).await;
+            }
         }
 
-        watch_pb.finish_with_message("All jobs completed!");
-
-        if args.notify {
-            let (completed, _, _) = count_job_states(&all_jobs);
-            let failed_count = all_jobs
-                .iter()
-                .filter(|j| j.result == "testfailed" || j.result == "busted")
-                .count();
-
-            let message = if failed_count > 0 {
-                format!("{} of {} jobs failed", failed_count, completed)
-            } else {
-                format!("All {} jobs passed!", completed)
-            };
-
-            if let Err(e) = send_notification("Treeherder Jobs Complete", &message) {
-                eprintln!("Failed to send notification: {}", e);
-            }
+        if let Some(until) = args
+            .until
+            .iter()
+            .find(|condition| condition.is_met(&all_jobs, &watched, failed))
+        {
+            break (all_jobs, *until);
         }
-    }
+        let (completed, running, pending) = count_job_states(&watched);
+        let delay = schedule.next_delay(changed, running > 0);
+        watch_pb.set_message(format!(
+            "{}: {} completed, {} running, {} pending; next poll in {}s",
+            label,
+            completed,
+            running,
+            pending,
+            delay.as_secs()
+        ));
+        tokio::time::sleep(delay).await;
+    };
+
+    watch_pb.finish_and_clear();
 
-    let mut filtered_jobs: Vec<_> = match args.match_filter {
-        MatchFilter::Failure => all_jobs
-            .into_iter()
-            .filter(|job| job.result == "testfailed" || job.result == "busted")
-            .collect(),
-        MatchFilter::Success => all_jobs
-            .into_iter()
-            .filter(|job| job.result == "success")
-            .collect(),
-        MatchFilter::All => all_jobs,
+    let reported_jobs = query.filter.apply(all_jobs.clone())?;
+    let status = if failed > 0 {
+        PushStatus::Failed
+    } else {
+        PushStatus::Green
     };
+    let total_jobs = JobFilter::default()
+        .any_result()
+        .apply(all_jobs.clone())?
+        .len();
+    let watched = watched_filter.apply(all_jobs)?;
+    let filtered = watched.len() < total_jobs;
+    let complete_event = event(WatchEventKind::PushComplete {
+        status,
+        until,
+        jobs: watched.len(),
+        total_jobs,
+        completed: count_job_states(&watched).0,
+        failed,
+        passed,
+    });
+    emit(&complete_event)?;
+
+    let message = if failed > 0 {
+        format!("{} jobs of {} failed", failed, label)
+    } else if filtered {
+        format!("All {} filtered jobs of {} passed!", passed, label)
+    } else {
+        format!("All {} jobs of {} passed!", passed, label)
+    };
+    let mut notified_failures: Vec<NotifiedFailure> = reported_jobs
+        .iter()
+        .filter(|job| job.result.is_failure())
+        .map(|job| match failures.get(&job.id) {
+            Some(details) => NotifiedFailure::from(details),
+            None => NotifiedFailure::from(job),
+        })
+        .collect();
+    notified_failures.sort_by_key(|failure| failure.job_id);
+    let notification = Notification {
+        title: "Treeherder Jobs Complete".to_string(),
+        message,
+        event: complete_event,
+        failures: notified_failures,
+    };
+    send_notifications(notifiers, &notification).await;
 
-    if let Some(filter_pattern) = &args.filter {
-        filtered_jobs.retain(|job| job.job_type_name.contains(filter_pattern));
+    if ctx.json {
+        ensure_complete(ctx, &fetch_errors)?;
+        return Ok(status.into());
     }
-
-    if let Some(platform_pattern) = &args.platform {
-        let platform_regex = Regex::new(platform_pattern)?;
-        filtered_jobs.retain(|job| platform_regex.is_match(&job.platform));
+    if reported_jobs.is_empty() {
+        dashboard.suspend(|| println!("No jobs found matching the specified criteria"));
+        return Ok(status.into());
     }
 
-    if let Some(min_duration) = args.duration_min {
-        filtered_jobs.retain(|job| job.duration.is_some_and(|d| d >= min_duration));
-    }
+    // The final report reuses the failures fetched while watching.
+    let (known, unknown): (Vec<Job>, Vec<Job>) = reported_jobs
+        .into_iter()
+        .partition(|job| failures.contains_key(&job.id));
+    let fetched = ctx.client.fetch_errors(unknown, || {}).await;
+    let mut jobs: Vec<JobWithLogs> = known
+        .iter()
+        .filter_map(|job| failures.remove(&job.id))
+        .collect();
+    jobs.extend(fetched.items);
+    jobs.sort_by_key(|job| job.job.id);
+    fetch_errors.extend(fetched.fetch_errors);
+
+    let push_report = PushReport {
+        revision,
+        push_id,
+        jobs,
+        fetch_errors,
+        pending_jobs: 0,
+    };
+    dashboard.suspend(|| print_jobs_report(ctx, &push_report, &args.report, false))
+}
 
-    if !args.include_intermittent {
-        filtered_jobs.retain(|job| job.failure_classification_id != Some(4));
+/// Sends `notification` through every notifier; failures are only reported.
+async fn send_notifications(notifiers: &[Box<dyn Notifier>], notification: &Notification) {
+    let results = join_all(
+        notifiers
+            .iter()
+            .map(|notifier| notifier.notify(notification)),
+    )
+    .await;
+    for error in results.into_iter().filter_map(Result::err) {
+        eprintln!("Failed to send notification: {}", error);
     }
+}
 
-    if filtered_jobs.is_empty() {
-        pb.finish_with_message("No jobs found matching criteria");
-        println!("No jobs found matching the specified criteria");
-        return Ok(());
+async fn run_logs(ctx: &Context, args: LogsArgs) -> Result<Outcome> {
+    let push_jobs = load_filtered_jobs(ctx, &args.revision, &args.filters).await?;
+    if push_jobs.jobs.is_empty() {
+        return Ok(push_jobs.status().into());
     }
-
-    pb.finish_with_message(format!(
-        "Found {} jobs matching criteria",
-        filtered_jobs.len()
-    ));
-
-    let (_temp_dir_guard, log_storage_path) = if args.fetch_logs {
-        if let Some(cache_dir) = &args.cache_dir {
-            let cache_path = PathBuf::from(cache_dir);
-            fs::create_dir_all(&cache_path)?;
-            (None, cache_path)
-        } else {
-            let temp_dir = TempDir::new()?;
-            let temp_path = temp_dir.path().to_path_buf();
-            (Some(temp_dir), temp_path)
-        }
+    let PushJobs {
+        revision,
+        push_id,
+        jobs: filtered_jobs,
+        pending_jobs,
+    } = push_jobs;
+
+    let (temp_dir_guard, log_storage_path) = if let Some(cache_dir) = &args.cache_dir {
+        let cache_path = PathBuf::from(cache_dir);
+        fs::create_dir_all(&cache_path)?;
+        (None, cache_path)
     } else {
-        (None, PathBuf::from("/tmp"))
+        let temp_dir = TempDir::new()?;
+        let temp_path = temp_dir.path().to_path_buf();
+        (Some(temp_dir), temp_path)
     };
 
-    let pattern = if let Some(pattern_str) = &args.pattern {
-        Some(Regex::new(pattern_str)?)
-    } else {
-        None
-    };
+    let pattern = compile_pattern(args.pattern.as_ref())?;
 
-    let client = Arc::new(client);
+    let pb_logs = progress_bar(filtered_jobs.len(), "Fetching and processing logs");
 
-    if args.fetch_logs {
-        let pb_logs = ProgressBar::new(filtered_jobs.len() as u64);
-        pb_logs.set_style(
-            ProgressStyle::default_bar()
-                .template("{bar:40.cyan/blue} {pos}/{len} {msg}")
-                .unwrap()
-                .progress_chars("=>-"),
-        );
-        pb_logs.set_message("Fetching and processing logs");
-
-        let pb_logs = Arc::new(pb_logs);
-
-        let jobs_with_logs: Vec<_> = stream::iter(filtered_jobs.clone())
-            .map(|job| {
-                let client = Arc::clone(&client);
-                let repo = args.repo.clone();
-                let pb_logs = Arc::clone(&pb_logs);
-                let log_path = log_storage_path.clone();
-                let pattern = pattern.as_ref();
-
-                async move {
-                    let result =
-                        fetch_job_with_full_logs(&client, &repo, job, &log_path, pattern).await;
-                    pb_logs.inc(1);
-                    result
-                }
-            })
-            .buffer_unordered(5)
-            .collect::<Vec<_>>()
-            .await
-            .into_iter()
-            .filter_map(|result| result.ok())
-            .collect();
+    let fetched = ctx
+        .client
+        .fetch_logs(
+            filtered_jobs.clone(),
+            &log_storage_path,
+            pattern.as_ref(),
+            || pb_logs.inc(1),
+        )
+        .await;
 
-        pb_logs.finish_with_message("Completed fetching and processing logs");
-
-        if args.cache_dir.is_some() {
-            let metadata = CachedPushMetadata {
-                revision: revision.clone(),
-                push_id,
-                repo: args.repo.clone(),
-                jobs: filtered_jobs.clone(),
-            };
-            save_cache_metadata(&log_storage_path, &metadata)?;
-            if !args.json {
-                println!(
-                    "\nMetadata saved to: {}",
-                    log_storage_path.join("metadata.json").display()
-                );
-            }
-        }
+    pb_logs.finish_with_message("Completed fetching and processing logs");
 
-        if args.group_by.is_some() {
-            let grouped = group_failures_by_test(&jobs_with_logs);
-            if args.json {
-                let json_output = format_grouped_json_output(&revision, push_id, &grouped)?;
-                println!("{}", json_output);
-            } else {
-                let summary = format_grouped_markdown_summary(&revision, push_id, &grouped);
-                println!("{}", summary);
-            }
-        } else if args.json {
-            let json_output = format_json_output(&revision, push_id, &jobs_with_logs)?;
-            println!("{}", json_output);
-        } else {
-            let summary = format_markdown_summary(
-                &revision,
-                push_id,
-                &jobs_with_logs,
-                args.show_stack_traces,
-                args.fetch_logs,
+    if args.cache_dir.is_some() {
+        let metadata = CachedPushMetadata {
+            revision: revision.clone(),
+            push_id,
+            repo: ctx.client.repo().to_string(),
+            jobs: filtered_jobs,
+        };
+        save_cache_metadata(&log_storage_path, &metadata)?;
+        if !ctx.json {
+            println!(
+                "\nMetadata saved to: {}",
+                log_storage_path.join("metadata.json").display()
             );
-            println!("{}", summary);
         }
+    }
 
-        if !args.json {
-            if let Some(temp_dir) = _temp_dir_guard.as_ref() {
-                println!(
-                    "\nLogs are stored in temporary directory: {}",
-                    temp_dir.path().display()
-                );
-                println!("The directory will be automatically cleaned up when the program exits.");
-            } else if args.cache_dir.is_some() {
-                println!(
-                    "\nLogs are stored persistently in: {}",
-                    log_storage_path.display()
-                );
-                println!(
-                    "Use --use-cache --cache-dir {} to query these logs later.",
-                    log_storage_path.display()
-                );
-            }
-        }
-    } else if args.download_artifacts {
-        let artifact_dir = if let Some(cache_dir) = &args.cache_dir {
-            let cache_path = PathBuf::from(cache_dir);
-            fs::create_dir_all(&cache_path)?;
-            cache_path
-        } else {
-            let dir = PathBuf::from(format!("artifacts-{}", revision));
-            fs::create_dir_all(&dir)?;
-            dir
-        };
-
-        let artifact_pattern = if let Some(pattern_str) = &args.artifact_pattern {
-            Some(Regex::new(pattern_str)?)
-        } else {
-            None
-        };
-
-        let pb_artifacts = ProgressBar::new(filtered_jobs.len() as u64);
-        pb_artifacts.set_style(
-            ProgressStyle::default_bar()
-                .template("{bar:40.cyan/blue} {pos}/{len} {msg}")
-                .unwrap()
-                .progress_chars("=>-"),
-        );
-        pb_artifacts.set_message("Downloading artifacts");
-
-        let pb_artifacts = Arc::new(pb_artifacts);
-
-        let all_downloaded: Vec<_> = stream::iter(filtered_jobs)
-            .map(|job| {
-                let client = Arc::clone(&client);
-                let repo = args.repo.clone();
-                let pb = Arc::clone(&pb_artifacts);
-                let output_dir = artifact_dir.clone();
-                let pattern = artifact_pattern.as_ref();
-
-                async move {
-                    let result =
-                        download_job_artifacts(&client, &repo, &job, &output_dir, pattern).await;
-                    pb.inc(1);
-                    result
-                }
-            })
-            .buffer_unordered(3)
-            .collect::<Vec<_>>()
-            .await;
-
-        pb_artifacts.finish_with_message("Completed downloading artifacts");
+    let push_report = PushReport {
+        revision,
+        push_id,
+        jobs: fetched.items,
+        fetch_errors: fetched.fetch_errors,
+        pending_jobs,
+    };
+    let printed = print_jobs_report(ctx, &push_report, &args.report, true);
 
-        let total_files: usize = all_downloaded
-            .iter()
-            .filter_map(|r| r.as_ref().ok())
-            .map(|v| v.len())
-            .sum();
-
-        if args.json {
-            let output = serde_json::json!({
-                "revision": revision,
-                "push_id": push_id,
-                "artifact_dir": artifact_dir.display().to_string(),
-                "total_files": total_files,
-            });
-            println!("{}", serde_json::to_string_pretty(&output)?);
-        } else {
-            println!("\n## Artifacts Downloaded\n");
-            println!("**Revision:** `{}`", revision);
-            println!("**Output directory:** `{}`", artifact_dir.display());
-            println!("**Total files:** {}", total_files);
+    if !ctx.json {
+        if let Some(temp_dir) = temp_dir_guard.as_ref() {
+            println!(
+                "\nLogs are stored in temporary directory: {}",
+                temp_dir.path().display()
+            );
+            println!("The directory will be automatically cleaned up when the program exits.");
+        } else if args.cache_dir.is_some() {
+            println!(
+                "\nLogs are stored persistently in: {}",
+                log_storage_path.display()
+            );
+            println!(
+                "Use `treeherder-cli cache --cache-dir {}` to query these logs later.",
+                log_storage_path.display()
+            );
         }
-    } else if args.perf {
-        let pb_perf = ProgressBar::new(filtered_jobs.len() as u64);
-        pb_perf.set_style(
-            ProgressStyle::default_bar()
-                .template("{bar:40.cyan/blue} {pos}/{len} {msg}")
-                .unwrap()
-                .progress_chars("=>-"),
-        );
-        pb_perf.set_message("Fetching performance data");
-
-        let pb_perf = Arc::new(pb_perf);
-
-        let perf_data: Vec<_> = stream::iter(filtered_jobs)
-            .map(|job| {
-                let client = Arc::clone(&client);
-                let repo = args.repo.clone();
-                let pb = Arc::clone(&pb_perf);
+    }
 
-                async move {
-                    let result = fetch_job_perf_data(&client, &repo, &job).await;
-                    pb.inc(1);
-                    result
-                }
-            })
-            .buffer_unordered(5)
-            .collect::<Vec<_>>()
-            .await
-            .into_iter()
-            .filter_map(|r| r.ok())
-            .collect();
+    printed
+}
 
-        pb_perf.finish_with_message("Completed fetching performance data");
+async fn run_artifacts(ctx: &Context, args: ArtifactsArgs) -> Result<Outcome> {
+    let PushJobs {
+        revision,
+        push_id,
+        jobs: filtered_jobs,
+        ..
+    } = load_filtered_jobs(ctx, &args.revision, &args.filters).await?;
+    if filtered_jobs.is_empty() {
+        return Ok(Outcome::Green);
+    }
 
-        if args.json {
-            let json_output = format_perf_json(&revision, push_id, &perf_data)?;
-            println!("{}", json_output);
-        } else {
-            let markdown_output = format_perf_markdown(&revision, push_id, &perf_data);
-            println!("{}", markdown_output);
-        }
+    let artifact_dir = match &args.output_dir {
+        Some(output_dir) => PathBuf::from(output_dir),
+        None => PathBuf::from(format!("artifacts-{}", revision)),
+    };
+    fs::create_dir_all(&artifact_dir)?;
+
+    let artifact_pattern = compile_pattern(args.artifact_pattern.as_ref())?;
+
+    let pb_artifacts = progress_bar(filtered_jobs.len(), "Downloading artifacts");
+
+    let fetched = ctx
+        .client
+        .download_artifacts(
+            filtered_jobs,
+            &artifact_dir,
+            artifact_pattern.as_ref(),
+            || pb_artifacts.inc(1),
+        )
+        .await;
+
+    pb_artifacts.finish_with_message("Completed downloading artifacts");
+
+    let total_files: usize = fetched.items.iter().map(|files| files.len()).sum();
+
+    if ctx.json {
+        let output = serde_json::json!({
+            "revision": revision,
+            "push_id": push_id,
+            "artifact_dir": artifact_dir.display().to_string(),
+            "total_files": total_files,
+            "fetch_errors": fetched.fetch_errors,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
     } else {
-        let pb_jobs = ProgressBar::new(filtered_jobs.len() as u64);
-        pb_jobs.set_style(
-            ProgressStyle::default_bar()
-                .template("{bar:40.cyan/blue} {pos}/{len} {msg}")
-                .unwrap()
-                .progress_chars("=>-"),
-        );
-        pb_jobs.set_message("Fetching job details");
+        println!("\n## Artifacts Downloaded\n");
+        println!("**Revision:** `{}`", revision);
+        println!("**Output directory:** `{}`", artifact_dir.display());
+        println!("**Total files:** {}", total_files);
+        print!("{}", format_fetch_errors_markdown(&fetched.fetch_errors));
+    }
 
-        let pb_jobs = Arc::new(pb_jobs);
+    ensure_complete(ctx, &fetched.fetch_errors)?;
+    Ok(Outcome::Green)
+}
 
-        let jobs_with_errors: Vec<_> = stream::iter(filtered_jobs)
-            .map(|job| {
-                let client = Arc::clone(&client);
-                let repo = args.repo.clone();
-                let pb_jobs = Arc::clone(&pb_jobs);
+async fn run_perf(ctx: &Context, args: PerfArgs) -> Result<Outcome> {
+    let PushJobs {
+        revision,
+        push_id,
+        jobs: filtered_jobs,
+        ..
+    } = load_filtered_jobs(ctx, &args.revision, &args.filters).await?;
+    if filtered_jobs.is_empty() {
+        return Ok(Outcome::Green);
+    }
 
-                async move {
-                    let result = fetch_job_details_with_errors(&client, &repo, job).await;
-                    pb_jobs.inc(1);
-                    result
-                }
-            })
-            .buffer_unordered(10)
-            .collect::<Vec<_>>()
-            .await
-            .into_iter()
-            .filter_map(|result| result.ok())
-            .collect();
+    let pb_perf = progress_bar(filtered_jobs.len(), "Fetching performance data");
 
-        pb_jobs.finish_with_message("Completed fetching job details");
+    let fetched = ctx
+        .client
+        .fetch_perf(filtered_jobs, || pb_perf.inc(1))
+        .await;
 
-        let jobs_with_logs: Vec<_> = jobs_with_errors
-            .into_iter()
-            .map(|(job, errors)| JobWithLogs {
-                job,
-                errors,
-                log_matches: vec![],
-                log_dir: None,
-            })
-            .collect();
+    pb_perf.finish_with_message("Completed fetching performance data");
 
-        if args.group_by.is_some() {
-            let grouped = group_failures_by_test(&jobs_with_logs);
-            if args.json {
-                let json_output = format_grouped_json_output(&revision, push_id, &grouped)?;
-                println!("{}", json_output);
-            } else {
-                let summary = format_grouped_markdown_summary(&revision, push_id, &grouped);
-                println!("{}", summary);
-            }
-        } else if args.json {
-            let json_output = format_json_output(&revision, push_id, &jobs_with_logs)?;
-            println!("{}", json_output);
-        } else {
-            let summary = format_markdown_summary(
-                &revision,
-                push_id,
-                &jobs_with_logs,
-                args.show_stack_traces,
-                args.fetch_logs,
-            );
-            println!("{}", summary);
-        }
+    if ctx.json {
+        let json_output =
+            format_perf_json(&revision, push_id, &fetched.items, &fetched.fetch_errors)?;
+        println!("{}", json_output);
+    } else {
+        let markdown_output =
+            format_perf_markdown(&revision, push_id, &fetched.items, &fetched.fetch_errors);
+        println!("{}", markdown_output);
     }
 
-    Ok(())
+    ensure_complete(ctx, &fetched.fetch_errors)?;
+    Ok(Outcome::Green)
 }
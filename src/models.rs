@@ -1,15 +1,28 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 
 #[derive(Deserialize, Debug)]
 pub struct PushResponse {
     pub results: Vec<PushResult>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct PushResult {
     pub id: u64,
-    #[allow(dead_code)]
+    pub revision: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    /// Unix timestamp of the push.
+    #[serde(default)]
+    pub push_timestamp: Option<u64>,
+    /// Commits of the push, newest first; the first is `revision`.
+    #[serde(default)]
+    pub revisions: Vec<PushCommit>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct PushCommit {
     pub revision: String,
 }
 
@@ -17,6 +30,9 @@ pub struct PushResult {
 pub struct JobsResponse {
     pub results: Vec<Vec<serde_json::Value>>,
     pub job_property_names: Vec<String>,
+    /// URL of the next page, if any.
+    #[serde(default)]
+    pub next: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -27,13 +43,300 @@ pub struct Job {
     pub platform: String,
     #[allow(dead_code)]
     pub platform_option: String,
-    pub result: String,
-    #[allow(dead_code)]
-    pub state: String,
-    #[allow(dead_code)]
-    pub failure_classification_id: Option<u64>,
+    pub result: JobResult,
+    pub state: JobState,
+    #[serde(rename = "failure_classification_id")]
+    pub failure_classification: Option<FailureClassification>,
     #[serde(default)]
     pub duration: Option<u64>,
+    /// 1 for jobs that sheriffs watch, 2 and 3 for less important ones.
+    #[serde(default)]
+    pub tier: Option<u64>,
+    /// Group shown around the symbol in Treeherder, e.g. `Mochitests`;
+    /// `unknown` for jobs outside any group.
+    #[serde(default)]
+    pub job_group_name: String,
+    /// Symbol of the group, e.g. `M`; `?` for jobs outside any group.
+    #[serde(default)]
+    pub job_group_symbol: String,
+    #[serde(default)]
+    pub task_id: Option<String>,
+    /// Taskcluster run of the task this job is, starting at 0.
+    #[serde(default)]
+    pub retry_id: Option<u64>,
+    /// Unix timestamps; unset until the job reaches that point.
+    #[serde(default)]
+    pub submit_timestamp: Option<u64>,
+    #[serde(default)]
+    pub start_timestamp: Option<u64>,
+    #[serde(default)]
+    pub end_timestamp: Option<u64>,
+    /// Email of the push author.
+    #[serde(default)]
+    pub who: Option<String>,
+    /// When Treeherder last updated the job, e.g. `2025-10-09T09:00:00.000000`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+    /// Earlier attempts of this job that ended in `retry`, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub retries: Vec<Job>,
+}
+
+impl Job {
+    /// The group and job symbols in Treeherder's notation, e.g. `M(bc3)`, or
+    /// just the job symbol for jobs outside any group.
+    pub fn symbol_notation(&self) -> String {
+        match self.job_group_symbol.as_str() {
+            "" | "?" => self.job_type_symbol.clone(),
+            group => format!("{}({})", group, self.job_type_symbol),
+        }
+    }
+}
+
+/// Outcome of a job. Serialized as Treeherder's string, and values this
+/// tool does not know about are kept in `Unknown`, as is Treeherder's own
+/// `unknown` for jobs that have not finished.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(from = "String", into = "String")]
+pub enum JobResult {
+    Success,
+    TestFailed,
+    Busted,
+    Exception,
+    Retry,
+    UserCancel,
+    Superseded,
+    Unknown(String),
+}
+
+impl JobResult {
+    /// Every result Treeherder reports.
+    pub const NAMES: [&'static str; 8] = [
+        "success",
+        "testfailed",
+        "busted",
+        "exception",
+        "retry",
+        "usercancel",
+        "superseded",
+        "unknown",
+    ];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            JobResult::Success => "success",
+            JobResult::TestFailed => "testfailed",
+            JobResult::Busted => "busted",
+            JobResult::Exception => "exception",
+            JobResult::Retry => "retry",
+            JobResult::UserCancel => "usercancel",
+            JobResult::Superseded => "superseded",
+            JobResult::Unknown(value) => value,
+        }
+    }
+
+    /// Whether the job failed: a test failure, a broken build or an
+    /// infrastructure exception.
+    pub fn is_failure(&self) -> bool {
+        matches!(
+            self,
+            JobResult::TestFailed | JobResult::Busted | JobResult::Exception
+        )
+    }
+}
+
+impl From<String> for JobResult {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "success" => JobResult::Success,
+            "testfailed" => JobResult::TestFailed,
+            "busted" => JobResult::Busted,
+            "exception" => JobResult::Exception,
+            "retry" => JobResult::Retry,
+            "usercancel" => JobResult::UserCancel,
+            "superseded" => JobResult::Superseded,
+            _ => JobResult::Unknown(value),
+        }
+    }
+}
+
+impl From<JobResult> for String {
+    fn from(result: JobResult) -> Self {
+        result.as_str().to_string()
+    }
+}
+
+impl fmt::Display for JobResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Scheduling state of a job, serialized as Treeherder's string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(from = "String", into = "String")]
+pub enum JobState {
+    Unscheduled,
+    Pending,
+    Running,
+    Completed,
+    Unknown(String),
+}
+
+impl JobState {
+    pub fn as_str(&self) -> &str {
+        match self {
+            JobState::Unscheduled => "unscheduled",
+            JobState::Pending => "pending",
+            JobState::Running => "running",
+            JobState::Completed => "completed",
+            JobState::Unknown(value) => value,
+        }
+    }
+}
+
+impl From<String> for JobState {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "unscheduled" => JobState::Unscheduled,
+            "pending" => JobState::Pending,
+            "running" => JobState::Running,
+            "completed" => JobState::Completed,
+            _ => JobState::Unknown(value),
+        }
+    }
+}
+
+impl From<JobState> for String {
+    fn from(state: JobState) -> Self {
+        state.as_str().to_string()
+    }
+}
+
+impl fmt::Display for JobState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How a failure was classified by sheriffs or autoclassification,
+/// serialized as Treeherder's `failure_classification_id`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(from = "u64", into = "u64")]
+pub enum FailureClassification {
+    NotClassified,
+    FixedByCommit,
+    ExpectedFail,
+    Intermittent,
+    Infra,
+    NewFailure,
+    AutoclassifiedIntermittent,
+    IntermittentNeedsBugid,
+    Unknown(u64),
+}
+
+impl FailureClassification {
+    /// Names accepted for each known classification, in ID order.
+    pub const NAMES: [&'static str; 8] = [
+        "not-classified",
+        "fixed-by-commit",
+        "expected-fail",
+        "intermittent",
+        "infra",
+        "new-failure",
+        "autoclassified-intermittent",
+        "intermittent-needs-bugid",
+    ];
+
+    /// The classification called `name`, if it is one of [`Self::NAMES`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .position(|&known| known == name)
+            .map(|index| Self::from(index as u64 + 1))
+    }
+
+    /// The name of the classification in [`Self::NAMES`], or `None` for an
+    /// unknown one.
+    pub fn name(self) -> Option<&'static str> {
+        match self {
+            FailureClassification::Unknown(_) => None,
+            known => Some(Self::NAMES[known.id() as usize - 1]),
+        }
+    }
+
+    pub fn id(self) -> u64 {
+        match self {
+            FailureClassification::NotClassified => 1,
+            FailureClassification::FixedByCommit => 2,
+            FailureClassification::ExpectedFail => 3,
+            FailureClassification::Intermittent => 4,
+            FailureClassification::Infra => 5,
+            FailureClassification::NewFailure => 6,
+            FailureClassification::AutoclassifiedIntermittent => 7,
+            FailureClassification::IntermittentNeedsBugid => 8,
+            FailureClassification::Unknown(id) => id,
+        }
+    }
+}
+
+impl From<u64> for FailureClassification {
+    fn from(id: u64) -> Self {
+        match id {
+            1 => FailureClassification::NotClassified,
+            2 => FailureClassification::FixedByCommit,
+            3 => FailureClassification::ExpectedFail,
+            4 => FailureClassification::Intermittent,
+            5 => FailureClassification::Infra,
+            6 => FailureClassification::NewFailure,
+            7 => FailureClassification::AutoclassifiedIntermittent,
+            8 => FailureClassification::IntermittentNeedsBugid,
+            _ => FailureClassification::Unknown(id),
+        }
+    }
+}
+
+impl From<FailureClassification> for u64 {
+    fn from(classification: FailureClassification) -> Self {
+        classification.id()
+    }
+}
+
+impl fmt::Display for FailureClassification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FailureClassification::NotClassified => f.write_str("not classified"),
+            FailureClassification::FixedByCommit => f.write_str("fixed by commit"),
+            FailureClassification::ExpectedFail => f.write_str("expected fail"),
+            FailureClassification::Intermittent => f.write_str("intermittent"),
+            FailureClassification::Infra => f.write_str("infra"),
+            FailureClassification::NewFailure => f.write_str("new failure not classified"),
+            FailureClassification::AutoclassifiedIntermittent => {
+                f.write_str("autoclassified intermittent")
+            }
+            FailureClassification::IntermittentNeedsBugid => {
+                f.write_str("intermittent needs bugid")
+            }
+            FailureClassification::Unknown(id) => write!(f, "classification {}", id),
+        }
+    }
+}
+
+/// Entry of `/api/project/{repo}/push/decisiontask/`.
+#[derive(Deserialize, Debug)]
+pub struct DecisionTask {
+    pub id: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct HookTriggerResponse {
+    pub status: HookTriggerStatus,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HookTriggerStatus {
+    pub task_id: String,
 }
 
 #[derive(Deserialize, Debug)]
@@ -45,7 +348,7 @@ pub struct JobDetail {
     #[allow(dead_code)]
     pub platform: String,
     #[allow(dead_code)]
-    pub result: String,
+    pub result: JobResult,
     pub logs: Vec<LogReference>,
 }
 
@@ -79,7 +382,7 @@ pub struct JobDetailExtended {
     pub id: u64,
     pub job_type_name: String,
     pub platform: String,
-    pub result: String,
+    pub result: JobResult,
     pub logs: Vec<LogReference>,
     pub task_id: Option<String>,
     pub retry_id: Option<u64>,
@@ -100,6 +403,79 @@ pub struct ErrorLine {
     pub message: Option<String>,
     #[serde(default)]
     pub stack: Option<String>,
+    /// Bugzilla bugs Treeherder suggests for this failure, open and recent
+    /// ones first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bug_suggestions: Vec<SuggestedBug>,
+}
+
+/// One failure line of a job's log and the bugs that match it, from
+/// `/api/project/{repo}/jobs/{id}/bug_suggestions/`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct BugSuggestions {
+    /// The failure line as searched, e.g. `TEST-UNEXPECTED-FAIL | test | message`.
+    pub search: String,
+    /// The test path the line is about, if any.
+    #[serde(default)]
+    pub path_end: Option<String>,
+    pub bugs: SuggestedBugGroups,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct SuggestedBugGroups {
+    #[serde(default)]
+    pub open_recent: Vec<SuggestedBug>,
+    #[serde(default)]
+    pub all_others: Vec<SuggestedBug>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct SuggestedBug {
+    /// Bugzilla ID; Treeherder-only bugs have none.
+    #[serde(default)]
+    pub id: Option<u64>,
+    pub summary: String,
+    #[serde(default)]
+    pub status: String,
+    #[serde(default)]
+    pub resolution: String,
+}
+
+impl ErrorLine {
+    /// Attaches the bugs of the `suggestions` lines about this failure's
+    /// test, narrowed to those mentioning its subtest when there are any.
+    pub fn add_bug_suggestions(&mut self, suggestions: &[BugSuggestions]) {
+        let Some(test) = &self.test else {
+            return;
+        };
+        let about_test: Vec<&BugSuggestions> = suggestions
+            .iter()
+            .filter(|s| s.path_end.as_deref() == Some(test.as_str()) || s.search.contains(test))
+            .collect();
+        let about_subtest: Vec<&BugSuggestions> = match &self.subtest {
+            Some(subtest) => about_test
+                .iter()
+                .copied()
+                .filter(|s| s.search.contains(subtest.as_str()))
+                .collect(),
+            None => vec![],
+        };
+        let lines = if about_subtest.is_empty() {
+            about_test
+        } else {
+            about_subtest
+        };
+
+        for bug in lines
+            .iter()
+            .flat_map(|s| s.bugs.open_recent.iter())
+            .chain(lines.iter().flat_map(|s| s.bugs.all_others.iter()))
+        {
+            if !self.bug_suggestions.contains(bug) {
+                self.bug_suggestions.push(bug.clone());
+            }
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -116,6 +492,77 @@ pub struct JobWithLogs {
     pub log_matches: Vec<LogMatch>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub log_dir: Option<String>,
+    /// How the failure was classified, newest first.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub classification_notes: Vec<ClassificationNote>,
+    /// Bugs the failure was classified under.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub classified_bugs: Vec<ClassifiedBug>,
+}
+
+/// A classification of a job by a sheriff or developer, from
+/// `/api/project/{repo}/note/`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ClassificationNote {
+    #[serde(
+        rename(
+            deserialize = "failure_classification_id",
+            serialize = "classification"
+        ),
+        serialize_with = "serialize_display"
+    )]
+    pub classification: FailureClassification,
+    pub who: String,
+    /// When the note was made, e.g. `2025-10-08T14:02:11.123456`.
+    pub created: String,
+    #[serde(default)]
+    pub text: String,
+}
+
+/// A bug a job was classified under, from `/api/project/{repo}/bug-job-map/`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ClassifiedBug {
+    /// Bugzilla ID; bugs only known to Treeherder have none.
+    #[serde(default)]
+    pub bug_id: Option<u64>,
+    pub who: String,
+    pub created: String,
+}
+
+fn serialize_display<S: serde::Serializer>(
+    value: &impl fmt::Display,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+/// A job, or one of its logs or artifacts, that could not be retrieved.
+#[derive(Debug, Clone, Serialize)]
+pub struct FetchError {
+    pub job_id: u64,
+    pub job_type_name: String,
+    /// The log or artifact that failed, or `None` when the whole job did.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_name: Option<String>,
+    pub error: String,
+}
+
+impl FetchError {
+    pub fn job(job: &Job, error: &anyhow::Error) -> Self {
+        Self {
+            job_id: job.id,
+            job_type_name: job.job_type_name.clone(),
+            log_name: None,
+            error: format!("{:#}", error),
+        }
+    }
+
+    pub fn log(job: &Job, log_name: &str, error: &anyhow::Error) -> Self {
+        Self {
+            log_name: Some(log_name.to_string()),
+            ..Self::job(job, error)
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -131,6 +578,9 @@ pub struct GroupedTestFailure {
     pub test_name: String,
     pub platforms: Vec<String>,
     pub jobs: Vec<GroupedJobInfo>,
+    /// Bugs suggested for the test on any of its jobs.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub bug_suggestions: Vec<SuggestedBug>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -151,6 +601,7 @@ pub struct ComparisonResult {
     pub new_failures: Vec<ComparisonFailure>,
     pub fixed_failures: Vec<ComparisonFailure>,
     pub still_failing: Vec<ComparisonFailure>,
+    pub fetch_errors: Vec<FetchError>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -197,8 +648,8 @@ pub struct SimilarJob {
     pub id: u64,
     pub job_type_name: String,
     pub platform: String,
-    pub result: String,
-    pub state: String,
+    pub result: JobResult,
+    pub state: JobState,
     pub push_id: u64,
     #[serde(default)]
     pub start_timestamp: Option<u64>,
@@ -238,12 +689,109 @@ pub struct SimilarJobHistory {
     pub jobs: Vec<SimilarJob>,
 }
 
+/// Treeherder's push health triage of a push, from
+/// `/api/project/{repo}/push/health/`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct PushHealth {
+    pub revision: String,
+    /// Push ID.
+    #[serde(default)]
+    pub id: Option<u64>,
+    /// `pass`, `fail`, or `indeterminate` while jobs are still running.
+    pub result: String,
+    pub metrics: PushHealthMetrics,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct PushHealthMetrics {
+    pub tests: HealthMetric<TestHealth>,
+    #[serde(default)]
+    pub builds: HealthMetric<Vec<HealthJob>>,
+    #[serde(default)]
+    pub linting: HealthMetric<Vec<HealthJob>>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct HealthMetric<T> {
+    pub name: String,
+    /// `pass`, `fail`, `indeterminate` or `none`.
+    pub result: String,
+    #[serde(default)]
+    pub details: T,
+}
+
+/// Test failures, as push health sorts them.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct TestHealth {
+    /// Failures that are likely caused by the push.
+    #[serde(default)]
+    pub need_investigation: Vec<HealthTestFailure>,
+    /// Failures matching known intermittents or also failing in the parent
+    /// push.
+    #[serde(default)]
+    pub known_issues: Vec<HealthTestFailure>,
+    /// Failures blamed on the infrastructure, for Treeherder versions that
+    /// report them separately.
+    #[serde(default)]
+    pub infra: Vec<HealthTestFailure>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct HealthTestFailure {
+    pub test_name: String,
+    pub job_name: String,
+    #[serde(default)]
+    pub job_symbol: String,
+    #[serde(default)]
+    pub job_group_symbol: String,
+    pub platform: String,
+    #[serde(default)]
+    pub config: String,
+    #[serde(default)]
+    pub suggested_classification: String,
+    /// How sure Treeherder is of `suggested_classification`, 0 to 100.
+    #[serde(default)]
+    pub confidence: u64,
+    #[serde(default)]
+    pub tier: Option<u64>,
+    #[serde(default)]
+    pub failed_in_parent: bool,
+    #[serde(default)]
+    pub is_classified_intermittent: bool,
+    /// Jobs running the test that failed it, out of `total_jobs`.
+    #[serde(default)]
+    pub total_failures: u64,
+    #[serde(default)]
+    pub total_jobs: u64,
+}
+
+/// A failed build or lint job listed by push health.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct HealthJob {
+    #[serde(default)]
+    pub id: Option<u64>,
+    #[serde(alias = "jobName")]
+    pub job_type_name: String,
+    #[serde(default)]
+    pub platform: String,
+    pub result: JobResult,
+}
+
 pub fn group_failures_by_test(jobs: &[JobWithLogs]) -> Vec<GroupedTestFailure> {
     let mut test_map: HashMap<String, Vec<GroupedJobInfo>> = HashMap::new();
+    let mut bug_map: HashMap<String, Vec<SuggestedBug>> = HashMap::new();
 
     for job_with_logs in jobs {
         for error in &job_with_logs.errors {
             if let Some(test_name) = &error.test {
+                let bugs = bug_map.entry(test_name.clone()).or_default();
+                for bug in &error.bug_suggestions {
+                    if !bugs.contains(bug) {
+                        bugs.push(bug.clone());
+                    }
+                }
                 let info = GroupedJobInfo {
                     job_id: job_with_logs.job.id,
                     platform: job_with_logs.job.platform.clone(),
@@ -266,6 +814,7 @@ pub fn group_failures_by_test(jobs: &[JobWithLogs]) -> Vec<GroupedTestFailure> {
                 .into_iter()
                 .collect();
             GroupedTestFailure {
+                bug_suggestions: bug_map.remove(&test_name).unwrap_or_default(),
                 test_name,
                 platforms,
                 jobs,
@@ -273,7 +822,7 @@ pub fn group_failures_by_test(jobs: &[JobWithLogs]) -> Vec<GroupedTestFailure> {
         })
         .collect();
 
-    grouped.sort_by(|a, b| b.platforms.len().cmp(&a.platforms.len()));
+    grouped.sort_by_key(|g| std::cmp::Reverse(g.platforms.len()));
     grouped
 }
 
@@ -287,7 +836,7 @@ pub fn compare_failures(
 ) -> ComparisonResult {
     let base_failures: std::collections::HashSet<(String, String)> = base_jobs
         .iter()
-        .filter(|j| j.job.result != "success")
+        .filter(|j| j.job.result != JobResult::Success)
         .flat_map(|j| {
             j.errors
                 .iter()
@@ -298,7 +847,7 @@ pub fn compare_failures(
 
     let compare_failures: std::collections::HashSet<(String, String)> = compare_jobs
         .iter()
-        .filter(|j| j.job.result != "success")
+        .filter(|j| j.job.result != JobResult::Success)
         .flat_map(|j| {
             j.errors
                 .iter()
@@ -372,5 +921,6 @@ pub fn compare_failures(
         new_failures,
         fixed_failures,
         still_failing,
+        fetch_errors: vec![],
     }
 }
@@ -0,0 +1,317 @@
+//! Notifications sent while watching a push: desktop notifications,
+//! terminal escape sequences, webhooks, Matrix room messages or a shell
+//! command, chosen with `--notify`.
+
+use crate::http::HttpClient;
+use crate::models::{Job, JobResult, JobWithLogs};
+use crate::util::send_notification;
+use crate::watch::WatchEvent;
+use anyhow::{Context, Result};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use reqwest::Method;
+use serde::Serialize;
+use serde_json::json;
+use std::io::Write;
+use std::process::Stdio;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::AsyncWriteExt;
+
+pub const DEFAULT_MATRIX_HOMESERVER: &str = "https://matrix.org";
+
+/// What a notification says, with the watch event it is about.
+#[derive(Debug, Clone, Serialize)]
+pub struct Notification {
+    pub title: String,
+    pub message: String,
+    #[serde(flatten)]
+    pub event: WatchEvent,
+    /// The failed jobs the notification is about: those found in a poll, or
+    /// all of them once the watch is over.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<NotifiedFailure>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NotifiedFailure {
+    pub job_id: u64,
+    pub job_type_name: String,
+    pub platform: String,
+    pub result: JobResult,
+    /// The failing tests, when the job's failures could be fetched.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tests: Vec<String>,
+}
+
+impl From<&Job> for NotifiedFailure {
+    fn from(job: &Job) -> Self {
+        Self {
+            job_id: job.id,
+            job_type_name: job.job_type_name.clone(),
+            platform: job.platform.clone(),
+            result: job.result.clone(),
+            tests: Vec::new(),
+        }
+    }
+}
+
+impl From<&JobWithLogs> for NotifiedFailure {
+    fn from(job: &JobWithLogs) -> Self {
+        let mut failure = NotifiedFailure::from(&job.job);
+        for test in job.errors.iter().filter_map(|error| error.test.as_ref()) {
+            if !failure.tests.contains(test) {
+                failure.tests.push(test.clone());
+            }
+        }
+        failure
+    }
+}
+
+/// Somewhere notifications can be sent.
+pub trait Notifier: Send + Sync {
+    fn notify<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, Result<()>>;
+}
+
+/// A notification backend, as given to `--notify`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotifyTarget {
+    Desktop,
+    /// The terminal bell.
+    Bell,
+    /// An OSC 9 escape sequence, shown by iTerm2, Windows Terminal and others.
+    Osc9,
+    /// An OSC 777 escape sequence, shown by rxvt, foot and VTE terminals.
+    Osc777,
+    /// A POST of the notification as JSON to this URL.
+    Webhook(String),
+    /// A message in this Matrix room.
+    Matrix(String),
+    /// A shell command, given the notification as JSON on its stdin.
+    Command(String),
+}
+
+/// The Matrix account messages are sent from.
+#[derive(Debug, Clone)]
+pub struct MatrixAccount {
+    pub homeserver: String,
+    pub access_token: Option<String>,
+}
+
+impl NotifyTarget {
+    pub const NAMES: &'static [&'static str] = &[
+        "desktop",
+        "bell",
+        "osc9",
+        "osc777",
+        "webhook:URL",
+        "matrix:ROOM_ID",
+        "command:CMD",
+    ];
+
+    pub fn parse(s: &str) -> Result<Self> {
+        let (kind, value) = match s.split_once(':') {
+            Some((kind, value)) => (kind, Some(value)),
+            None => (s, None),
+        };
+        Ok(match (kind, value) {
+            ("desktop", None) => NotifyTarget::Desktop,
+            ("bell", None) => NotifyTarget::Bell,
+            ("osc9", None) => NotifyTarget::Osc9,
+            ("osc777", None) => NotifyTarget::Osc777,
+            ("webhook", Some(url)) => {
+                url::Url::parse(url).with_context(|| format!("Invalid webhook URL '{}'", url))?;
+                NotifyTarget::Webhook(url.to_string())
+            }
+            ("matrix", Some(room)) if room.starts_with('!') => {
+                NotifyTarget::Matrix(room.to_string())
+            }
+            ("matrix", _) => {
+                anyhow::bail!("Expected a Matrix room ID, e.g. matrix:!abc:example.org")
+            }
+            ("command", Some(command)) if !command.trim().is_empty() => {
+                NotifyTarget::Command(command.to_string())
+            }
+            _ => anyhow::bail!(
+                "Unknown notification target '{}', expected one of: {}",
+                s,
+                Self::NAMES.join(", ")
+            ),
+        })
+    }
+
+    pub fn notifier(&self, http: &HttpClient, matrix: &MatrixAccount) -> Result<Box<dyn Notifier>> {
+        Ok(match self {
+            NotifyTarget::Desktop => Box::new(DesktopNotifier),
+            NotifyTarget::Bell => Box::new(TerminalNotifier::Bell),
+            NotifyTarget::Osc9 => Box::new(TerminalNotifier::Osc9),
+            NotifyTarget::Osc777 => Box::new(TerminalNotifier::Osc777),
+            NotifyTarget::Webhook(url) => Box::new(WebhookNotifier {
+                http: http.clone(),
+                url: url.clone(),
+            }),
+            NotifyTarget::Matrix(room) => Box::new(MatrixNotifier {
+                http: http.clone(),
+                homeserver: matrix.homeserver.trim_end_matches('/').to_string(),
+                access_token: matrix.access_token.clone().context(
+                    "Matrix notifications need an access token: set MATRIX_ACCESS_TOKEN",
+                )?,
+                room: room.clone(),
+            }),
+            NotifyTarget::Command(command) => Box::new(CommandNotifier {
+                command: command.clone(),
+            }),
+        })
+    }
+}
+
+/// Shows a desktop notification. Showing it blocks until the notification
+/// daemon answers, so it runs off the async runtime.
+pub struct DesktopNotifier;
+
+impl Notifier for DesktopNotifier {
+    fn notify<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, Result<()>> {
+        let title = notification.title.clone();
+        let message = notification.message.clone();
+        async move {
+            tokio::task::spawn_blocking(move || send_notification(&title, &message))
+                .await
+                .context("Desktop notification panicked")?
+        }
+        .boxed()
+    }
+}
+
+/// Notifies through the terminal, on stderr so that `--json` output is
+/// left alone.
+pub enum TerminalNotifier {
+    Bell,
+    Osc9,
+    Osc777,
+}
+
+impl Notifier for TerminalNotifier {
+    fn notify<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, Result<()>> {
+        async move {
+            // Text from Treeherder must not end the escape sequence early.
+            let clean =
+                |text: &str| -> String { text.chars().filter(|c| !c.is_control()).collect() };
+            let sequence = match self {
+                TerminalNotifier::Bell => "\x07".to_string(),
+                TerminalNotifier::Osc9 => format!(
+                    "\x1b]9;{}: {}\x07",
+                    clean(&notification.title),
+                    clean(&notification.message)
+                ),
+                TerminalNotifier::Osc777 => format!(
+                    "\x1b]777;notify;{};{}\x07",
+                    clean(&notification.title).replace(';', ","),
+                    clean(&notification.message)
+                ),
+            };
+            let mut stderr = std::io::stderr();
+            stderr.write_all(sequence.as_bytes())?;
+            stderr.flush()?;
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+pub struct WebhookNotifier {
+    http: HttpClient,
+    url: String,
+}
+
+impl Notifier for WebhookNotifier {
+    fn notify<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, Result<()>> {
+        async move {
+            self.http
+                .send_json(Method::POST, &self.url, notification, None)
+                .await?;
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+pub struct MatrixNotifier {
+    http: HttpClient,
+    homeserver: String,
+    access_token: String,
+    room: String,
+}
+
+impl Notifier for MatrixNotifier {
+    fn notify<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, Result<()>> {
+        async move {
+            let transaction_id = format!(
+                "treeherder-cli-{}-{}",
+                SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis(),
+                fastrand::u32(..)
+            );
+            let url = format!(
+                "{}/_matrix/client/v3/rooms/{}/send/m.room.message/{}",
+                self.homeserver,
+                url::form_urlencoded::byte_serialize(self.room.as_bytes()).collect::<String>(),
+                transaction_id
+            );
+            let body = json!({
+                "msgtype": "m.text",
+                "body": format!("{}: {}", notification.title, notification.message),
+            });
+            self.http
+                .send_json(
+                    Method::PUT,
+                    &url,
+                    &body,
+                    Some(&format!("Bearer {}", self.access_token)),
+                )
+                .await?;
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+/// Runs a command through the shell, with the notification as JSON on its
+/// stdin and its title and message in `TREEHERDER_NOTIFY_TITLE` and
+/// `TREEHERDER_NOTIFY_MESSAGE`. Its output goes to stderr.
+pub struct CommandNotifier {
+    command: String,
+}
+
+impl Notifier for CommandNotifier {
+    fn notify<'a>(&'a self, notification: &'a Notification) -> BoxFuture<'a, Result<()>> {
+        async move {
+            let mut command = if cfg!(windows) {
+                let mut command = tokio::process::Command::new("cmd");
+                command.arg("/C");
+                command
+            } else {
+                let mut command = tokio::process::Command::new("sh");
+                command.arg("-c");
+                command
+            };
+            let mut child = command
+                .arg(&self.command)
+                .env("TREEHERDER_NOTIFY_TITLE", &notification.title)
+                .env("TREEHERDER_NOTIFY_MESSAGE", &notification.message)
+                .stdin(Stdio::piped())
+                .stdout(std::io::stderr())
+                .spawn()
+                .with_context(|| format!("Could not run '{}'", self.command))?;
+            if let Some(mut stdin) = child.stdin.take() {
+                // The command may exit without reading its input.
+                let _ = stdin
+                    .write_all(serde_json::to_string(notification)?.as_bytes())
+                    .await;
+            }
+            let status = child.wait().await?;
+            if !status.success() {
+                anyhow::bail!("'{}' exited with {}", self.command, status);
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+}
@@ -1,4 +1,6 @@
+use crate::actions::ActionOutcome;
 use crate::models::*;
+use crate::query::PushRollup;
 use anyhow::Result;
 use serde::Serialize;
 
@@ -7,6 +9,7 @@ pub struct JsonOutput {
     pub revision: String,
     pub push_id: u64,
     pub jobs: Vec<JobWithLogs>,
+    pub fetch_errors: Vec<FetchError>,
 }
 
 #[derive(Serialize)]
@@ -14,13 +17,20 @@ pub struct GroupedJsonOutput {
     pub revision: String,
     pub push_id: u64,
     pub grouped_failures: Vec<GroupedTestFailure>,
+    pub fetch_errors: Vec<FetchError>,
 }
 
-pub fn format_json_output(revision: &str, push_id: u64, jobs: &[JobWithLogs]) -> Result<String> {
+pub fn format_json_output(
+    revision: &str,
+    push_id: u64,
+    jobs: &[JobWithLogs],
+    fetch_errors: &[FetchError],
+) -> Result<String> {
     let output = JsonOutput {
         revision: revision.to_string(),
         push_id,
         jobs: jobs.to_vec(),
+        fetch_errors: fetch_errors.to_vec(),
     };
     Ok(serde_json::to_string_pretty(&output)?)
 }
@@ -29,11 +39,13 @@ pub fn format_grouped_json_output(
     revision: &str,
     push_id: u64,
     grouped: &[GroupedTestFailure],
+    fetch_errors: &[FetchError],
 ) -> Result<String> {
     let output = GroupedJsonOutput {
         revision: revision.to_string(),
         push_id,
         grouped_failures: grouped.to_vec(),
+        fetch_errors: fetch_errors.to_vec(),
     };
     Ok(serde_json::to_string_pretty(&output)?)
 }
@@ -42,11 +54,17 @@ pub fn format_comparison_json(result: &ComparisonResult) -> Result<String> {
     Ok(serde_json::to_string_pretty(result)?)
 }
 
-pub fn format_perf_json(revision: &str, push_id: u64, perf_data: &[JobPerfData]) -> Result<String> {
+pub fn format_perf_json(
+    revision: &str,
+    push_id: u64,
+    perf_data: &[JobPerfData],
+    fetch_errors: &[FetchError],
+) -> Result<String> {
     let output = serde_json::json!({
         "revision": revision,
         "push_id": push_id,
         "jobs": perf_data,
+        "fetch_errors": fetch_errors,
     });
     Ok(serde_json::to_string_pretty(&output)?)
 }
@@ -54,3 +72,38 @@ pub fn format_perf_json(revision: &str, push_id: u64, perf_data: &[JobPerfData])
 pub fn format_similar_history_json(history: &SimilarJobHistory) -> Result<String> {
     Ok(serde_json::to_string_pretty(history)?)
 }
+
+pub fn format_push_rollup_json(repo: &str, rollups: &[PushRollup]) -> Result<String> {
+    let output = serde_json::json!({
+        "repo": repo,
+        "pushes": rollups,
+    });
+    Ok(serde_json::to_string_pretty(&output)?)
+}
+
+pub fn format_push_health_json(health: &PushHealth) -> Result<String> {
+    Ok(serde_json::to_string_pretty(health)?)
+}
+
+#[derive(Serialize)]
+pub struct ActionsJsonOutput<'a> {
+    pub revision: &'a str,
+    pub push_id: u64,
+    pub dry_run: bool,
+    pub actions: &'a [ActionOutcome],
+}
+
+pub fn format_actions_json(
+    revision: &str,
+    push_id: u64,
+    dry_run: bool,
+    outcomes: &[ActionOutcome],
+) -> Result<String> {
+    let output = ActionsJsonOutput {
+        revision,
+        push_id,
+        dry_run,
+        actions: outcomes,
+    };
+    Ok(serde_json::to_string_pretty(&output)?)
+}
@@ -1,11 +1,23 @@
+use crate::actions::{ActionOutcome, ActionStatus};
 use crate::models::*;
+use crate::query::{PushColor, PushRollup, PushStatus};
+use crate::watch::{WatchEvent, WatchEventKind};
 use colored::Colorize;
 use comfy_table::{presets::UTF8_FULL, Attribute, Cell, Color, ContentArrangement, Table};
 
+fn result_color(result: &JobResult) -> Color {
+    match result {
+        JobResult::Success => Color::Green,
+        result if result.is_failure() => Color::Red,
+        _ => Color::Yellow,
+    }
+}
+
 pub fn format_markdown_summary(
     revision: &str,
     push_id: u64,
     jobs: &[JobWithLogs],
+    fetch_errors: &[FetchError],
     show_stack_traces: bool,
     fetch_logs: bool,
 ) -> String {
@@ -31,17 +43,19 @@ pub fn format_markdown_summary(
             "{}\n",
             "✓ No jobs found matching criteria!".green().bold()
         ));
+        output.push_str(&format_fetch_errors_markdown(fetch_errors));
         return output;
     }
 
     let failed_count = jobs
         .iter()
-        .filter(|j| {
-            j.job.state == "completed" && (j.job.result == "testfailed" || j.job.result == "busted")
-        })
+        .filter(|j| j.job.state == JobState::Completed && j.job.result.is_failure())
         .count();
 
-    let unknown_count = jobs.iter().filter(|j| j.job.result == "unknown").count();
+    let unknown_count = jobs
+        .iter()
+        .filter(|j| matches!(j.job.result, JobResult::Unknown(_)))
+        .count();
 
     // Show header based on whether there are failures
     if failed_count > 0 {
@@ -68,6 +82,7 @@ pub fn format_markdown_summary(
         .set_content_arrangement(ContentArrangement::Dynamic)
         .set_header(vec![
             Cell::new("Job ID").add_attribute(Attribute::Bold),
+            Cell::new("Symbol").add_attribute(Attribute::Bold),
             Cell::new("Job Type").add_attribute(Attribute::Bold),
             Cell::new("Platform").add_attribute(Attribute::Bold),
             Cell::new("Result").add_attribute(Attribute::Bold),
@@ -76,14 +91,11 @@ pub fn format_markdown_summary(
 
     for job_with_logs in jobs {
         let job = &job_with_logs.job;
-        let result_cell = match job.result.as_str() {
-            "success" => Cell::new(&job.result).fg(Color::Green),
-            "testfailed" | "busted" => Cell::new(&job.result).fg(Color::Red),
-            _ => Cell::new(&job.result).fg(Color::Yellow),
-        };
+        let result_cell = Cell::new(&job.result).fg(result_color(&job.result));
 
         table.add_row(vec![
             Cell::new(job.id),
+            Cell::new(job.symbol_notation()),
             Cell::new(&job.job_type_name),
             Cell::new(&job.platform),
             result_cell,
@@ -98,10 +110,10 @@ pub fn format_markdown_summary(
         let errors = &job_with_logs.errors;
         let log_matches = &job_with_logs.log_matches;
 
-        let result_colored = match job.result.as_str() {
-            "success" => job.result.green(),
-            "testfailed" | "busted" => job.result.red(),
-            _ => job.result.yellow(),
+        let result_colored = match &job.result {
+            JobResult::Success => job.result.as_str().green(),
+            result if result.is_failure() => job.result.as_str().red(),
+            _ => job.result.as_str().yellow(),
         };
 
         output.push_str(&format!(
@@ -115,11 +127,30 @@ pub fn format_markdown_summary(
             "ID:".dimmed(),
             job.id.to_string().cyan(),
             "Symbol:".dimmed(),
-            job.job_type_symbol.cyan(),
+            job.symbol_notation().cyan(),
             "Result:".dimmed(),
             result_colored
         ));
 
+        if let Some(tier) = job.tier.filter(|&tier| tier > 1) {
+            output.push_str(&format!("  {} {}\n", "Tier:".dimmed(), tier));
+        }
+
+        output.push_str(&format_classification(job_with_logs));
+
+        if !job.retries.is_empty() {
+            let attempts: Vec<String> = job
+                .retries
+                .iter()
+                .map(|retry| format!("{} ({})", retry.id, retry.result))
+                .collect();
+            output.push_str(&format!(
+                "  {} {}\n",
+                "Earlier attempts:".dimmed(),
+                attempts.join(" → ")
+            ));
+        }
+
         if let Some(log_dir) = &job_with_logs.log_dir {
             output.push_str(&format!("  {} {}\n", "Logs:".dimmed(), log_dir.blue()));
         }
@@ -165,6 +196,21 @@ pub fn format_markdown_summary(
 
             output.push_str(&format!("{}\n", error_table));
 
+            let mut tests_with_bugs = Vec::new();
+            for error in errors {
+                if let Some(test) = &error.test {
+                    if !error.bug_suggestions.is_empty() && !tests_with_bugs.contains(&test) {
+                        tests_with_bugs.push(test);
+                        output.push_str(&format!(
+                            "\n  {} for {}:\n",
+                            "Suggested bugs".yellow().bold(),
+                            test
+                        ));
+                        output.push_str(&format_bug_suggestions(&error.bug_suggestions, "    "));
+                    }
+                }
+            }
+
             if show_stack_traces {
                 for error in errors {
                     let stack_trace = if let Some(stack) = &error.stack {
@@ -227,6 +273,7 @@ pub fn format_markdown_summary(
         output.push('\n');
     }
 
+    output.push_str(&format_fetch_errors_markdown(fetch_errors));
     output
 }
 
@@ -234,6 +281,7 @@ pub fn format_grouped_markdown_summary(
     revision: &str,
     push_id: u64,
     grouped: &[GroupedTestFailure],
+    fetch_errors: &[FetchError],
 ) -> String {
     let mut output = String::new();
 
@@ -256,6 +304,7 @@ pub fn format_grouped_markdown_summary(
 
     if grouped.is_empty() {
         output.push_str(&format!("{}\n", "✓ No test failures found!".green().bold()));
+        output.push_str(&format_fetch_errors_markdown(fetch_errors));
         return output;
     }
 
@@ -301,9 +350,83 @@ pub fn format_grouped_markdown_summary(
             ]);
         }
 
-        output.push_str(&format!("{}\n\n", table));
+        output.push_str(&format!("{}\n", table));
+        if !failure.bug_suggestions.is_empty() {
+            output.push_str(&format!("  {}:\n", "Suggested bugs".yellow().bold()));
+            output.push_str(&format_bug_suggestions(&failure.bug_suggestions, "    "));
+        }
+        output.push('\n');
     }
 
+    output.push_str(&format_fetch_errors_markdown(fetch_errors));
+    output
+}
+
+/// Who classified the job as what, and under which bugs.
+fn format_classification(job_with_logs: &JobWithLogs) -> String {
+    let mut output = String::new();
+    for note in &job_with_logs.classification_notes {
+        // Only the date of `2025-10-08T14:02:11.123456`.
+        let date = note.created.split('T').next().unwrap_or(&note.created);
+        output.push_str(&format!(
+            "  {} {} by {} on {}",
+            "Classified:".dimmed(),
+            note.classification.to_string().magenta(),
+            note.who,
+            date
+        ));
+        if !note.text.is_empty() {
+            output.push_str(&format!(" - {}", note.text));
+        }
+        output.push('\n');
+    }
+    if !job_with_logs.classified_bugs.is_empty() {
+        let bugs: Vec<String> = job_with_logs
+            .classified_bugs
+            .iter()
+            .map(|bug| match bug.bug_id {
+                Some(id) => format!("Bug {} ({})", id, bug.who),
+                None => format!("Treeherder-only bug ({})", bug.who),
+            })
+            .collect();
+        output.push_str(&format!(
+            "  {} {}\n",
+            "Classified bugs:".dimmed(),
+            bugs.join(", ")
+        ));
+    }
+    output
+}
+
+/// Bugs shown below a failure, at most a few since later ones rarely match.
+const MAX_BUG_SUGGESTIONS_SHOWN: usize = 3;
+
+fn format_bug_suggestions(bugs: &[SuggestedBug], indent: &str) -> String {
+    let mut output = String::new();
+    for bug in bugs.iter().take(MAX_BUG_SUGGESTIONS_SHOWN) {
+        let id = bug
+            .id
+            .map_or("Bug ?".to_string(), |id| format!("Bug {}", id));
+        let status = if bug.resolution.is_empty() {
+            bug.status.clone()
+        } else {
+            format!("{} {}", bug.status, bug.resolution)
+        };
+        output.push_str(&format!(
+            "{}{} {} {}\n",
+            indent,
+            id.cyan(),
+            format!("[{}]", status).dimmed(),
+            bug.summary
+        ));
+    }
+    if bugs.len() > MAX_BUG_SUGGESTIONS_SHOWN {
+        output.push_str(&format!(
+            "{}{}\n",
+            indent,
+            format!("... and {} more", bugs.len() - MAX_BUG_SUGGESTIONS_SHOWN).dimmed()
+        ));
+    }
     output
 }
 
@@ -452,10 +575,16 @@ pub fn format_comparison_markdown(result: &ComparisonResult) -> String {
         output.push_str(&format!("{}\n\n", table));
     }
 
+    output.push_str(&format_fetch_errors_markdown(&result.fetch_errors));
     output
 }
 
-pub fn format_perf_markdown(revision: &str, push_id: u64, perf_data: &[JobPerfData]) -> String {
+pub fn format_perf_markdown(
+    revision: &str,
+    push_id: u64,
+    perf_data: &[JobPerfData],
+    fetch_errors: &[FetchError],
+) -> String {
     let mut output = String::new();
 
     output.push_str(&format!("{}\n\n", "Performance Data".bold().underline()));
@@ -477,6 +606,7 @@ pub fn format_perf_markdown(revision: &str, push_id: u64, perf_data: &[JobPerfDa
             "{}\n",
             "No performance data available for selected jobs".dimmed()
         ));
+        output.push_str(&format_fetch_errors_markdown(fetch_errors));
         return output;
     }
 
@@ -529,6 +659,46 @@ pub fn format_perf_markdown(revision: &str, push_id: u64, perf_data: &[JobPerfDa
         output.push('\n');
     }
 
+    output.push_str(&format_fetch_errors_markdown(fetch_errors));
+    output
+}
+
+/// Lists the jobs, logs and artifacts that could not be retrieved, so that a
+/// partial report is not mistaken for a complete one. Empty when nothing
+/// failed.
+pub fn format_fetch_errors_markdown(fetch_errors: &[FetchError]) -> String {
+    if fetch_errors.is_empty() {
+        return String::new();
+    }
+
+    let mut output = String::new();
+    output.push_str(&format!(
+        "{} ({} could not be retrieved, report is incomplete)\n\n",
+        "Fetch Errors".red().bold(),
+        fetch_errors.len()
+    ));
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Job ID").add_attribute(Attribute::Bold),
+            Cell::new("Job Type").add_attribute(Attribute::Bold),
+            Cell::new("Log").add_attribute(Attribute::Bold),
+            Cell::new("Error").add_attribute(Attribute::Bold),
+        ]);
+
+    for fetch_error in fetch_errors {
+        table.add_row(vec![
+            Cell::new(fetch_error.job_id),
+            Cell::new(&fetch_error.job_type_name),
+            Cell::new(fetch_error.log_name.as_deref().unwrap_or("(job)")),
+            Cell::new(&fetch_error.error).fg(Color::Red),
+        ]);
+    }
+
+    output.push_str(&format!("{}\n\n", table));
     output
 }
 
@@ -586,15 +756,9 @@ pub fn format_similar_history_markdown(history: &SimilarJobHistory) -> String {
         ]);
 
     for job in &history.jobs {
-        let (result_color, result_text) = match job.result.as_str() {
-            "success" => (Color::Green, &job.result),
-            "testfailed" | "busted" => (Color::Red, &job.result),
-            _ => (Color::Yellow, &job.result),
-        };
-
         table.add_row(vec![
             Cell::new(job.push_id),
-            Cell::new(result_text).fg(result_color),
+            Cell::new(&job.result).fg(result_color(&job.result)),
             Cell::new(&job.platform),
         ]);
     }
@@ -602,3 +766,460 @@ pub fn format_similar_history_markdown(history: &SimilarJobHistory) -> String {
     output.push_str(&format!("{}\n", table));
     output
 }
+
+/// One row per push of a range, newest first, followed by the failures each
+/// push introduced.
+pub fn format_push_rollup_markdown(repo: &str, rollups: &[PushRollup]) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!(
+        "{}\n\n",
+        "Treeherder Push Overview".bold().underline()
+    ));
+    output.push_str(&format!(
+        "{} {}\n\n",
+        "Repository:".cyan().bold(),
+        repo.yellow()
+    ));
+
+    if rollups.is_empty() {
+        output.push_str("No pushes found matching the specified criteria\n");
+        return output;
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Push ID").add_attribute(Attribute::Bold),
+            Cell::new("Revision").add_attribute(Attribute::Bold),
+            Cell::new("Author").add_attribute(Attribute::Bold),
+            Cell::new("Status").add_attribute(Attribute::Bold),
+            Cell::new("Failed Jobs").add_attribute(Attribute::Bold),
+            Cell::new("Failing Tests").add_attribute(Attribute::Bold),
+            Cell::new("New Failures").add_attribute(Attribute::Bold),
+        ]);
+
+    for rollup in rollups {
+        let (color_name, color) = match rollup.color {
+            Some(PushColor::Green) => ("green", Color::Green),
+            Some(PushColor::Orange) => ("orange", Color::DarkYellow),
+            Some(PushColor::Red) => ("red", Color::Red),
+            None => ("unknown", Color::DarkGrey),
+        };
+        let status = if rollup.pending_jobs > 0 {
+            format!("{} ({} pending)", color_name, rollup.pending_jobs)
+        } else {
+            color_name.to_string()
+        };
+        let new_failures = match &rollup.new_failures {
+            Some(failures) => Cell::new(failures.len()).fg(if failures.is_empty() {
+                Color::Green
+            } else {
+                Color::Red
+            }),
+            None => Cell::new("-"),
+        };
+
+        table.add_row(vec![
+            Cell::new(rollup.push_id),
+            Cell::new(rollup.revision.chars().take(12).collect::<String>()),
+            Cell::new(rollup.author.as_deref().unwrap_or("-")),
+            Cell::new(status).fg(color),
+            Cell::new(rollup.failed_jobs),
+            Cell::new(rollup.failing_tests),
+            new_failures,
+        ]);
+    }
+
+    output.push_str(&format!("{}\n\n", table));
+
+    for rollup in rollups {
+        let Some(failures) = rollup.new_failures.as_ref().filter(|f| !f.is_empty()) else {
+            continue;
+        };
+        output.push_str(&format!(
+            "{} {} ({} tests)\n",
+            "New failures in".red().bold(),
+            rollup.revision.yellow(),
+            failures.len()
+        ));
+        for failure in failures {
+            output.push_str(&format!(
+                "  {} {}\n",
+                failure.test_name,
+                failure.platforms.join(", ").dimmed()
+            ));
+        }
+        output.push('\n');
+    }
+
+    for rollup in rollups {
+        if let Some(error) = &rollup.error {
+            output.push_str(&format!(
+                "{} {}: {}\n",
+                "Could not fetch the jobs of".red().bold(),
+                rollup.revision.yellow(),
+                error
+            ));
+        }
+    }
+    if rollups.iter().any(|rollup| rollup.error.is_some()) {
+        output.push('\n');
+    }
+
+    let fetch_errors: Vec<FetchError> = rollups
+        .iter()
+        .flat_map(|rollup| rollup.fetch_errors.iter().cloned())
+        .collect();
+    output.push_str(&format_fetch_errors_markdown(&fetch_errors));
+    output
+}
+
+fn health_color(result: &str) -> Color {
+    match result {
+        "pass" => Color::Green,
+        "fail" => Color::Red,
+        _ => Color::Yellow,
+    }
+}
+
+fn health_failures_table(failures: &[HealthTestFailure], color: Color) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Test").add_attribute(Attribute::Bold),
+            Cell::new("Platform").add_attribute(Attribute::Bold),
+            Cell::new("Job").add_attribute(Attribute::Bold),
+            Cell::new("Failed").add_attribute(Attribute::Bold),
+            Cell::new("In Parent").add_attribute(Attribute::Bold),
+            Cell::new("Suggestion").add_attribute(Attribute::Bold),
+        ]);
+
+    for failure in failures {
+        let symbol = if failure.job_group_symbol.is_empty() {
+            failure.job_symbol.clone()
+        } else {
+            format!("{}({})", failure.job_group_symbol, failure.job_symbol)
+        };
+        table.add_row(vec![
+            Cell::new(&failure.test_name).fg(color),
+            Cell::new(format!("{} {}", failure.platform, failure.config)),
+            Cell::new(symbol),
+            Cell::new(format!("{}/{}", failure.total_failures, failure.total_jobs)),
+            Cell::new(if failure.failed_in_parent {
+                "yes"
+            } else {
+                "no"
+            }),
+            Cell::new(format!(
+                "{} ({}%)",
+                failure.suggested_classification, failure.confidence
+            )),
+        ]);
+    }
+    table
+}
+
+pub fn format_push_health_markdown(health: &PushHealth) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!(
+        "{}\n\n",
+        "Treeherder Push Health".bold().underline()
+    ));
+    output.push_str(&format!(
+        "{} {}\n",
+        "Revision:".cyan().bold(),
+        health.revision.yellow()
+    ));
+    if let Some(push_id) = health.id {
+        output.push_str(&format!(
+            "{} {}\n",
+            "Push ID:".cyan().bold(),
+            push_id.to_string().yellow()
+        ));
+    }
+    let result = match health.result.as_str() {
+        "pass" => health.result.green(),
+        "fail" => health.result.red(),
+        _ => health.result.yellow(),
+    };
+    output.push_str(&format!("{} {}\n\n", "Result:".cyan().bold(), result));
+
+    let metrics = &health.metrics;
+    let tests = &metrics.tests.details;
+    let mut summary_table = Table::new();
+    summary_table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Metric").add_attribute(Attribute::Bold),
+            Cell::new("Result").add_attribute(Attribute::Bold),
+            Cell::new("Failures").add_attribute(Attribute::Bold),
+        ]);
+    summary_table.add_row(vec![
+        Cell::new("Need Investigation"),
+        Cell::new(&metrics.tests.result).fg(health_color(&metrics.tests.result)),
+        Cell::new(tests.need_investigation.len()),
+    ]);
+    summary_table.add_row(vec![
+        Cell::new("Known Issues"),
+        Cell::new("-"),
+        Cell::new(tests.known_issues.len()),
+    ]);
+    if !tests.infra.is_empty() {
+        summary_table.add_row(vec![
+            Cell::new("Infra"),
+            Cell::new("-"),
+            Cell::new(tests.infra.len()),
+        ]);
+    }
+    for metric in [&metrics.builds, &metrics.linting] {
+        if metric.name.is_empty() {
+            continue;
+        }
+        summary_table.add_row(vec![
+            Cell::new(&metric.name),
+            Cell::new(&metric.result).fg(health_color(&metric.result)),
+            Cell::new(metric.details.len()),
+        ]);
+    }
+    output.push_str(&format!("{}\n\n", summary_table));
+
+    if tests.need_investigation.is_empty() {
+        output.push_str(&format!(
+            "{} {}\n\n",
+            "Need Investigation:".red().bold(),
+            "✓ None!".green()
+        ));
+    } else {
+        output.push_str(&format!(
+            "{} ({} tests)\n",
+            "Need Investigation".red().bold(),
+            tests.need_investigation.len()
+        ));
+        output.push_str(&format!(
+            "{}\n\n",
+            "Failures that are likely caused by this push:".dimmed()
+        ));
+        output.push_str(&format!(
+            "{}\n\n",
+            health_failures_table(&tests.need_investigation, Color::Red)
+        ));
+    }
+
+    for (title, description, failures) in [
+        (
+            "Known Issues",
+            "Known intermittents and failures also seen in the parent push:",
+            &tests.known_issues,
+        ),
+        (
+            "Infra",
+            "Failures blamed on the infrastructure:",
+            &tests.infra,
+        ),
+    ] {
+        if failures.is_empty() {
+            continue;
+        }
+        output.push_str(&format!(
+            "{} ({} tests)\n",
+            title.yellow().bold(),
+            failures.len()
+        ));
+        output.push_str(&format!("{}\n\n", description.dimmed()));
+        output.push_str(&format!(
+            "{}\n\n",
+            health_failures_table(failures, Color::Yellow)
+        ));
+    }
+
+    let failed_jobs: Vec<&HealthJob> = metrics
+        .builds
+        .details
+        .iter()
+        .chain(&metrics.linting.details)
+        .collect();
+    if !failed_jobs.is_empty() {
+        output.push_str(&format!(
+            "{} ({})\n\n",
+            "Build and Lint Failures".red().bold(),
+            failed_jobs.len()
+        ));
+        let mut table = Table::new();
+        table
+            .load_preset(UTF8_FULL)
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_header(vec![
+                Cell::new("Job ID").add_attribute(Attribute::Bold),
+                Cell::new("Job Type").add_attribute(Attribute::Bold),
+                Cell::new("Platform").add_attribute(Attribute::Bold),
+                Cell::new("Result").add_attribute(Attribute::Bold),
+            ]);
+        for job in failed_jobs {
+            table.add_row(vec![
+                Cell::new(job.id.map_or("-".to_string(), |id| id.to_string())),
+                Cell::new(&job.job_type_name),
+                Cell::new(&job.platform),
+                Cell::new(&job.result).fg(result_color(&job.result)),
+            ]);
+        }
+        output.push_str(&format!("{}\n\n", table));
+    }
+
+    output
+}
+
+pub fn format_actions_markdown(
+    revision: &str,
+    push_id: u64,
+    dry_run: bool,
+    outcomes: &[ActionOutcome],
+) -> String {
+    let mut output = String::new();
+
+    let title = if dry_run {
+        "Treeherder Actions (dry run)"
+    } else {
+        "Treeherder Actions"
+    };
+    output.push_str(&format!("{}\n\n", title.bold().underline()));
+    output.push_str(&format!(
+        "{} {}\n",
+        "Revision:".cyan().bold(),
+        revision.yellow()
+    ));
+    output.push_str(&format!(
+        "{} {}\n\n",
+        "Push ID:".cyan().bold(),
+        push_id.to_string().yellow()
+    ));
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Job ID").add_attribute(Attribute::Bold),
+            Cell::new("Job Type").add_attribute(Attribute::Bold),
+            Cell::new("Platform").add_attribute(Attribute::Bold),
+            Cell::new("Action").add_attribute(Attribute::Bold),
+            Cell::new("Status").add_attribute(Attribute::Bold),
+            Cell::new("Detail").add_attribute(Attribute::Bold),
+        ]);
+    for outcome in outcomes {
+        let (status, color) = match outcome.status {
+            ActionStatus::Planned => ("planned", Color::Yellow),
+            ActionStatus::Done => ("done", Color::Green),
+            ActionStatus::Failed => ("failed", Color::Red),
+        };
+        table.add_row(vec![
+            Cell::new(outcome.job_id),
+            Cell::new(&outcome.job_type_name),
+            Cell::new(&outcome.platform),
+            Cell::new(&outcome.action),
+            Cell::new(status).fg(color),
+            Cell::new(outcome.detail.as_deref().unwrap_or("")),
+        ]);
+    }
+    output.push_str(&format!("{}\n", table));
+
+    let count = |status| outcomes.iter().filter(|o| o.status == status).count();
+    if dry_run {
+        output.push_str(&format!(
+            "\n{} actions planned; nothing was changed. Run again without --dry-run to \
+             carry them out.\n",
+            count(ActionStatus::Planned)
+        ));
+    } else {
+        output.push_str(&format!(
+            "\n{} done, {} failed\n",
+            count(ActionStatus::Done),
+            count(ActionStatus::Failed)
+        ));
+    }
+    output
+}
+
+/// A line or two about a watch event worth telling right away: a failure
+/// and its tests, or the end of the push. Started and passed jobs are only
+/// counted.
+pub fn format_watch_event_markdown(event: &WatchEvent) -> Option<String> {
+    match &event.kind {
+        WatchEventKind::JobFailed {
+            job,
+            errors,
+            fetch_errors,
+        } => {
+            let mut output = format!(
+                "{} {} {} {} {}\n",
+                "✗".red().bold(),
+                job.symbol_notation().cyan(),
+                job.job_type_name.bold(),
+                job.platform.dimmed(),
+                job.result.as_str().red()
+            );
+            let mut tests: Vec<&str> = Vec::new();
+            for error in errors {
+                let Some(test) = error.test.as_deref() else {
+                    continue;
+                };
+                if tests.contains(&test) {
+                    continue;
+                }
+                tests.push(test);
+                let message = error
+                    .message
+                    .as_deref()
+                    .and_then(|m| m.lines().next())
+                    .unwrap_or("");
+                output.push_str(&format!("    {} {}\n", test, message.dimmed()));
+            }
+            for fetch_error in fetch_errors {
+                output.push_str(&format!(
+                    "    {} {}\n",
+                    "Could not fetch failures:".yellow(),
+                    fetch_error.error
+                ));
+            }
+            Some(output)
+        }
+        WatchEventKind::PushComplete {
+            status,
+            until,
+            jobs,
+            total_jobs,
+            completed,
+            failed,
+            passed,
+        } => {
+            let (which, in_push) = if jobs < total_jobs {
+                ("filtered jobs", format!(" ({} in the push)", total_jobs))
+            } else {
+                ("jobs", String::new())
+            };
+            let summary = if until.waits_for_all() {
+                format!(
+                    "All {} {} of push {} completed{}: {} failed, {} passed",
+                    jobs, which, event.push_id, in_push, failed, passed
+                )
+            } else {
+                format!(
+                    "Stopped watching push {} on {}: {} of {} {} completed{}, \
+                     {} failed, {} passed",
+                    event.push_id, until, completed, jobs, which, in_push, failed, passed
+                )
+            };
+            Some(match status {
+                PushStatus::Green => format!("{} {}\n", "✓".green().bold(), summary),
+                _ => format!("{} {}\n", "✗".red().bold(), summary),
+            })
+        }
+        WatchEventKind::JobStarted { .. } | WatchEventKind::JobPassed { .. } => None,
+    }
+}
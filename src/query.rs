@@ -0,0 +1,455 @@
+use crate::expr::Expr;
+use crate::models::*;
+use anyhow::Result;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum MatchFilter {
+    /// Failed jobs: test failures, broken builds and exceptions, plus
+    /// cancelled jobs and jobs that had to be retried.
+    #[default]
+    Failure,
+    Success,
+    All,
+}
+
+impl MatchFilter {
+    fn matches(self, job: &Job) -> bool {
+        match self {
+            MatchFilter::Failure => {
+                job.result.is_failure()
+                    || matches!(job.result, JobResult::Retry | JobResult::UserCancel)
+                    || !job.retries.is_empty()
+            }
+            MatchFilter::Success => job.result == JobResult::Success,
+            MatchFilter::All => true,
+        }
+    }
+}
+
+/// Which jobs of a push to keep. The default keeps failed jobs that are not
+/// classified as intermittent, like the command-line tool does.
+///
+/// Jobs that ended in `retry` are moved under the attempt that replaced
+/// them. `match_filter` looks at the final attempt, except that `Failure`
+/// also keeps a job with earlier attempts, while a job matches `results` if
+/// it or any of its earlier attempts ended with one of them.
+#[derive(Debug, Clone, Default)]
+pub struct JobFilter {
+    pub match_filter: MatchFilter,
+    /// Results to keep; when not empty, this replaces `match_filter`.
+    pub results: Vec<JobResult>,
+    /// Substring that `job_type_name` must contain.
+    pub name: Option<String>,
+    /// Regex that `platform` must match.
+    pub platform: Option<String>,
+    /// Minimum duration in seconds.
+    pub duration_min: Option<u64>,
+    /// Tiers to keep; all of them when empty.
+    pub tiers: Vec<u64>,
+    /// Group symbol (`M`) or name (`Mochitests`) the job must belong to.
+    pub group: Option<String>,
+    /// Glob that `job_type_symbol` must match, e.g. `bc*`.
+    pub symbol: Option<String>,
+    /// Classifications to keep, unclassified jobs counting as
+    /// `NotClassified`; when not empty, this replaces
+    /// `include_intermittent`.
+    pub classifications: Vec<FailureClassification>,
+    pub include_intermittent: bool,
+    /// A `--where` expression the jobs must also match.
+    pub expression: Option<Expr>,
+}
+
+impl JobFilter {
+    pub fn apply(&self, jobs: Vec<Job>) -> Result<Vec<Job>> {
+        let keeps = |job: &Job| {
+            if self.results.is_empty() {
+                self.match_filter.matches(job)
+            } else {
+                self.results.contains(&job.result)
+                    || job.retries.iter().any(|r| self.results.contains(&r.result))
+            }
+        };
+        let mut filtered_jobs: Vec<_> = chain_retries(jobs).into_iter().filter(keeps).collect();
+
+        if let Some(filter_pattern) = &self.name {
+            filtered_jobs.retain(|job| job.job_type_name.contains(filter_pattern));
+        }
+
+        if let Some(platform_pattern) = &self.platform {
+            let platform_regex = Regex::new(platform_pattern)?;
+            filtered_jobs.retain(|job| platform_regex.is_match(&job.platform));
+        }
+
+        if let Some(min_duration) = self.duration_min {
+            filtered_jobs.retain(|job| job.duration.is_some_and(|d| d >= min_duration));
+        }
+
+        if !self.tiers.is_empty() {
+            filtered_jobs.retain(|job| job.tier.is_some_and(|tier| self.tiers.contains(&tier)));
+        }
+
+        if let Some(group) = &self.group {
+            filtered_jobs.retain(|job| {
+                job.job_group_symbol == *group || job.job_group_name.eq_ignore_ascii_case(group)
+            });
+        }
+
+        if let Some(symbol_pattern) = &self.symbol {
+            let symbol_regex = glob_regex(symbol_pattern)?;
+            filtered_jobs.retain(|job| symbol_regex.is_match(&job.job_type_symbol));
+        }
+
+        if !self.classifications.is_empty() {
+            filtered_jobs.retain(|job| {
+                let classification = job
+                    .failure_classification
+                    .unwrap_or(FailureClassification::NotClassified);
+                self.classifications.contains(&classification)
+            });
+        } else if !self.include_intermittent {
+            filtered_jobs.retain(|job| {
+                job.failure_classification != Some(FailureClassification::Intermittent)
+            });
+        }
+
+        if let Some(expression) = &self.expression {
+            filtered_jobs.retain(|job| expression.matches(job));
+        }
+
+        Ok(filtered_jobs)
+    }
+
+    /// The same filter, but keeping jobs whatever their result or
+    /// classification, e.g. to follow them while they run.
+    pub fn any_result(&self) -> JobFilter {
+        JobFilter {
+            match_filter: MatchFilter::All,
+            results: vec![],
+            classifications: vec![],
+            include_intermittent: true,
+            ..self.clone()
+        }
+    }
+
+    /// Number of jobs matching the filter, whatever their result, that have
+    /// not completed yet.
+    pub fn pending(&self, jobs: &[Job]) -> Result<usize> {
+        Ok(self
+            .any_result()
+            .apply(jobs.to_vec())?
+            .iter()
+            .filter(|job| job.state != JobState::Completed)
+            .count())
+    }
+}
+
+/// A regex matching the whole of what `pattern` matches, with `*` standing
+/// for any run of characters and `?` for any one character.
+fn glob_regex(pattern: &str) -> Result<Regex> {
+    let regex = regex::escape(pattern)
+        .replace(r"\*", ".*")
+        .replace(r"\?", ".");
+    Ok(Regex::new(&format!("^{}$", regex))?)
+}
+
+/// Moves each job that ended in `retry` under the `retries` of the attempt
+/// that replaced it: the next job, by ID, with the same name, platform and
+/// options. Retries with no later attempt are left in place.
+fn chain_retries(jobs: Vec<Job>) -> Vec<Job> {
+    let mut chains: HashMap<usize, Vec<usize>> = HashMap::new();
+    {
+        let mut by_id: Vec<usize> = (0..jobs.len()).collect();
+        by_id.sort_by_key(|&i| jobs[i].id);
+
+        let mut waiting: HashMap<(&str, &str, &str), Vec<usize>> = HashMap::new();
+        for i in by_id {
+            let job = &jobs[i];
+            let key = (
+                job.job_type_name.as_str(),
+                job.platform.as_str(),
+                job.platform_option.as_str(),
+            );
+            if job.result == JobResult::Retry {
+                waiting.entry(key).or_default().push(i);
+            } else if let Some(retries) = waiting.remove(&key) {
+                chains.insert(i, retries);
+            }
+        }
+    }
+
+    let chained: HashSet<usize> = chains.values().flatten().copied().collect();
+    let mut slots: Vec<Option<Job>> = jobs.into_iter().map(Some).collect();
+    let mut chained_jobs = Vec::new();
+    for i in 0..slots.len() {
+        if chained.contains(&i) {
+            continue;
+        }
+        let Some(mut job) = slots[i].take() else {
+            continue;
+        };
+        if let Some(retries) = chains.get(&i) {
+            job.retries = retries.iter().filter_map(|&r| slots[r].take()).collect();
+        }
+        chained_jobs.push(job);
+    }
+    chained_jobs
+}
+
+/// Overall state of the jobs a query selected: any failure wins over jobs
+/// still running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PushStatus {
+    Green,
+    Failed,
+    Pending,
+}
+
+impl PushStatus {
+    fn of<'a>(mut jobs: impl Iterator<Item = &'a Job>, pending_jobs: usize) -> Self {
+        if jobs.any(|job| job.result.is_failure()) {
+            PushStatus::Failed
+        } else if pending_jobs > 0 {
+            PushStatus::Pending
+        } else {
+            PushStatus::Green
+        }
+    }
+}
+
+/// Treeherder's color for a push: red when a job broke (busted or
+/// exception), orange when tests failed, green otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PushColor {
+    Green,
+    Orange,
+    Red,
+}
+
+impl PushColor {
+    fn of<'a>(jobs: impl Iterator<Item = &'a Job>) -> Self {
+        jobs.fold(PushColor::Green, |color, job| match job.result {
+            JobResult::Busted | JobResult::Exception => PushColor::Red,
+            JobResult::TestFailed if color == PushColor::Green => PushColor::Orange,
+            _ => color,
+        })
+    }
+}
+
+/// Pushes of a repository to list, newest first, as filtered by
+/// Treeherder's push endpoint. Every bound is inclusive.
+#[derive(Debug, Clone, Default)]
+pub struct PushRange {
+    pub author: Option<String>,
+    /// Earliest push date, as `YYYY-MM-DD`.
+    pub from_date: Option<String>,
+    /// Latest push date, as `YYYY-MM-DD`.
+    pub to_date: Option<String>,
+    /// Revision of the oldest push.
+    pub from_change: Option<String>,
+    /// Revision of the newest push.
+    pub to_change: Option<String>,
+    /// Most pushes to list.
+    pub limit: usize,
+}
+
+impl PushRange {
+    /// The push endpoint parameters selecting this range.
+    pub fn query_string(&self) -> String {
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        for (name, value) in [
+            ("author", &self.author),
+            ("startdate", &self.from_date),
+            ("enddate", &self.to_date),
+            ("fromchange", &self.from_change),
+            ("tochange", &self.to_change),
+        ] {
+            if let Some(value) = value {
+                query.append_pair(name, value);
+            }
+        }
+        query.finish()
+    }
+}
+
+/// One line of a push range overview.
+#[derive(Debug, Clone, Serialize)]
+pub struct PushRollup {
+    pub revision: String,
+    pub push_id: u64,
+    pub author: Option<String>,
+    pub push_timestamp: Option<u64>,
+    /// `None` when the jobs of the push could not be fetched.
+    pub status: Option<PushStatus>,
+    pub color: Option<PushColor>,
+    /// Filtered jobs that failed.
+    pub failed_jobs: usize,
+    /// See [`JobFilter::pending`].
+    pub pending_jobs: usize,
+    /// Distinct tests failing, as grouped by [`PushReport::grouped_failures`].
+    pub failing_tests: usize,
+    /// Failures not seen in the previous push of the range; `None` for the
+    /// oldest push, which has nothing to compare with.
+    pub new_failures: Option<Vec<ComparisonFailure>>,
+    pub fetch_errors: Vec<FetchError>,
+    /// Why the jobs of the push could not be fetched.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl PushRollup {
+    pub fn new(push: &PushResult, report: &PushReport, previous: Option<&PushReport>) -> Self {
+        Self {
+            revision: report.revision.clone(),
+            push_id: report.push_id,
+            author: push.author.clone(),
+            push_timestamp: push.push_timestamp,
+            status: Some(report.status()),
+            color: Some(PushColor::of(report.jobs.iter().map(|j| &j.job))),
+            failed_jobs: report
+                .jobs
+                .iter()
+                .filter(|j| j.job.result.is_failure())
+                .count(),
+            pending_jobs: report.pending_jobs,
+            failing_tests: report.grouped_failures().len(),
+            new_failures: previous.map(|previous| report.compare(previous).new_failures),
+            fetch_errors: report.fetch_errors.clone(),
+            error: None,
+        }
+    }
+
+    /// A push whose jobs could not be fetched.
+    pub fn failed(push: &PushResult, error: &anyhow::Error) -> Self {
+        Self {
+            revision: push.revision.clone(),
+            push_id: push.id,
+            author: push.author.clone(),
+            push_timestamp: push.push_timestamp,
+            status: None,
+            color: None,
+            failed_jobs: 0,
+            pending_jobs: 0,
+            failing_tests: 0,
+            new_failures: None,
+            fetch_errors: Vec::new(),
+            error: Some(format!("{:#}", error)),
+        }
+    }
+}
+
+/// Identifies a push, either by a Treeherder URL or revision hash, by the
+/// Lando job that landed it, or by the commit checked out in a local clone.
+#[derive(Debug, Clone)]
+pub enum RevisionSpec {
+    Input(String),
+    LandoJob(u64),
+    Checkout(PathBuf),
+}
+
+/// A push and the filter to apply to its jobs.
+#[derive(Debug, Clone)]
+pub struct PushQuery {
+    pub revision: RevisionSpec,
+    pub filter: JobFilter,
+}
+
+impl PushQuery {
+    /// Query a push by Treeherder URL or revision hash.
+    pub fn new(input: impl Into<String>) -> Self {
+        Self {
+            revision: RevisionSpec::Input(input.into()),
+            filter: JobFilter::default(),
+        }
+    }
+
+    /// Query the push created by a landed Lando job.
+    pub fn lando_job(job_id: u64) -> Self {
+        Self {
+            revision: RevisionSpec::LandoJob(job_id),
+            filter: JobFilter::default(),
+        }
+    }
+
+    /// Query the push of the commit checked out in the clone `dir` belongs
+    /// to; see [`crate::Checkout`].
+    pub fn checkout(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            revision: RevisionSpec::Checkout(dir.into()),
+            filter: JobFilter::default(),
+        }
+    }
+
+    pub fn with_filter(mut self, filter: JobFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+}
+
+/// The jobs of a push that matched a query.
+#[derive(Debug, Clone)]
+pub struct PushJobs {
+    pub revision: String,
+    pub push_id: u64,
+    pub jobs: Vec<Job>,
+    /// See [`JobFilter::pending`].
+    pub pending_jobs: usize,
+}
+
+impl PushJobs {
+    pub fn status(&self) -> PushStatus {
+        PushStatus::of(self.jobs.iter(), self.pending_jobs)
+    }
+}
+
+/// The filtered jobs of a push along with their errors, and the jobs or logs
+/// that could not be fetched.
+#[derive(Debug, Clone, Serialize)]
+pub struct PushReport {
+    pub revision: String,
+    pub push_id: u64,
+    pub jobs: Vec<JobWithLogs>,
+    pub fetch_errors: Vec<FetchError>,
+    /// See [`JobFilter::pending`].
+    pub pending_jobs: usize,
+}
+
+impl PushReport {
+    pub fn status(&self) -> PushStatus {
+        PushStatus::of(self.jobs.iter().map(|j| &j.job), self.pending_jobs)
+    }
+
+    /// Whether every job and log of the report was retrieved.
+    pub fn is_complete(&self) -> bool {
+        self.fetch_errors.is_empty()
+    }
+
+    pub fn grouped_failures(&self) -> Vec<GroupedTestFailure> {
+        group_failures_by_test(&self.jobs)
+    }
+
+    /// Failures that are new in `self` relative to `other`, fixed, or present
+    /// in both.
+    pub fn compare(&self, other: &PushReport) -> ComparisonResult {
+        let mut result = compare_failures(
+            &self.jobs,
+            &other.jobs,
+            &self.revision,
+            &other.revision,
+            self.push_id,
+            other.push_id,
+        );
+        result.fetch_errors = self
+            .fetch_errors
+            .iter()
+            .chain(&other.fetch_errors)
+            .cloned()
+            .collect();
+        result
+    }
+}
@@ -1,15 +1,18 @@
-use crate::models::Job;
+use crate::models::{Job, JobState};
 use anyhow::Result;
 use notify_rust::Notification;
 
 pub fn are_all_jobs_complete(jobs: &[Job]) -> bool {
-    jobs.iter().all(|job| job.state == "completed")
+    jobs.iter().all(|job| job.state == JobState::Completed)
 }
 
 pub fn count_job_states(jobs: &[Job]) -> (usize, usize, usize) {
-    let completed = jobs.iter().filter(|j| j.state == "completed").count();
-    let running = jobs.iter().filter(|j| j.state == "running").count();
-    let pending = jobs.iter().filter(|j| j.state == "pending").count();
+    let completed = jobs
+        .iter()
+        .filter(|j| j.state == JobState::Completed)
+        .count();
+    let running = jobs.iter().filter(|j| j.state == JobState::Running).count();
+    let pending = jobs.iter().filter(|j| j.state == JobState::Pending).count();
     (completed, running, pending)
 }
 
@@ -0,0 +1,257 @@
+//! Following a push as its jobs run: successive job lists are compared to
+//! report each job as it starts, fails or passes.
+
+use crate::models::{ErrorLine, FetchError, Job, JobState};
+use crate::query::PushStatus;
+use crate::util::are_all_jobs_complete;
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::time::Duration;
+
+/// A job that changed state between two job lists.
+#[derive(Debug, Clone)]
+pub enum JobChange {
+    Started(Job),
+    Completed(Job),
+}
+
+/// Remembers the state of every job seen so far.
+#[derive(Debug, Clone, Default)]
+pub struct JobTracker {
+    states: HashMap<u64, JobState>,
+}
+
+impl JobTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `jobs` and returns those that started or completed since the
+    /// previous call. On the first call, jobs already running or completed
+    /// count as changed, so that nothing that happened before is missed.
+    pub fn update(&mut self, jobs: &[Job]) -> Vec<JobChange> {
+        let mut changes = Vec::new();
+        for job in jobs {
+            let previous = self.states.insert(job.id, job.state.clone());
+            if previous.as_ref() == Some(&job.state) {
+                continue;
+            }
+            match job.state {
+                JobState::Completed => changes.push(JobChange::Completed(job.clone())),
+                JobState::Running if previous != Some(JobState::Completed) => {
+                    changes.push(JobChange::Started(job.clone()))
+                }
+                _ => {}
+            }
+        }
+        changes
+    }
+}
+
+/// The jobs of a push as last seen, updated with the jobs modified since.
+#[derive(Debug, Clone, Default)]
+pub struct JobList {
+    jobs: BTreeMap<u64, Job>,
+}
+
+impl JobList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the jobs with the same IDs as `jobs` and adds the others.
+    pub fn merge(&mut self, jobs: Vec<Job>) {
+        for job in jobs {
+            self.jobs.insert(job.id, job);
+        }
+    }
+
+    pub fn jobs(&self) -> Vec<Job> {
+        self.jobs.values().cloned().collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// The latest `last_modified` of the jobs, to only fetch the jobs
+    /// modified after it next time.
+    pub fn last_modified(&self) -> Option<&str> {
+        self.jobs
+            .values()
+            .filter_map(|job| job.last_modified.as_deref())
+            .max()
+    }
+}
+
+/// How long to wait between polls: `min` while jobs keep changing, backing
+/// off to `max` while they do not, and `max` right away while no job runs.
+/// Delays are jittered so that watches of the same push spread out.
+#[derive(Debug, Clone)]
+pub struct PollSchedule {
+    min: Duration,
+    max: Duration,
+    current: Duration,
+}
+
+impl PollSchedule {
+    pub fn new(min: Duration, max: Duration) -> Self {
+        let min = min.min(max);
+        Self {
+            min,
+            max,
+            current: min,
+        }
+    }
+
+    /// The delay before the next poll, given whether the last poll saw jobs
+    /// start or complete and whether any job is running.
+    pub fn next_delay(&mut self, changed: bool, running: bool) -> Duration {
+        self.current = if changed {
+            self.min
+        } else if !running {
+            self.max
+        } else {
+            self.current.saturating_mul(2).clamp(self.min, self.max)
+        };
+        self.current.mul_f64(0.8 + 0.4 * fastrand::f64())
+    }
+}
+
+/// When to stop watching a push.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchCondition {
+    /// Every job of the push completed, whatever the filters.
+    AllComplete,
+    /// Every job matching the filters completed.
+    FilteredComplete,
+    /// Every tier-1 job matching the filters completed.
+    Tier1Complete,
+    /// A job matching the filters failed.
+    FirstFailure,
+    /// At least this many jobs matching the filters failed.
+    Failures(usize),
+}
+
+impl WatchCondition {
+    pub const NAMES: &'static [&'static str] = &[
+        "all-complete",
+        "filtered-complete",
+        "tier1-complete",
+        "first-failure",
+        "failures>=N",
+    ];
+
+    pub fn parse(s: &str) -> Result<Self> {
+        Ok(match s.trim() {
+            "all-complete" => WatchCondition::AllComplete,
+            "filtered-complete" => WatchCondition::FilteredComplete,
+            "tier1-complete" => WatchCondition::Tier1Complete,
+            "first-failure" => WatchCondition::FirstFailure,
+            other => {
+                let count = other
+                    .strip_prefix("failures>=")
+                    .and_then(|n| n.trim().parse::<usize>().ok())
+                    .filter(|n| *n > 0)
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Unknown watch condition '{}', expected one of: {}",
+                            other,
+                            Self::NAMES.join(", ")
+                        )
+                    })?;
+                WatchCondition::Failures(count)
+            }
+        })
+    }
+
+    /// Whether the condition holds, given every job of the push, the
+    /// `watched` jobs matching the filters and the number of reported
+    /// failures so far. Conditions on a subset of the jobs wait for it to
+    /// have jobs, unless the whole push completed without any.
+    pub fn is_met(&self, all_jobs: &[Job], watched: &[Job], failed: usize) -> bool {
+        let push_complete = are_all_jobs_complete(all_jobs);
+        match self {
+            WatchCondition::AllComplete => push_complete,
+            WatchCondition::FilteredComplete => {
+                push_complete || (!watched.is_empty() && are_all_jobs_complete(watched))
+            }
+            WatchCondition::Tier1Complete => {
+                let mut tier1 = watched.iter().filter(|job| job.tier == Some(1)).peekable();
+                push_complete
+                    || (tier1.peek().is_some() && tier1.all(|job| job.state == JobState::Completed))
+            }
+            WatchCondition::FirstFailure => failed > 0,
+            WatchCondition::Failures(count) => failed >= *count,
+        }
+    }
+
+    /// Whether the condition only holds once every watched job completed.
+    pub fn waits_for_all(&self) -> bool {
+        matches!(
+            self,
+            WatchCondition::AllComplete | WatchCondition::FilteredComplete
+        )
+    }
+}
+
+impl fmt::Display for WatchCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WatchCondition::AllComplete => f.write_str("all-complete"),
+            WatchCondition::FilteredComplete => f.write_str("filtered-complete"),
+            WatchCondition::Tier1Complete => f.write_str("tier1-complete"),
+            WatchCondition::FirstFailure => f.write_str("first-failure"),
+            WatchCondition::Failures(count) => write!(f, "failures>={}", count),
+        }
+    }
+}
+
+impl Serialize for WatchCondition {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Something that happened on a watched push, printed as one line of
+/// NDJSON under `--json`.
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchEvent {
+    #[serde(flatten)]
+    pub kind: WatchEventKind,
+    pub revision: String,
+    pub push_id: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum WatchEventKind {
+    JobStarted {
+        job: Job,
+    },
+    /// A job failed, with the failures of its errorsummary logs.
+    JobFailed {
+        job: Job,
+        errors: Vec<ErrorLine>,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        fetch_errors: Vec<FetchError>,
+    },
+    JobPassed {
+        job: Job,
+    },
+    /// The watch ended because the `until` condition was met; unless it
+    /// waits for all jobs, some watched jobs may still be running.
+    PushComplete {
+        status: PushStatus,
+        until: WatchCondition,
+        /// Watched jobs, those matching the filters.
+        jobs: usize,
+        /// Every job of the push, whether or not it matches the filters.
+        total_jobs: usize,
+        completed: usize,
+        failed: usize,
+        passed: usize,
+    },
+}
@@ -0,0 +1,359 @@
+mod common;
+
+use common::*;
+use serde_json::json;
+
+const HOOK_TRIGGER: &str =
+    "/api/hooks/v1/hooks/project-gecko/in-tree-action-1-generic%2Fea5d85cbef/trigger";
+
+fn posts(server: &MockServer) -> Vec<RecordedRequest> {
+    server
+        .requests()
+        .into_iter()
+        .filter(|r| r.method == "POST")
+        .collect()
+}
+
+fn body(request: &RecordedRequest) -> serde_json::Value {
+    serde_json::from_slice(&request.body).unwrap()
+}
+
+/// Checks the MAC of a Hawk `authorization` header for a POST to `path`
+/// on `server`, signed with `access_token`.
+fn assert_hawk_mac(server: &MockServer, authorization: &str, path: &str, access_token: &str) {
+    let field = |name: &str| {
+        let start = authorization.find(&format!("{}=\"", name)).unwrap() + name.len() + 2;
+        let end = start + authorization[start..].find('"').unwrap();
+        authorization[start..end].to_string()
+    };
+    let url = url::Url::parse(server.url()).unwrap();
+    let normalized = format!(
+        "hawk.1.header\n{}\n{}\nPOST\n{}\n{}\n{}\n\n\n",
+        field("ts"),
+        field("nonce"),
+        path,
+        url.host_str().unwrap(),
+        url.port().unwrap()
+    );
+    let key = ring::hmac::Key::new(ring::hmac::HMAC_SHA256, access_token.as_bytes());
+    let expected = base64::engine::Engine::encode(
+        &base64::engine::general_purpose::STANDARD,
+        ring::hmac::sign(&key, normalized.as_bytes()).as_ref(),
+    );
+    assert_eq!(field("mac"), expected, "{}", authorization);
+}
+
+fn with_taskcluster_credentials(server: &MockServer) -> std::process::Command {
+    let mut cmd = cli(server);
+    cmd.env(
+        "TASKCLUSTER_CLIENT_ID",
+        "mozilla-auth0/ad|Mozilla-LDAP|dev/cli",
+    )
+    .env("TASKCLUSTER_ACCESS_TOKEN", "secret-token");
+    cmd
+}
+
+#[test]
+fn test_retrigger_dry_run() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .args([
+            "--json",
+            "retrigger",
+            BASE_REVISION,
+            "--times",
+            "3",
+            "--dry-run",
+        ])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+    let json = stdout_json(&output);
+    assert_eq!(json["dry_run"], true);
+    let actions = json["actions"].as_array().unwrap();
+    let ids: Vec<u64> = actions
+        .iter()
+        .map(|a| a["job_id"].as_u64().unwrap())
+        .collect();
+    assert_eq!(ids, vec![5001, 5002, 5005]);
+    assert!(actions
+        .iter()
+        .all(|a| a["status"] == "planned" && a["action"] == "retrigger 3 times"));
+    assert!(posts(&server).is_empty());
+
+    let output = cli(&server)
+        .args(["retrigger", BASE_REVISION, "--dry-run"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Treeherder Actions (dry run)"),
+        "{}",
+        stdout
+    );
+    assert!(
+        stdout.contains("3 actions planned; nothing was changed"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn test_retrigger_needs_credentials() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .args(["retrigger", BASE_REVISION])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("TASKCLUSTER_CLIENT_ID"), "{}", stderr);
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_retrigger_triggers_action_hook() {
+    let server = fixture_server();
+    server.mock_method(
+        "POST",
+        HOOK_TRIGGER,
+        MockResponse::json(r#"{"status": {"taskId": "AcTask5001AAAAAAAAAAAA"}}"#),
+    );
+
+    let output = with_taskcluster_credentials(&server)
+        .args(["--json", "retrigger", BASE_REVISION, "--symbol", "mda1"])
+        .args(["--times", "3"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+    let json = stdout_json(&output);
+    assert_eq!(json["actions"][0]["status"], "done");
+    assert_eq!(
+        json["actions"][0]["detail"],
+        "action task AcTask5001AAAAAAAAAAAA"
+    );
+
+    let posts = posts(&server);
+    assert_eq!(posts.len(), 1);
+    let authorization = posts[0].header("Authorization").unwrap();
+    assert!(
+        authorization.starts_with("Hawk id=\"mozilla-auth0/ad|Mozilla-LDAP|dev/cli\", ts=\""),
+        "{}",
+        authorization
+    );
+    assert_hawk_mac(&server, authorization, HOOK_TRIGGER, "secret-token");
+
+    let payload = body(&posts[0]);
+    assert_eq!(payload["decision"]["action"]["cb_name"], "retrigger_action");
+    assert_eq!(
+        payload["user"],
+        json!({
+            "input": {"times": 3, "downstream": false},
+            "taskId": "Tm5001AAAAAAAAAAAAAAAA",
+            "taskGroupId": "Dc1001AAAAAAAAAAAAAAAA",
+        })
+    );
+}
+
+#[test]
+fn test_backfill_and_cancel_inputs() {
+    let server = fixture_server();
+    server.mock_method(
+        "POST",
+        HOOK_TRIGGER,
+        MockResponse::json(r#"{"status": {"taskId": "AcTaskAAAAAAAAAAAAAAAA"}}"#),
+    );
+
+    let output = with_taskcluster_credentials(&server)
+        .args([
+            "backfill",
+            BASE_REVISION,
+            "--symbol",
+            "mda2",
+            "--depth",
+            "8",
+        ])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+
+    let output = with_taskcluster_credentials(&server)
+        .args(["cancel", BASE_REVISION, "--where", "state = running"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("1 done, 0 failed"), "{}", stdout);
+
+    let posts = posts(&server);
+    assert_eq!(posts.len(), 2);
+    let backfill = body(&posts[0]);
+    assert_eq!(backfill["decision"]["action"]["name"], "backfill");
+    assert_eq!(backfill["user"]["input"], json!({"depth": 8, "times": 1}));
+    assert_eq!(backfill["user"]["taskId"], "Tm5002AAAAAAAAAAAAAAAA");
+    let cancel = body(&posts[1]);
+    assert_eq!(cancel["decision"]["action"]["name"], "cancel");
+    assert_eq!(cancel["user"]["input"], json!({}));
+    assert_eq!(cancel["user"]["taskId"], "Tm5006AAAAAAAAAAAAAAAA");
+}
+
+#[test]
+fn test_failed_action_reported() {
+    let server = fixture_server();
+    server.mock_method("POST", HOOK_TRIGGER, MockResponse::status(403));
+
+    let output = with_taskcluster_credentials(&server)
+        .args(["--json", "retrigger", BASE_REVISION, "--symbol", "mda1"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    let json = stdout_json(&output);
+    assert_eq!(json["actions"][0]["status"], "failed");
+    assert!(json["actions"][0]["detail"]
+        .as_str()
+        .unwrap()
+        .contains("403"));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("1 of 1 actions failed"), "{}", stderr);
+}
+
+/// Serves a Treeherder login that starts session `sess-1` with CSRF token
+/// `csrf-1`, as Django does.
+fn mock_login(server: &MockServer) {
+    server.mock(
+        "/api/auth/login/",
+        MockResponse::json(r#"{"username": "mozilla-auth0/ad|Mozilla-LDAP|dev"}"#)
+            .header(
+                "Set-Cookie",
+                "csrftoken=csrf-1; expires=Sat, 17 Oct 2027 00:00:00 GMT; Path=/; SameSite=Lax",
+            )
+            .header(
+                "Set-Cookie",
+                "sessionid=sess-1; HttpOnly; Path=/; SameSite=Lax; Secure",
+            ),
+    );
+}
+
+/// The classify command, with the tokens of a Treeherder login.
+fn classify(server: &MockServer) -> std::process::Command {
+    let mut cmd = cli(server);
+    cmd.env("TREEHERDER_ACCESS_TOKEN", "th-token")
+        .env("TREEHERDER_ID_TOKEN", "th-id-token")
+        .env("TREEHERDER_ACCESS_TOKEN_EXPIRES_AT", "1760000000")
+        .args(["--json", "classify", BASE_REVISION, "--symbol", "mda1"]);
+    cmd
+}
+
+#[test]
+fn test_classify_posts_note_and_bug() {
+    let server = fixture_server();
+    mock_login(&server);
+    server.mock_method(
+        "POST",
+        "/api/project/try/note/",
+        MockResponse::json(r#"{"message": "note stored for job 5001"}"#),
+    );
+    server.mock_method(
+        "POST",
+        "/api/project/try/bug-job-map/",
+        MockResponse::json(r#"{"message": "Bug job map saved"}"#),
+    );
+
+    let output = classify(&server)
+        .args(["--bug", "1876543", "--comment", "known intermittent"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+    let json = stdout_json(&output);
+    assert_eq!(
+        json["actions"][0]["action"],
+        "classify as intermittent with bug 1876543"
+    );
+    assert_eq!(json["actions"][0]["status"], "done");
+
+    let logins: Vec<RecordedRequest> = server
+        .requests()
+        .into_iter()
+        .filter(|r| r.path == "/api/auth/login/")
+        .collect();
+    assert_eq!(logins.len(), 1);
+    assert_eq!(logins[0].method, "GET");
+    assert_eq!(logins[0].header("Authorization"), Some("Bearer th-token"));
+    assert_eq!(logins[0].header("Id-Token"), Some("th-id-token"));
+    assert_eq!(
+        logins[0].header("Access-Token-Expires-At"),
+        Some("1760000000")
+    );
+
+    let posts = posts(&server);
+    assert_eq!(posts.len(), 2);
+    for post in &posts {
+        assert_eq!(
+            post.header("Cookie"),
+            Some("sessionid=sess-1; csrftoken=csrf-1")
+        );
+        assert_eq!(post.header("X-CSRFToken"), Some("csrf-1"));
+        assert_eq!(
+            post.header("Referer"),
+            Some(format!("{}/", server.url()).as_str())
+        );
+        assert_eq!(post.header("Authorization"), None);
+    }
+    assert_eq!(posts[0].path, "/api/project/try/note/");
+    assert_eq!(
+        body(&posts[0]),
+        json!({"job_id": 5001, "failure_classification_id": 4, "text": "known intermittent"})
+    );
+    assert_eq!(posts[1].path, "/api/project/try/bug-job-map/");
+    assert_eq!(
+        body(&posts[1]),
+        json!({"job_id": 5001, "bug_id": 1876543, "type": "annotation"})
+    );
+}
+
+#[test]
+fn test_classify_needs_full_login() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .env("TREEHERDER_ACCESS_TOKEN", "th-token")
+        .args(["classify", BASE_REVISION, "--symbol", "mda1"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("TREEHERDER_ID_TOKEN"), "{}", stderr);
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_classify_fails_without_session() {
+    let server = fixture_server();
+    server.mock("/api/auth/login/", MockResponse::status(403));
+
+    let output = classify(&server).output().unwrap();
+
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Could not log in to Treeherder"),
+        "{}",
+        stderr
+    );
+    assert!(posts(&server).is_empty());
+
+    let server = fixture_server();
+    server.mock("/api/auth/login/", MockResponse::json("{}"));
+
+    let output = classify(&server).output().unwrap();
+
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("set no session cookie"), "{}", stderr);
+    assert!(posts(&server).is_empty());
+}
@@ -3,17 +3,17 @@ use predicates::prelude::*;
 use std::process::Command;
 
 #[test]
-fn test_download_artifacts_flag_exists() {
+fn test_artifacts_subcommand_exists() {
     let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
     cmd.arg("--help");
 
     cmd.assert()
         .success()
-        .stdout(predicate::str::contains("--download-artifacts"));
+        .stdout(predicate::str::contains("artifacts"));
 }
 
 #[test]
-fn test_download_artifacts_help_text() {
+fn test_artifacts_help_text() {
     let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
     cmd.arg("--help");
 
@@ -25,7 +25,7 @@ fn test_download_artifacts_help_text() {
 #[test]
 fn test_artifact_pattern_flag_exists() {
     let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
-    cmd.arg("--help");
+    cmd.arg("artifacts").arg("--help");
 
     cmd.assert()
         .success()
@@ -35,9 +35,19 @@ fn test_artifact_pattern_flag_exists() {
 #[test]
 fn test_artifact_pattern_help_text() {
     let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
-    cmd.arg("--help");
+    cmd.arg("artifacts").arg("--help");
 
     cmd.assert().success().stdout(predicate::str::contains(
         "Regex pattern to filter artifacts",
     ));
 }
+
+#[test]
+fn test_artifacts_output_dir_flag_exists() {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
+    cmd.arg("artifacts").arg("--help");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("--output-dir"));
+}
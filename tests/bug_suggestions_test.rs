@@ -0,0 +1,122 @@
+mod common;
+
+use common::*;
+
+fn job(json: &serde_json::Value, id: u64) -> &serde_json::Value {
+    json["jobs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|j| j["job"]["id"] == id)
+        .unwrap()
+}
+
+#[test]
+fn test_bug_suggestions_json() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .args(["--json", BASE_REVISION])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let json = stdout_json(&output);
+    assert_eq!(json["fetch_errors"], serde_json::json!([]));
+
+    let errors = job(&json, 5001)["errors"].as_array().unwrap();
+    let playback = errors
+        .iter()
+        .find(|e| e["test"] == "dom/media/test/test_playback.html")
+        .unwrap();
+    let bugs = playback["bug_suggestions"].as_array().unwrap();
+    assert_eq!(bugs.len(), 2);
+    // Open recent bugs come first.
+    assert_eq!(bugs[0]["id"], 1876543);
+    assert_eq!(bugs[0]["status"], "NEW");
+    assert_eq!(bugs[1]["id"], 1500001);
+    assert_eq!(bugs[1]["resolution"], "WORKSFORME");
+
+    // Lines without matching bugs have no suggestions.
+    let seek = job(&json, 5002)["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|e| e["test"] == "dom/media/test/test_seek.html")
+        .unwrap()
+        .clone();
+    assert!(seek.get("bug_suggestions").is_none(), "{}", seek);
+    assert_eq!(
+        server.hits("/api/project/try/jobs/5003/bug_suggestions/"),
+        0
+    );
+}
+
+#[test]
+fn test_bug_suggestions_markdown() {
+    let server = fixture_server();
+    let output = cli(&server).arg(BASE_REVISION).output().unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Suggested bugs for dom/media/test/test_playback.html"),
+        "{}",
+        stdout
+    );
+    assert!(
+        stdout.contains(
+            "Bug 1876543 [NEW] Intermittent dom/media/test/test_playback.html | Timed out waiting for ended event"
+        ),
+        "{}",
+        stdout
+    );
+    assert!(
+        stdout.contains("Bug 1500001 [RESOLVED WORKSFORME]"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn test_bug_suggestions_grouped() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .args(["--json", "--group-by", "test", BASE_REVISION])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let json = stdout_json(&output);
+    let grouped = json["grouped_failures"].as_array().unwrap();
+    assert_eq!(grouped[0]["test_name"], "dom/media/test/test_playback.html");
+    // Both jobs suggest the same bugs; each is listed once.
+    let bugs = grouped[0]["bug_suggestions"].as_array().unwrap();
+    assert_eq!(bugs.len(), 2);
+    assert!(grouped[1].get("bug_suggestions").is_none());
+}
+
+#[test]
+fn test_bug_suggestions_fetch_error() {
+    let server = fixture_server();
+    server.mock_first(
+        "/api/project/try/jobs/5001/bug_suggestions/",
+        MockResponse::status(404),
+    );
+
+    let output = cli(&server)
+        .args(["--json", BASE_REVISION])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let json = stdout_json(&output);
+    // The failures are still reported, without suggestions.
+    let errors = job(&json, 5001)["errors"].as_array().unwrap();
+    assert!(!errors.is_empty());
+    assert!(errors.iter().all(|e| e.get("bug_suggestions").is_none()));
+
+    let fetch_errors = json["fetch_errors"].as_array().unwrap();
+    assert_eq!(fetch_errors.len(), 1);
+    assert_eq!(fetch_errors[0]["job_id"], 5001);
+    assert_eq!(fetch_errors[0]["log_name"], "bug_suggestions");
+}
@@ -0,0 +1,131 @@
+mod common;
+
+use common::*;
+use serde_json::json;
+use std::path::Path;
+use std::process::Command;
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .env("GIT_AUTHOR_NAME", "Dev")
+        .env("GIT_AUTHOR_EMAIL", "dev@mozilla.com")
+        .env("GIT_COMMITTER_NAME", "Dev")
+        .env("GIT_COMMITTER_EMAIL", "dev@mozilla.com")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+/// A Git clone with one commit, returning its hash.
+fn checkout(dir: &Path) -> String {
+    git(dir, &["init", "-q"]);
+    git(dir, &["config", "user.email", "dev@mozilla.com"]);
+    git(
+        dir,
+        &["commit", "-q", "--allow-empty", "-m", "Bug 1 - Fix it"],
+    );
+    git(dir, &["rev-parse", "HEAD"])
+}
+
+#[test]
+fn test_checked_out_push_tip() {
+    let server = fixture_server();
+    let dir = tempfile::tempdir().unwrap();
+    let revision = checkout(dir.path());
+    server.mock(
+        &format!("/api/project/try/push/?revision={}", revision),
+        MockResponse::json(
+            json!({"results": [{"id": BASE_PUSH_ID, "revision": revision}]}).to_string(),
+        ),
+    );
+
+    let output = cli(&server)
+        .current_dir(dir.path())
+        .arg("--json")
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let json = stdout_json(&output);
+    assert_eq!(json["revision"], revision);
+    assert_eq!(json["push_id"], BASE_PUSH_ID);
+}
+
+#[test]
+fn test_checked_out_commit_under_try_push() {
+    let server = fixture_server();
+    let dir = tempfile::tempdir().unwrap();
+    let revision = checkout(dir.path());
+    server.mock(
+        &format!("/api/project/try/push/?revision={}", revision),
+        MockResponse::json(json!({"results": []}).to_string()),
+    );
+    server.mock(
+        "/api/project/try/push/?author=dev@mozilla.com",
+        MockResponse::json(
+            json!({"results": [
+                {"id": COMPARE_PUSH_ID, "revision": COMPARE_REVISION,
+                 "revisions": [{"revision": COMPARE_REVISION}]},
+                {"id": BASE_PUSH_ID, "revision": BASE_REVISION,
+                 "revisions": [{"revision": BASE_REVISION}, {"revision": revision}]},
+            ]})
+            .to_string(),
+        ),
+    );
+
+    let output = cli(&server)
+        .current_dir(dir.path())
+        .args(["--json", "summary", "."])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    assert_eq!(stdout_json(&output)["revision"], BASE_REVISION);
+}
+
+#[test]
+fn test_checked_out_commit_never_pushed() {
+    let server = fixture_server();
+    let dir = tempfile::tempdir().unwrap();
+    let revision = checkout(dir.path());
+    server.mock(
+        "/api/project/try/push/",
+        MockResponse::json(json!({"results": []}).to_string()),
+    );
+
+    let output = cli(&server).arg(dir.path()).output().unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(&format!(
+            "No try push found containing the checked out revision {}",
+            revision
+        )),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_not_a_checkout() {
+    let server = fixture_server();
+    let dir = tempfile::tempdir().unwrap();
+
+    let output = cli(&server)
+        .current_dir(dir.path())
+        .env("GIT_CEILING_DIRECTORIES", dir.path().parent().unwrap())
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("is not in a Git or Mercurial checkout"),
+        "{}",
+        stderr
+    );
+}
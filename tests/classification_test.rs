@@ -0,0 +1,126 @@
+mod common;
+
+use common::*;
+
+#[test]
+fn test_classification_json() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .args(["--json", "--include-intermittent", BASE_REVISION])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let json = stdout_json(&output);
+    assert_eq!(json["fetch_errors"], serde_json::json!([]));
+
+    let jobs = json["jobs"].as_array().unwrap();
+    let classified = jobs.iter().find(|j| j["job"]["id"] == 5004).unwrap();
+    let notes = classified["classification_notes"].as_array().unwrap();
+    assert_eq!(notes.len(), 2);
+    // Newest first, with the classification by name.
+    assert_eq!(notes[0]["classification"], "intermittent");
+    assert_eq!(notes[0]["who"], "sheriff@mozilla.com");
+    assert_eq!(notes[0]["text"], "frequent on macOS");
+    assert_eq!(notes[1]["classification"], "intermittent needs bugid");
+    assert_eq!(classified["classified_bugs"][0]["bug_id"], 1765432);
+    assert_eq!(
+        classified["classified_bugs"][0]["who"],
+        "sheriff@mozilla.com"
+    );
+
+    // Unclassified jobs are not looked up.
+    let unclassified = jobs.iter().find(|j| j["job"]["id"] == 5001).unwrap();
+    assert!(unclassified.get("classification_notes").is_none());
+    assert!(unclassified.get("classified_bugs").is_none());
+    assert_eq!(server.hits("/api/project/try/note/"), 1);
+}
+
+#[test]
+fn test_classification_markdown() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .args(["--include-intermittent", BASE_REVISION])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains(
+            "Classified: intermittent by sheriff@mozilla.com on 2025-10-08 - frequent on macOS"
+        ),
+        "{}",
+        stdout
+    );
+    assert!(
+        stdout.contains("Classified: intermittent needs bugid by dev@mozilla.com on 2025-10-08"),
+        "{}",
+        stdout
+    );
+    assert!(
+        stdout.contains("Classified bugs: Bug 1765432 (sheriff@mozilla.com)"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn test_classification_filter() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .args(["--json", "--classification", "intermittent", BASE_REVISION])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    assert_eq!(job_ids(&output), vec![5004]);
+
+    let output = cli(&server)
+        .args([
+            "--json",
+            "--classification",
+            "not-classified,infra",
+            BASE_REVISION,
+        ])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    assert_eq!(job_ids(&output), vec![5001, 5002, 5005]);
+}
+
+#[test]
+fn test_classification_filter_rejects_unknown_names() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .args(["--classification", "4", BASE_REVISION])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("intermittent-needs-bugid"), "{}", stderr);
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_classification_fetch_error() {
+    let server = fixture_server();
+    server.mock_first(
+        "/api/project/try/bug-job-map/?job_id=5004",
+        MockResponse::status(404),
+    );
+
+    let output = cli(&server)
+        .args(["--json", "--include-intermittent", BASE_REVISION])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let json = stdout_json(&output);
+    let fetch_errors = json["fetch_errors"].as_array().unwrap();
+    assert_eq!(fetch_errors.len(), 1);
+    assert_eq!(fetch_errors[0]["job_id"], 5004);
+    assert_eq!(fetch_errors[0]["log_name"], "bug-job-map");
+}
@@ -0,0 +1,517 @@
+//! Offline stand-in for Treeherder, Taskcluster and Lando.
+//!
+//! `MockServer` is a tiny HTTP/1.1 server serving canned responses from
+//! `tests/fixtures`. Tests point the binary at it through the endpoint
+//! environment variables, so the full pipeline runs without
+//! network access.
+#![allow(dead_code)]
+
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+pub const BASE_REVISION: &str = "a13b9fc22101b1e7a44ba1392eb275d9bdf202a2";
+pub const COMPARE_REVISION: &str = "b2c3d4e5f6789012345678901234567890abcdef";
+pub const BASE_PUSH_ID: u64 = 1001;
+pub const COMPARE_PUSH_ID: u64 = 1002;
+pub const LANDO_JOB_ID: u64 = 12345;
+
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl MockResponse {
+    pub fn json(body: impl Into<String>) -> Self {
+        Self::with_content_type("application/json", body.into().into_bytes())
+    }
+
+    pub fn text(body: impl Into<String>) -> Self {
+        Self::with_content_type("text/plain", body.into().into_bytes())
+    }
+
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            headers: vec![],
+            body: vec![],
+        }
+    }
+
+    pub fn status_code(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    fn with_content_type(content_type: &str, body: Vec<u8>) -> Self {
+        Self {
+            status: 200,
+            headers: vec![("Content-Type".to_string(), content_type.to_string())],
+            body,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn query_param(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+struct Route {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    // The last response is sticky; earlier ones are served once each.
+    responses: VecDeque<MockResponse>,
+}
+
+#[derive(Default)]
+struct State {
+    routes: Vec<Route>,
+    requests: Vec<RecordedRequest>,
+}
+
+#[derive(Clone)]
+pub struct MockServer {
+    url: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockServer {
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let accept_state = Arc::clone(&state);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let state = Arc::clone(&accept_state);
+                thread::spawn(move || handle_connection(stream, state));
+            }
+        });
+
+        Self { url, state }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Registers a response for `GET path`. `path` may carry a query string,
+    /// whose parameters must all be present in a request for it to match.
+    /// Calling this several times for the same route queues the responses.
+    pub fn mock(&self, path: &str, response: MockResponse) {
+        self.mock_method("GET", path, response);
+    }
+
+    pub fn mock_method(&self, method: &str, path: &str, response: MockResponse) {
+        let (path, query) = split_path(path);
+        let mut state = self.state.lock().unwrap();
+        if let Some(route) = state
+            .routes
+            .iter_mut()
+            .find(|r| r.method == method && r.path == path && r.query == query)
+        {
+            route.responses.push_back(response);
+        } else {
+            state.routes.push(Route {
+                method: method.to_string(),
+                path,
+                query,
+                responses: VecDeque::from([response]),
+            });
+        }
+    }
+
+    /// Queues `response` ahead of those already registered for `GET path`,
+    /// e.g. to make the first request to a fixture route fail.
+    pub fn mock_first(&self, path: &str, response: MockResponse) {
+        let (path, query) = split_path(path);
+        let mut state = self.state.lock().unwrap();
+        match state
+            .routes
+            .iter_mut()
+            .find(|r| r.method == "GET" && r.path == path && r.query == query)
+        {
+            Some(route) => route.responses.push_front(response),
+            None => state.routes.push(Route {
+                method: "GET".to_string(),
+                path,
+                query,
+                responses: VecDeque::from([response]),
+            }),
+        }
+    }
+
+    /// Serves a file from `tests/fixtures`, with `{{base}}` replaced by the
+    /// server URL so that log links point back at the mock.
+    pub fn mock_fixture(&self, path: &str, fixture_name: &str) {
+        let body = self.fixture(fixture_name);
+        let response = if fixture_name.ends_with(".json") {
+            MockResponse::json(body)
+        } else {
+            MockResponse::text(body)
+        };
+        self.mock(path, response);
+    }
+
+    pub fn fixture(&self, name: &str) -> String {
+        fixture(name).replace("{{base}}", &self.url)
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    pub fn hits(&self, path: &str) -> usize {
+        self.requests().iter().filter(|r| r.path == path).count()
+    }
+}
+
+pub fn fixture(name: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name);
+    std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("failed to read fixture {}: {}", path.display(), e))
+}
+
+/// A mock serving the base and comparison try pushes used by the end-to-end
+/// tests, along with their job details, logs, artifacts and history.
+pub fn fixture_server() -> MockServer {
+    let server = MockServer::start();
+
+    server.mock_fixture(
+        &format!("/api/project/try/push/?revision={}", BASE_REVISION),
+        "push_base.json",
+    );
+    server.mock_fixture(
+        &format!("/api/project/try/push/?revision={}", COMPARE_REVISION),
+        "push_compare.json",
+    );
+    server.mock_fixture(
+        &format!("/api/jobs/?push_id={}", BASE_PUSH_ID),
+        "jobs_base.json",
+    );
+    server.mock_fixture(
+        &format!("/api/jobs/?push_id={}", COMPARE_PUSH_ID),
+        "jobs_compare.json",
+    );
+
+    for job_id in [5001, 5002, 5003, 5004, 5005, 5006, 6001, 6002, 6003] {
+        server.mock_fixture(
+            &format!("/api/project/try/jobs/{}/", job_id),
+            &format!("job_{}.json", job_id),
+        );
+    }
+
+    for job_id in [5001, 5002, 5004, 6001, 6002] {
+        server.mock_fixture(
+            &format!("/logs/{}/errorsummary.log", job_id),
+            &format!("errorsummary_{}.log", job_id),
+        );
+    }
+    for job_id in [5001, 5002] {
+        server.mock_fixture(
+            &format!("/api/project/try/jobs/{}/bug_suggestions/", job_id),
+            "bug_suggestions_5001.json",
+        );
+    }
+    for job_id in [5004, 6001, 6002] {
+        server.mock(
+            &format!("/api/project/try/jobs/{}/bug_suggestions/", job_id),
+            MockResponse::json("[]"),
+        );
+    }
+    server.mock_fixture("/api/project/try/note/?job_id=5004", "note_5004.json");
+    server.mock_fixture(
+        "/api/project/try/bug-job-map/?job_id=5004",
+        "bug_job_map_5004.json",
+    );
+    for job_id in [5001, 5002, 5004, 5005, 6001, 6002] {
+        server.mock_fixture(
+            &format!("/logs/{}/live_backing.log", job_id),
+            "live_backing_failure.log",
+        );
+    }
+    for job_id in [5003, 5006, 6003] {
+        server.mock_fixture(
+            &format!("/logs/{}/live_backing.log", job_id),
+            "live_backing_success.log",
+        );
+    }
+
+    for job_id in [5001, 5002, 5005] {
+        let task_id = format!("Tm{}AAAAAAAAAAAAAAAA", job_id);
+        server.mock_fixture(
+            &format!("/api/queue/v1/task/{}/runs/0/artifacts", task_id),
+            &format!("artifacts_{}.json", job_id),
+        );
+        for name in [
+            "public/logs/live_backing.log",
+            "public/test_info/mochitest-media-errorsummary.log",
+            "public/test_info/screenshot-1.png",
+        ] {
+            server.mock(
+                &format!("/api/queue/v1/task/{}/runs/0/artifacts/{}", task_id, name),
+                MockResponse::text(format!("{} of {}", name, task_id)),
+            );
+        }
+    }
+    server.mock_fixture(
+        "/api/queue/v1/task/Tm5001AAAAAAAAAAAAAAAA/runs/0/artifacts/public/test_info/perfherder-data-resource-usage.json",
+        "perfherder_resource_usage.json",
+    );
+
+    server.mock_fixture(
+        "/api/project/try/jobs/5001/similar_jobs/",
+        "similar_jobs_5001.json",
+    );
+    server.mock_fixture(
+        &format!("/api/project/try/push/health/?revision={}", BASE_REVISION),
+        "push_health_base.json",
+    );
+    server.mock_fixture(
+        &format!(
+            "/api/project/try/push/decisiontask/?push_ids={}",
+            BASE_PUSH_ID
+        ),
+        "decision_task_1001.json",
+    );
+    server.mock_fixture(
+        "/api/queue/v1/task/Dc1001AAAAAAAAAAAAAAAA/artifacts/public/actions.json",
+        "actions_1001.json",
+    );
+    server.mock_fixture(
+        &format!("/landing_jobs/{}", LANDO_JOB_ID),
+        "lando_12345.json",
+    );
+
+    server
+}
+
+/// The base push's jobs, with the state and result of some changed as
+/// `(job ID, state, result)`.
+pub fn jobs_snapshot(changes: &[(u64, &str, &str)]) -> String {
+    let mut jobs: serde_json::Value = serde_json::from_str(&fixture("jobs_base.json")).unwrap();
+    let names: Vec<String> = serde_json::from_value(jobs["job_property_names"].clone()).unwrap();
+    let column = |name: &str| names.iter().position(|n| n == name).unwrap();
+    for row in jobs["results"].as_array_mut().unwrap() {
+        if let Some((_, state, result)) = changes.iter().find(|(id, _, _)| row[0] == *id) {
+            row[column("state")] = (*state).into();
+            row[column("result")] = (*result).into();
+        }
+    }
+    jobs.to_string()
+}
+
+/// A server whose base push runs in three steps: 5001 fails, then 5002
+/// fails and 5006 passes.
+pub fn running_push_server() -> MockServer {
+    let server = fixture_server();
+    let path = format!("/api/jobs/?push_id={}", BASE_PUSH_ID);
+    for snapshot in [
+        jobs_snapshot(&[
+            (5002, "completed", "testfailed"),
+            (5006, "completed", "success"),
+        ]),
+        jobs_snapshot(&[(5002, "running", "unknown")]),
+        jobs_snapshot(&[(5001, "running", "unknown"), (5002, "pending", "unknown")]),
+    ] {
+        server.mock_first(&path, MockResponse::json(snapshot));
+    }
+    server
+}
+
+/// The binary, pointed at `server` and isolated from the developer's
+/// environment (no update check, no colours, no config file, no
+/// coding-agent detection).
+pub fn cli(server: &MockServer) -> Command {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
+    cmd.env("TREEHERDER_URL", server.url())
+        .env("TASKCLUSTER_ROOT_URL", server.url())
+        .env("LANDO_URL", server.url())
+        .env("MOZTOOLS_UPDATE_CHECK", "0")
+        .env("NO_COLOR", "1")
+        .env("TREEHERDER_CLI_CONFIG", "/nonexistent/config.toml");
+    for var in [
+        "TREEHERDER_PROFILE",
+        "TASKCLUSTER_CLIENT_ID",
+        "TASKCLUSTER_ACCESS_TOKEN",
+        "TASKCLUSTER_CERTIFICATE",
+        "TREEHERDER_ACCESS_TOKEN",
+        "TREEHERDER_ID_TOKEN",
+        "TREEHERDER_ACCESS_TOKEN_EXPIRES_AT",
+        "MATRIX_HOMESERVER",
+        "MATRIX_ACCESS_TOKEN",
+        "CLAUDECODE",
+        "CODEX_SANDBOX",
+        "GEMINI_CLI",
+        "OPENCODE",
+    ] {
+        cmd.env_remove(var);
+    }
+    cmd
+}
+
+pub fn stdout_json(output: &std::process::Output) -> serde_json::Value {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    serde_json::from_str(&stdout)
+        .unwrap_or_else(|e| panic!("Output is not valid JSON: {}\nOutput: {}", e, stdout))
+}
+
+/// The sorted IDs of the jobs in a `--json` report.
+pub fn job_ids(output: &std::process::Output) -> Vec<u64> {
+    let mut ids: Vec<u64> = stdout_json(output)["jobs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|j| j["job"]["id"].as_u64().unwrap())
+        .collect();
+    ids.sort();
+    ids
+}
+
+fn split_path(path: &str) -> (String, Vec<(String, String)>) {
+    match path.split_once('?') {
+        Some((path, query)) => (path.to_string(), parse_query(query)),
+        None => (path.to_string(), vec![]),
+    }
+}
+
+fn parse_query(query: &str) -> Vec<(String, String)> {
+    url::form_urlencoded::parse(query.as_bytes())
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect()
+}
+
+fn handle_connection(stream: TcpStream, state: Arc<Mutex<State>>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut stream = stream;
+
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).unwrap_or(0) == 0 {
+        return;
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("").to_string();
+    let target = parts.next().unwrap_or("/").to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+            return;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    let content_length = headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, v)| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; content_length];
+    if reader.read_exact(&mut body).is_err() {
+        return;
+    }
+
+    let (path, query) = split_path(&target);
+    let request = RecordedRequest {
+        method,
+        path,
+        query,
+        headers,
+        body,
+    };
+    let response = respond(&state, request);
+
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        reason(response.status),
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.write_all(&response.body);
+    let _ = stream.flush();
+}
+
+fn respond(state: &Arc<Mutex<State>>, request: RecordedRequest) -> MockResponse {
+    let mut state = state.lock().unwrap();
+
+    let response = state
+        .routes
+        .iter_mut()
+        .filter(|route| route.method == request.method && route.path == request.path)
+        .filter(|route| route.query.iter().all(|pair| request.query.contains(pair)))
+        .max_by_key(|route| route.query.len())
+        .map(|route| {
+            if route.responses.len() > 1 {
+                route.responses.pop_front().unwrap()
+            } else {
+                route.responses[0].clone()
+            }
+        })
+        .unwrap_or_else(|| MockResponse::text("Not found").status_code(404));
+
+    state.requests.push(request);
+    response
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        304 => "Not Modified",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}
@@ -3,17 +3,17 @@ use predicates::prelude::*;
 use std::process::Command;
 
 #[test]
-fn test_compare_flag_exists() {
+fn test_compare_subcommand_exists() {
     let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
     cmd.arg("--help");
 
     cmd.assert()
         .success()
-        .stdout(predicate::str::contains("--compare"));
+        .stdout(predicate::str::contains("compare"));
 }
 
 #[test]
-fn test_compare_flag_help_text() {
+fn test_compare_help_text() {
     let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
     cmd.arg("--help");
 
@@ -23,31 +23,27 @@ fn test_compare_flag_help_text() {
 }
 
 #[test]
-fn test_compare_incompatible_with_use_cache() {
+fn test_compare_requires_two_revisions() {
     let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
-    cmd.arg("--compare")
-        .arg("abc123")
-        .arg("--use-cache")
-        .arg("--cache-dir")
-        .arg("/tmp/test")
-        .arg("def456");
+    cmd.arg("compare").arg("def456");
 
-    cmd.assert().failure().stderr(predicate::str::contains(
-        "--compare cannot be used with --use-cache",
-    ));
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("<COMPARE>"));
 }
 
 #[test]
-fn test_compare_incompatible_with_watch() {
+fn test_compare_rejects_cache_dir() {
     let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
-    cmd.arg("--compare")
+    cmd.arg("compare")
+        .arg("def456")
         .arg("abc123")
-        .arg("--watch")
-        .arg("def456");
+        .arg("--cache-dir")
+        .arg("/tmp/test");
 
-    cmd.assert().failure().stderr(predicate::str::contains(
-        "--compare cannot be used with --watch",
-    ));
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--cache-dir"));
 }
 
 #[test]
@@ -57,10 +53,10 @@ fn test_compare_json_output_structure() {
     let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
     cmd.arg("--repo")
         .arg("try")
-        .arg("--compare")
-        .arg("a13b9fc22101b1e7a44ba1392eb275d9bdf202a2") // compare to itself for structure test
         .arg("--json")
-        .arg("a13b9fc22101b1e7a44ba1392eb275d9bdf202a2");
+        .arg("compare")
+        .arg("a13b9fc22101b1e7a44ba1392eb275d9bdf202a2")
+        .arg("a13b9fc22101b1e7a44ba1392eb275d9bdf202a2"); // compare to itself for structure test
 
     let output = cmd.output().unwrap();
 
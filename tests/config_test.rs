@@ -0,0 +1,96 @@
+mod common;
+
+use common::*;
+use std::fs;
+use tempfile::TempDir;
+
+const CONFIG: &str = r#"
+json = true
+match-filter = "all"
+
+[profile.media]
+filter = "mochitest-media"
+platform = "linux.*64"
+
+[profile.everything]
+include_intermittent = true
+"#;
+
+fn write_config(content: &str) -> TempDir {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("config.toml"), content).unwrap();
+    dir
+}
+
+#[test]
+fn test_top_level_defaults() {
+    let server = fixture_server();
+    let dir = write_config(CONFIG);
+
+    let output = cli(&server)
+        .env("TREEHERDER_CLI_CONFIG", dir.path().join("config.toml"))
+        .args(["--filter", "xpcshell", BASE_REVISION])
+        .output()
+        .unwrap();
+
+    assert_eq!(job_ids(&output), vec![5003]);
+}
+
+#[test]
+fn test_profile() {
+    let server = fixture_server();
+    let dir = write_config(CONFIG);
+
+    let output = cli(&server)
+        .env("TREEHERDER_CLI_CONFIG", dir.path().join("config.toml"))
+        .args(["--profile", "media", BASE_REVISION])
+        .output()
+        .unwrap();
+
+    assert_eq!(job_ids(&output), vec![5001]);
+}
+
+#[test]
+fn test_command_line_wins_over_profile() {
+    let server = fixture_server();
+    let dir = write_config(CONFIG);
+
+    let output = cli(&server)
+        .env("TREEHERDER_CLI_CONFIG", dir.path().join("config.toml"))
+        .env("TREEHERDER_PROFILE", "media")
+        .args(["summary", BASE_REVISION, "--platform", "windows"])
+        .output()
+        .unwrap();
+
+    assert_eq!(job_ids(&output), vec![5002]);
+}
+
+#[test]
+fn test_unknown_profile() {
+    let server = fixture_server();
+    let dir = write_config(CONFIG);
+
+    let output = cli(&server)
+        .env("TREEHERDER_CLI_CONFIG", dir.path().join("config.toml"))
+        .args(["--profile=missing", BASE_REVISION])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Profile 'missing' not found"));
+}
+
+#[test]
+fn test_unknown_option() {
+    let server = fixture_server();
+    let dir = write_config("colour = true\n");
+
+    let output = cli(&server)
+        .env("TREEHERDER_CLI_CONFIG", dir.path().join("config.toml"))
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown option 'colour'"));
+}
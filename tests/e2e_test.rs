@@ -0,0 +1,374 @@
+mod common;
+
+use common::*;
+
+#[test]
+fn test_default_summary_json() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .arg("--json")
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let json = stdout_json(&output);
+
+    assert_eq!(json["revision"], BASE_REVISION);
+    assert_eq!(json["push_id"], BASE_PUSH_ID);
+
+    let mut ids: Vec<u64> = json["jobs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|j| j["job"]["id"].as_u64().unwrap())
+        .collect();
+    ids.sort();
+    // Failures only, with the intermittent (5004) left out.
+    assert_eq!(ids, vec![5001, 5002, 5005]);
+
+    let linux = json["jobs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|j| j["job"]["id"] == 5001)
+        .unwrap();
+    let errors = linux["errors"].as_array().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0]["test"], "dom/media/test/test_playback.html");
+}
+
+#[test]
+fn test_default_summary_markdown() {
+    let server = fixture_server();
+    let output = cli(&server).arg(BASE_REVISION).output().unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(stdout.contains("Treeherder Test Results Summary"));
+    assert!(stdout.contains("Failed Jobs (3 failures)"));
+    assert!(stdout.contains("test-linux1804-64-qr/opt-mochitest-media-1"));
+    assert!(stdout.contains("dom/media/test/test_seek.html"));
+    assert!(stdout.contains("Stack trace"));
+    assert!(!stdout.contains("browser_tabs.js"));
+}
+
+#[test]
+fn test_summary_include_intermittent() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .arg("--json")
+        .arg("--include-intermittent")
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let json = stdout_json(&output);
+    assert_eq!(json["jobs"].as_array().unwrap().len(), 4);
+}
+
+#[test]
+fn test_summary_platform_and_duration_filters() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .arg("--json")
+        .arg("--match-filter")
+        .arg("all")
+        .arg("--platform")
+        .arg("linux")
+        .arg("--duration-min")
+        .arg("1000")
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let json = stdout_json(&output);
+    let jobs = json["jobs"].as_array().unwrap();
+    assert_eq!(jobs.len(), 1);
+    assert_eq!(jobs[0]["job"]["id"], 5001);
+}
+
+#[test]
+fn test_group_by_test() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .arg("--json")
+        .arg("--group-by")
+        .arg("test")
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let json = stdout_json(&output);
+    let grouped = json["grouped_failures"].as_array().unwrap();
+
+    assert_eq!(grouped.len(), 2);
+    // Sorted by number of affected platforms.
+    assert_eq!(grouped[0]["test_name"], "dom/media/test/test_playback.html");
+    assert_eq!(grouped[0]["platforms"].as_array().unwrap().len(), 2);
+    assert_eq!(grouped[1]["test_name"], "dom/media/test/test_seek.html");
+}
+
+#[test]
+fn test_compare() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .arg("--json")
+        .arg("compare")
+        .arg(BASE_REVISION)
+        .arg(COMPARE_REVISION)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(4), "{:?}", output);
+    let json = stdout_json(&output);
+
+    assert_eq!(json["base_push_id"], BASE_PUSH_ID);
+    assert_eq!(json["compare_push_id"], COMPARE_PUSH_ID);
+
+    let names = |key: &str| -> Vec<String> {
+        json[key]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["test_name"].as_str().unwrap().to_string())
+            .collect()
+    };
+    assert_eq!(
+        names("new_failures"),
+        vec!["dom/media/test/test_playback.html"]
+    );
+    assert_eq!(
+        names("fixed_failures"),
+        vec!["dom/media/test/test_old_failure.html"]
+    );
+    assert_eq!(
+        names("still_failing"),
+        vec!["dom/media/test/test_seek.html"]
+    );
+}
+
+#[test]
+fn test_compare_markdown() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .arg("compare")
+        .arg(BASE_REVISION)
+        .arg(COMPARE_REVISION)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(4), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Treeherder Comparison Results"));
+    assert!(stdout.contains("New Failures (1 tests)"));
+}
+
+#[test]
+fn test_perf() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .arg("--json")
+        .arg("perf")
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{:?}", output);
+    let json = stdout_json(&output);
+    let jobs = json["jobs"].as_array().unwrap();
+    assert_eq!(jobs.len(), 3);
+
+    let with_data: Vec<_> = jobs.iter().filter(|j| !j["perf_data"].is_null()).collect();
+    assert_eq!(with_data.len(), 1);
+    assert_eq!(with_data[0]["job_id"], 5001);
+    assert_eq!(
+        with_data[0]["perf_data"]["framework"]["name"],
+        "job_resource_usage"
+    );
+}
+
+#[test]
+fn test_logs_with_pattern() {
+    let server = fixture_server();
+    let cache_dir = tempfile::tempdir().unwrap();
+    let output = cli(&server)
+        .arg("--json")
+        .arg("logs")
+        .arg("--pattern")
+        .arg("ASSERTION")
+        .arg("--platform")
+        .arg("linux")
+        .arg("--cache-dir")
+        .arg(cache_dir.path())
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let json = stdout_json(&output);
+    let jobs = json["jobs"].as_array().unwrap();
+    assert_eq!(jobs.len(), 1);
+
+    let matches = jobs[0]["log_matches"].as_array().unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0]["log_name"], "live_backing_log");
+    assert_eq!(matches[0]["line_number"], 4);
+
+    assert!(cache_dir
+        .path()
+        .join("job_5001")
+        .join("live_backing_log.log")
+        .exists());
+    assert!(cache_dir.path().join("metadata.json").exists());
+}
+
+#[test]
+fn test_cache_after_logs() {
+    let server = fixture_server();
+    let cache_dir = tempfile::tempdir().unwrap();
+    let fetched = cli(&server)
+        .arg("--json")
+        .arg("logs")
+        .arg("--cache-dir")
+        .arg(cache_dir.path())
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+    assert_eq!(fetched.status.code(), Some(2), "{:?}", fetched);
+
+    let requests_before = server.requests().len();
+    let output = cli(&server)
+        .arg("cache")
+        .arg("--cache-dir")
+        .arg(cache_dir.path())
+        .arg("--pattern")
+        .arg("TEST-UNEXPECTED-FAIL")
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    assert_eq!(server.requests().len(), requests_before);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Jobs matching filter: 3"));
+    assert!(stdout.contains("Pattern Matches (1 matches)"));
+}
+
+#[test]
+fn test_artifacts() {
+    let server = fixture_server();
+    let out_dir = tempfile::tempdir().unwrap();
+    let output = cli(&server)
+        .arg("--json")
+        .arg("artifacts")
+        .arg("--artifact-pattern")
+        .arg("screenshot|errorsummary")
+        .arg("--output-dir")
+        .arg(out_dir.path())
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{:?}", output);
+    let json = stdout_json(&output);
+    assert_eq!(json["total_files"], 3);
+
+    let screenshot = out_dir
+        .path()
+        .join("job-5001")
+        .join("public/test_info/screenshot-1.png");
+    assert_eq!(
+        std::fs::read_to_string(screenshot).unwrap(),
+        "public/test_info/screenshot-1.png of Tm5001AAAAAAAAAAAAAAAA"
+    );
+    assert!(!out_dir
+        .path()
+        .join("job-5001")
+        .join("public/logs/live_backing.log")
+        .exists());
+}
+
+#[test]
+fn test_history() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .arg("--json")
+        .arg("history")
+        .arg("5001")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{:?}", output);
+    let json = stdout_json(&output);
+    assert_eq!(json["total_jobs"], 10);
+    assert_eq!(json["pass_count"], 8);
+    assert_eq!(json["fail_count"], 2);
+}
+
+#[test]
+fn test_lando_job_id() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .arg("--json")
+        .arg("--lando-job-id")
+        .arg(LANDO_JOB_ID.to_string())
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let json = stdout_json(&output);
+    assert_eq!(json["revision"], BASE_REVISION);
+}
+
+#[test]
+fn test_unknown_revision_fails() {
+    let server = fixture_server();
+    server.mock(
+        "/api/project/try/push/?revision=ffffffffffff",
+        MockResponse::json(r#"{"meta": {"count": 0}, "results": []}"#),
+    );
+    let output = cli(&server).arg("ffffffffffff").output().unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No push found for revision"));
+}
+
+#[test]
+fn test_summary_subcommand_matches_bare_invocation() {
+    let server = fixture_server();
+    let bare = cli(&server)
+        .arg("--json")
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+    let summary = cli(&server)
+        .arg("summary")
+        .arg("--json")
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+
+    assert_eq!(summary.status.code(), Some(2), "{:?}", summary);
+    assert_eq!(job_ids(&bare), job_ids(&summary));
+}
+
+#[test]
+fn test_summary_flags_rejected_before_subcommand() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .arg("--platform")
+        .arg("linux")
+        .arg("history")
+        .arg("5001")
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("'--platform' cannot be used before the 'history' subcommand"));
+}
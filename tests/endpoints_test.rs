@@ -0,0 +1,54 @@
+use assert_cmd::assert::OutputAssertExt;
+use predicates::prelude::*;
+use std::process::Command;
+
+#[test]
+fn test_endpoint_flags_exist() {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
+    cmd.arg("--help");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("--treeherder-url"))
+        .stdout(predicate::str::contains("--taskcluster-url"))
+        .stdout(predicate::str::contains("--lando-url"));
+}
+
+#[test]
+fn test_endpoint_env_vars_documented() {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
+    cmd.arg("--help");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("TREEHERDER_URL"))
+        .stdout(predicate::str::contains("TASKCLUSTER_ROOT_URL"))
+        .stdout(predicate::str::contains("LANDO_URL"));
+}
+
+#[test]
+fn test_treeherder_url_is_used_for_requests() {
+    // Nothing listens on port 9 of the loopback interface, so the request must
+    // fail there rather than reaching treeherder.mozilla.org.
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
+    cmd.env("MOZTOOLS_UPDATE_CHECK", "0")
+        .arg("--treeherder-url")
+        .arg("http://127.0.0.1:9/")
+        .arg("a13b9fc22101");
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("127.0.0.1:9"));
+}
+
+#[test]
+fn test_treeherder_url_from_env() {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
+    cmd.env("MOZTOOLS_UPDATE_CHECK", "0")
+        .env("TREEHERDER_URL", "http://127.0.0.1:9")
+        .arg("a13b9fc22101");
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("127.0.0.1:9"));
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use common::*;
+
+fn exit_code(server: &MockServer, args: &[&str]) -> Option<i32> {
+    cli(server).args(args).output().unwrap().status.code()
+}
+
+#[test]
+fn test_green_push_exits_zero() {
+    let server = fixture_server();
+    assert_eq!(
+        exit_code(&server, &["--json", BASE_REVISION, "--filter", "xpcshell"]),
+        Some(0)
+    );
+}
+
+#[test]
+fn test_tool_error_exits_one() {
+    let server = fixture_server();
+    assert_eq!(exit_code(&server, &["--json", "deadbeef"]), Some(1));
+}
+
+#[test]
+fn test_failures_exit_two() {
+    let server = fixture_server();
+    assert_eq!(exit_code(&server, &["--json", BASE_REVISION]), Some(2));
+}
+
+#[test]
+fn test_pending_jobs_exit_three() {
+    let server = fixture_server();
+    assert_eq!(
+        exit_code(
+            &server,
+            &["--json", BASE_REVISION, "--filter", "web-platform-tests"]
+        ),
+        Some(3)
+    );
+}
+
+#[test]
+fn test_compare_exit_codes() {
+    let server = fixture_server();
+    assert_eq!(
+        exit_code(
+            &server,
+            &["--json", "compare", BASE_REVISION, COMPARE_REVISION]
+        ),
+        Some(4)
+    );
+    assert_eq!(
+        exit_code(
+            &server,
+            &["--json", "compare", BASE_REVISION, BASE_REVISION]
+        ),
+        Some(0)
+    );
+}
+
+#[test]
+fn test_usage_error_exits_one() {
+    let server = fixture_server();
+    assert_eq!(
+        exit_code(&server, &["--no-such-flag", BASE_REVISION]),
+        Some(1)
+    );
+    assert_eq!(exit_code(&server, &["--help"]), Some(0));
+}
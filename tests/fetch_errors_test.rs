@@ -0,0 +1,116 @@
+mod common;
+
+use common::*;
+
+fn fail_job_details(server: &MockServer, job_id: u64) {
+    server.mock_first(
+        &format!("/api/project/try/jobs/{}/", job_id),
+        MockResponse::status(404),
+    );
+}
+
+#[test]
+fn test_complete_report_has_no_fetch_errors() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .arg("--json")
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let json = stdout_json(&output);
+    assert_eq!(json["fetch_errors"], serde_json::json!([]));
+}
+
+#[test]
+fn test_failed_job_listed_in_json() {
+    let server = fixture_server();
+    fail_job_details(&server, 5002);
+
+    let output = cli(&server)
+        .arg("--json")
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let json = stdout_json(&output);
+    assert_eq!(json["jobs"].as_array().unwrap().len(), 2);
+
+    let fetch_errors = json["fetch_errors"].as_array().unwrap();
+    assert_eq!(fetch_errors.len(), 1);
+    assert_eq!(fetch_errors[0]["job_id"], 5002);
+    assert_eq!(
+        fetch_errors[0]["job_type_name"],
+        "test-windows11-64-24h2/debug-mochitest-media-2"
+    );
+    assert!(fetch_errors[0].get("log_name").is_none());
+    assert!(fetch_errors[0]["error"].as_str().unwrap().contains("404"));
+}
+
+#[test]
+fn test_failed_log_listed_in_json() {
+    let server = fixture_server();
+    server.mock_first("/logs/5001/errorsummary.log", MockResponse::status(404));
+
+    let output = cli(&server)
+        .arg("--json")
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let json = stdout_json(&output);
+    // The job is still reported, without the errors from the missing log.
+    assert_eq!(json["jobs"].as_array().unwrap().len(), 3);
+
+    let fetch_errors = json["fetch_errors"].as_array().unwrap();
+    assert_eq!(fetch_errors.len(), 1);
+    assert_eq!(fetch_errors[0]["job_id"], 5001);
+    assert_eq!(fetch_errors[0]["log_name"], "errorsummary_json");
+}
+
+#[test]
+fn test_fetch_errors_section_in_markdown() {
+    let server = fixture_server();
+    fail_job_details(&server, 5002);
+
+    let output = cli(&server).arg(BASE_REVISION).output().unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Fetch Errors (1 could not be retrieved, report is incomplete)"));
+    assert!(stdout.contains("test-windows11-64-24h2/debug-mochitest-media-2"));
+}
+
+#[test]
+fn test_fail_on_incomplete() {
+    let server = fixture_server();
+    fail_job_details(&server, 5002);
+
+    let output = cli(&server)
+        .arg("--json")
+        .arg("--fail-on-incomplete")
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    // The partial report is still printed before failing.
+    assert_eq!(stdout_json(&output)["fetch_errors"][0]["job_id"], 5002);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Report is incomplete"), "{}", stderr);
+}
+
+#[test]
+fn test_fail_on_incomplete_with_complete_report() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .arg("--fail-on-incomplete")
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+}
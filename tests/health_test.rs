@@ -0,0 +1,86 @@
+mod common;
+
+use common::*;
+use serde_json::json;
+
+#[test]
+fn test_health_json() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .args(["--json", "health", BASE_REVISION])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let json = stdout_json(&output);
+    assert_eq!(json["revision"], BASE_REVISION);
+    assert_eq!(json["result"], "fail");
+
+    let tests = &json["metrics"]["tests"]["details"];
+    let need_investigation = tests["need_investigation"].as_array().unwrap();
+    assert_eq!(need_investigation.len(), 2);
+    assert_eq!(
+        need_investigation[0]["test_name"],
+        "dom/media/test/test_playback.html"
+    );
+    assert_eq!(
+        need_investigation[0]["suggested_classification"],
+        "New Failure"
+    );
+    assert_eq!(need_investigation[1]["total_jobs"], 3);
+    assert_eq!(tests["known_issues"][0]["failed_in_parent"], true);
+    assert_eq!(tests["infra"], json!([]));
+    assert_eq!(json["metrics"]["linting"]["details"][0]["result"], "busted");
+}
+
+#[test]
+fn test_health_markdown() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .args(["health", BASE_REVISION])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Treeherder Push Health"), "{}", stdout);
+    assert!(
+        stdout.contains("Need Investigation (2 tests)"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("Known Issues (1 tests)"), "{}", stdout);
+    assert!(stdout.contains("browser_tabs.js"), "{}", stdout);
+    assert!(stdout.contains("M(bc3)"), "{}", stdout);
+    assert!(stdout.contains("1/4"), "{}", stdout);
+    assert!(stdout.contains("Build and Lint Failures (1)"), "{}", stdout);
+    assert!(stdout.contains("source-test-mozlint-eslint"), "{}", stdout);
+}
+
+#[test]
+fn test_health_still_running() {
+    let server = fixture_server();
+    server.mock_first(
+        &format!("/api/project/try/push/health/?revision={}", BASE_REVISION),
+        MockResponse::json(
+            json!({
+                "revision": BASE_REVISION,
+                "result": "indeterminate",
+                "metrics": {
+                    "tests": {"name": "Tests", "result": "indeterminate",
+                              "details": {"needInvestigation": [], "knownIssues": []}},
+                },
+            })
+            .to_string(),
+        ),
+    );
+
+    let output = cli(&server)
+        .args(["health", BASE_REVISION])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(3), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Need Investigation: ✓ None!"), "{}", stdout);
+}
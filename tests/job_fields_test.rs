@@ -0,0 +1,103 @@
+mod common;
+
+use common::*;
+
+#[test]
+fn test_job_fields_in_json() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .args(["--json", "--match-filter", "all", "--include-intermittent"])
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+
+    let json = stdout_json(&output);
+    let jobs = json["jobs"].as_array().unwrap();
+    let job = |id: u64| {
+        &jobs
+            .iter()
+            .find(|j| j["job"]["id"] == id)
+            .unwrap_or_else(|| panic!("job {} missing", id))["job"]
+    };
+
+    assert_eq!(job(5004)["tier"], 2);
+    assert_eq!(job(5004)["job_group_name"], "Mochitests");
+    assert_eq!(job(5004)["job_group_symbol"], "M");
+    assert_eq!(job(5004)["task_id"], "Tm5004AAAAAAAAAAAAAAAA");
+    assert_eq!(job(5004)["retry_id"], 0);
+    assert_eq!(job(5004)["start_timestamp"], 1760000300);
+    assert_eq!(job(5004)["end_timestamp"], 1760003300);
+    assert_eq!(job(5004)["who"], "dev@mozilla.com");
+    // A running job has not ended yet.
+    assert!(job(5006)["end_timestamp"].is_null());
+}
+
+#[test]
+fn test_tier_filter() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .args(["--json", "--match-filter", "all", "--include-intermittent"])
+        .args(["--tier", "2"])
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+
+    assert_eq!(job_ids(&output), vec![5004, 5006]);
+}
+
+#[test]
+fn test_group_and_symbol_filters() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .args(["--json", "--group", "M", "--symbol", "mda*"])
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+    assert_eq!(job_ids(&output), vec![5001, 5002]);
+
+    let output = cli(&server)
+        .args(["--json", "--include-intermittent"])
+        .args(["--group", "mochitests", "--symbol", "bc?"])
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+    assert_eq!(job_ids(&output), vec![5004]);
+}
+
+#[test]
+fn test_tier_out_of_range() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .args([BASE_REVISION, "--tier", "4"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_where_on_job_fields() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .args(["--json", "--where", "group_symbol = M and not tier = 2"])
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+
+    assert_eq!(job_ids(&output), vec![5001, 5002]);
+}
+
+#[test]
+fn test_group_symbol_notation_in_markdown() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .args(["--include-intermittent", BASE_REVISION])
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("M(mda1)"), "{}", stdout);
+    assert!(stdout.contains("M(bc3)"), "{}", stdout);
+    assert!(stdout.contains("L(es)"), "{}", stdout);
+    assert!(stdout.contains("Tier: 2"), "{}", stdout);
+}
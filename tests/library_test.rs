@@ -0,0 +1,123 @@
+mod common;
+
+use common::*;
+use treeherder_cli::models::{FailureClassification, Job, JobResult, JobState};
+use treeherder_cli::{Endpoints, JobFilter, MatchFilter, PushQuery, PushStatus, TreeherderClient};
+
+fn client(server: &MockServer) -> TreeherderClient {
+    TreeherderClient::with_endpoints(
+        "try",
+        Endpoints::new(server.url(), server.url(), server.url()),
+    )
+}
+
+#[tokio::test]
+async fn test_report() {
+    let server = fixture_server();
+    let report = client(&server)
+        .report(&PushQuery::new(BASE_REVISION))
+        .await
+        .unwrap();
+
+    assert_eq!(report.revision, BASE_REVISION);
+    assert_eq!(report.push_id, BASE_PUSH_ID);
+
+    let mut ids: Vec<u64> = report.jobs.iter().map(|j| j.job.id).collect();
+    ids.sort();
+    assert_eq!(ids, vec![5001, 5002, 5005]);
+
+    let grouped = report.grouped_failures();
+    assert_eq!(grouped[0].test_name, "dom/media/test/test_playback.html");
+}
+
+#[tokio::test]
+async fn test_report_with_filter() {
+    let server = fixture_server();
+    let query = PushQuery::new(BASE_REVISION).with_filter(JobFilter {
+        match_filter: MatchFilter::All,
+        platform: Some("linux".to_string()),
+        duration_min: Some(1000),
+        ..JobFilter::default()
+    });
+    let report = client(&server).report(&query).await.unwrap();
+
+    assert_eq!(report.jobs.len(), 1);
+    assert_eq!(report.jobs[0].job.id, 5001);
+}
+
+#[tokio::test]
+async fn test_report_from_lando_job() {
+    let server = fixture_server();
+    let report = client(&server)
+        .report(&PushQuery::lando_job(LANDO_JOB_ID))
+        .await
+        .unwrap();
+
+    assert_eq!(report.revision, BASE_REVISION);
+}
+
+#[tokio::test]
+async fn test_compare() {
+    let server = fixture_server();
+    let result = client(&server)
+        .compare(
+            &PushQuery::new(BASE_REVISION),
+            &PushQuery::new(COMPARE_REVISION),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result.base_push_id, BASE_PUSH_ID);
+    assert_eq!(result.compare_push_id, COMPARE_PUSH_ID);
+    assert_eq!(result.new_failures.len(), 1);
+    assert_eq!(
+        result.new_failures[0].test_name,
+        "dom/media/test/test_playback.html"
+    );
+}
+
+#[tokio::test]
+async fn test_report_status() {
+    let server = fixture_server();
+    let client = client(&server);
+
+    let report = client.report(&PushQuery::new(BASE_REVISION)).await.unwrap();
+    assert_eq!(report.status(), PushStatus::Failed);
+
+    let query = PushQuery::new(BASE_REVISION).with_filter(JobFilter {
+        name: Some("web-platform-tests".to_string()),
+        ..JobFilter::default()
+    });
+    let report = client.report(&query).await.unwrap();
+    assert_eq!(report.pending_jobs, 1);
+    assert_eq!(report.status(), PushStatus::Pending);
+}
+
+#[test]
+fn test_job_enums_round_trip() {
+    let job: Job = serde_json::from_value(serde_json::json!({
+        "id": 1,
+        "job_type_name": "test-linux/opt-xpcshell",
+        "job_type_symbol": "X",
+        "platform": "linux",
+        "platform_option": "opt",
+        "result": "usercancel",
+        "state": "completed",
+        "failure_classification_id": 4,
+    }))
+    .unwrap();
+    assert_eq!(job.result, JobResult::UserCancel);
+    assert_eq!(job.state, JobState::Completed);
+    assert_eq!(
+        job.failure_classification,
+        Some(FailureClassification::Intermittent)
+    );
+
+    let unknown: JobResult = serde_json::from_value(serde_json::json!("brand-new")).unwrap();
+    assert_eq!(unknown, JobResult::Unknown("brand-new".to_string()));
+    assert_eq!(serde_json::to_value(&unknown).unwrap(), "brand-new");
+
+    let value = serde_json::to_value(&job).unwrap();
+    assert_eq!(value["result"], "usercancel");
+    assert_eq!(value["failure_classification_id"], 4);
+}
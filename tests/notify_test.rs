@@ -0,0 +1,250 @@
+mod common;
+
+use common::*;
+
+/// Watches the base push, whose failed jobs are all known on the first poll.
+fn watch(server: &MockServer, notify: &str) -> std::process::Command {
+    let mut cmd = cli(server);
+    cmd.args(["--json", "watch", BASE_REVISION, "--interval", "0"])
+        .args(["--until", "first-failure"])
+        .arg(format!("--notify={}", notify));
+    cmd
+}
+
+#[test]
+fn test_notify_webhook() {
+    let server = fixture_server();
+    server.mock_method("POST", "/hook", MockResponse::text("ok"));
+
+    let output = watch(&server, &format!("webhook:{}/hook", server.url()))
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let bodies: Vec<serde_json::Value> = server
+        .requests()
+        .into_iter()
+        .filter(|r| r.method == "POST" && r.path == "/hook")
+        .map(|r| serde_json::from_slice(&r.body).unwrap())
+        .collect();
+    // The failures are all found in the first poll, so one notification
+    // lists them before the one as the push completes.
+    assert_eq!(bodies.len(), 2);
+
+    let failing = &bodies[0];
+    assert_eq!(failing["event"], "job_failed");
+    assert_eq!(failing["title"], "Treeherder Jobs Failed");
+    assert_eq!(failing["job"]["id"], 5001);
+    assert_eq!(
+        failing["message"],
+        "3 jobs failed on a13b9fc22101, including M(mda1) \
+         test-linux1804-64-qr/opt-mochitest-media-1"
+    );
+    let ids: Vec<u64> = failing["failures"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["job_id"].as_u64().unwrap())
+        .collect();
+    assert_eq!(ids, vec![5001, 5002, 5005]);
+
+    let complete = &bodies[1];
+    assert_eq!(complete["event"], "push_complete");
+    assert_eq!(complete["title"], "Treeherder Jobs Complete");
+    assert_eq!(complete["message"], "3 jobs of a13b9fc22101 failed");
+    assert_eq!(complete["revision"], BASE_REVISION);
+    assert_eq!(complete["failed"], 3);
+    let failures = complete["failures"].as_array().unwrap();
+    let ids: Vec<u64> = failures
+        .iter()
+        .map(|f| f["job_id"].as_u64().unwrap())
+        .collect();
+    assert_eq!(ids, vec![5001, 5002, 5005]);
+    assert_eq!(failures[0]["result"], "testfailed");
+    assert_eq!(failures[0]["tests"][0], "dom/media/test/test_playback.html");
+}
+
+#[test]
+fn test_notify_each_poll_with_new_failures() {
+    let server = running_push_server();
+    server.mock_method("POST", "/hook", MockResponse::text("ok"));
+
+    let output = cli(&server)
+        .args(["--json", "watch", BASE_REVISION, "--interval", "0"])
+        .arg(format!("--notify=webhook:{}/hook", server.url()))
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let bodies: Vec<serde_json::Value> = server
+        .requests()
+        .into_iter()
+        .filter(|r| r.method == "POST" && r.path == "/hook")
+        .map(|r| serde_json::from_slice(&r.body).unwrap())
+        .collect();
+    let failed: Vec<Vec<u64>> = bodies
+        .iter()
+        .filter(|b| b["event"] == "job_failed")
+        .map(|b| {
+            b["failures"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|f| f["job_id"].as_u64().unwrap())
+                .collect()
+        })
+        .collect();
+    assert_eq!(failed, vec![vec![5005], vec![5001], vec![5002]]);
+    assert_eq!(bodies.last().unwrap()["event"], "push_complete");
+}
+
+#[test]
+fn test_notify_webhook_failure_does_not_stop_watch() {
+    let server = fixture_server();
+    server.mock_method("POST", "/hook", MockResponse::status(500));
+
+    let output = watch(&server, &format!("webhook:{}/hook", server.url()))
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Failed to send notification"), "{}", stderr);
+    assert!(stderr.contains("500"), "{}", stderr);
+}
+
+#[test]
+fn test_notify_matrix() {
+    let server = fixture_server();
+    let output = watch(&server, "matrix:!room:example.org")
+        .env("MATRIX_HOMESERVER", server.url())
+        .env("MATRIX_ACCESS_TOKEN", "matrix-token")
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let puts: Vec<RecordedRequest> = server
+        .requests()
+        .into_iter()
+        .filter(|r| r.method == "PUT")
+        .collect();
+    assert_eq!(puts.len(), 2);
+    assert!(puts.iter().all(|p| p
+        .path
+        .starts_with("/_matrix/client/v3/rooms/%21room%3Aexample.org/send/m.room.message/")));
+    assert_eq!(puts[1].header("Authorization"), Some("Bearer matrix-token"));
+    let body: serde_json::Value = serde_json::from_slice(&puts[1].body).unwrap();
+    assert_eq!(body["msgtype"], "m.text");
+    assert_eq!(
+        body["body"],
+        "Treeherder Jobs Complete: 3 jobs of a13b9fc22101 failed"
+    );
+}
+
+#[test]
+fn test_notify_matrix_needs_token() {
+    let server = fixture_server();
+    let output = watch(&server, "matrix:!room:example.org").output().unwrap();
+
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("MATRIX_ACCESS_TOKEN"), "{}", stderr);
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_notify_command() {
+    let server = fixture_server();
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("notifications");
+    let command = format!(
+        "command:printf '%s\\n' \"$TREEHERDER_NOTIFY_TITLE\" >> '{0}'; cat >> '{0}'; echo >> '{0}'",
+        out.display()
+    );
+
+    let output = watch(&server, &command).output().unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let written = std::fs::read_to_string(&out).unwrap();
+    let lines: Vec<&str> = written.lines().collect();
+    assert_eq!(lines.len(), 4, "{}", written);
+    assert_eq!(lines[0], "Treeherder Jobs Failed");
+    assert_eq!(lines[2], "Treeherder Jobs Complete");
+    let complete: serde_json::Value = serde_json::from_str(lines[3]).unwrap();
+    assert_eq!(complete["event"], "push_complete");
+    assert_eq!(complete["message"], "3 jobs of a13b9fc22101 failed");
+
+    // Only the NDJSON events are written to stdout.
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout
+        .lines()
+        .all(|line| serde_json::from_str::<serde_json::Value>(line).is_ok()));
+}
+
+#[test]
+fn test_notify_terminal_escape_sequences() {
+    let server = fixture_server();
+    let output = watch(&server, "osc9").output().unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("\x1b]9;Treeherder Jobs Complete: 3 jobs of a13b9fc22101 failed\x07"),
+        "{:?}",
+        stderr
+    );
+
+    let output = watch(&server, "osc777").output().unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr
+            .contains("\x1b]777;notify;Treeherder Jobs Complete;3 jobs of a13b9fc22101 failed\x07"),
+        "{:?}",
+        stderr
+    );
+
+    let output = watch(&server, "bell").output().unwrap();
+    assert_eq!(output.stderr.iter().filter(|b| **b == 0x07).count(), 2);
+}
+
+#[test]
+fn test_notify_rejects_unknown_target() {
+    let server = fixture_server();
+    let output = watch(&server, "pager").output().unwrap();
+
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Unknown notification target 'pager'"),
+        "{}",
+        stderr
+    );
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_notify_each_push_as_it_completes() {
+    let server = fixture_server();
+    server.mock_method("POST", "/hook", MockResponse::text("ok"));
+
+    let output = watch(&server, &format!("webhook:{}/hook", server.url()))
+        .arg(COMPARE_REVISION)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let mut messages: Vec<String> = server
+        .requests()
+        .into_iter()
+        .filter(|r| r.method == "POST" && r.path == "/hook")
+        .map(|r| serde_json::from_slice::<serde_json::Value>(&r.body).unwrap())
+        .filter(|b| b["event"] == "push_complete")
+        .map(|b| b["message"].as_str().unwrap().to_string())
+        .collect();
+    messages.sort();
+    assert_eq!(
+        messages,
+        vec![
+            "2 jobs of b2c3d4e5f678 failed",
+            "3 jobs of a13b9fc22101 failed"
+        ]
+    );
+}
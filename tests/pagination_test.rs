@@ -0,0 +1,169 @@
+mod common;
+
+use common::*;
+use serde_json::json;
+use std::sync::Mutex;
+use treeherder_cli::api::{fetch_pushes, PUSH_PAGE_SIZE};
+use treeherder_cli::{Endpoints, HttpClient, TreeherderClient};
+
+fn client(server: &MockServer) -> TreeherderClient {
+    TreeherderClient::with_endpoints(
+        "try",
+        Endpoints::new(server.url(), server.url(), server.url()),
+    )
+}
+
+fn jobs_page(ids: &[u64], next: Option<String>) -> MockResponse {
+    let results: Vec<_> = ids
+        .iter()
+        .map(|id| {
+            json!([
+                id,
+                format!("test-linux/opt-{}", id),
+                "t",
+                "linux",
+                "success",
+                "completed"
+            ])
+        })
+        .collect();
+    MockResponse::json(
+        json!({
+            "results": results,
+            "job_property_names": ["id", "job_type_name", "job_type_symbol", "platform", "result", "state"],
+            "next": next,
+        })
+        .to_string(),
+    )
+}
+
+#[tokio::test]
+async fn test_jobs_follow_next_links() {
+    let server = MockServer::start();
+    server.mock(
+        "/api/jobs/?push_id=7",
+        jobs_page(
+            &[1, 2],
+            Some(format!("{}/api/jobs/?push_id=7&page=2", server.url())),
+        ),
+    );
+    server.mock("/api/jobs/?push_id=7&page=2", jobs_page(&[3], None));
+
+    let progress = Mutex::new(Vec::new());
+    let jobs = client(&server)
+        .jobs(7, |count| progress.lock().unwrap().push(count))
+        .await
+        .unwrap();
+
+    let ids: Vec<u64> = jobs.iter().map(|j| j.id).collect();
+    assert_eq!(ids, vec![1, 2, 3]);
+    assert_eq!(*progress.lock().unwrap(), vec![2, 3]);
+    assert_eq!(server.hits("/api/jobs/"), 2);
+}
+
+#[tokio::test]
+async fn test_jobs_stop_at_max_jobs() {
+    let server = MockServer::start();
+    server.mock(
+        "/api/jobs/?push_id=7",
+        jobs_page(
+            &[1, 2],
+            Some(format!("{}/api/jobs/?push_id=7&page=2", server.url())),
+        ),
+    );
+    server.mock("/api/jobs/?push_id=7&page=2", jobs_page(&[3, 4], None));
+
+    let jobs = client(&server)
+        .with_max_jobs(2)
+        .jobs(7, |_| {})
+        .await
+        .unwrap();
+
+    assert_eq!(jobs.len(), 2);
+    assert_eq!(server.hits("/api/jobs/"), 1);
+    assert_eq!(server.requests()[0].query_param("count"), Some("2"));
+}
+
+#[test]
+fn test_max_jobs_warns_when_truncated() {
+    let server = fixture_server();
+
+    let output = cli(&server)
+        .args(["--json", "--max-jobs", "2", "--match-filter", "all"])
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    assert_eq!(stdout_json(&output)["jobs"].as_array().unwrap().len(), 2);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("stopped after 2 jobs of push 1001"),
+        "{}",
+        stderr
+    );
+}
+
+#[tokio::test]
+async fn test_pushes_page_by_id() {
+    let server = MockServer::start();
+    let first_page: Vec<_> = (0..PUSH_PAGE_SIZE as u64)
+        .map(|i| json!({"id": 5000 - i, "revision": format!("rev{}", i)}))
+        .collect();
+    let last_id = 5000 - PUSH_PAGE_SIZE as u64 + 1;
+    server.mock(
+        "/api/project/try/push/?author=dev",
+        MockResponse::json(json!({ "results": first_page }).to_string()),
+    );
+    server.mock(
+        &format!("/api/project/try/push/?author=dev&id__lt={}", last_id),
+        MockResponse::json(json!({"results": [{"id": 10, "revision": "last"}]}).to_string()),
+    );
+
+    let pushes = fetch_pushes(
+        &HttpClient::default(),
+        &Endpoints::new(server.url(), server.url(), server.url()),
+        "try",
+        "author=dev",
+        PUSH_PAGE_SIZE + 50,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(pushes.len(), PUSH_PAGE_SIZE + 1);
+    assert_eq!(pushes.last().unwrap().id, 10);
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].query_param("count"), Some("50"));
+}
+
+#[test]
+fn test_similar_jobs_page_by_offset() {
+    let server = fixture_server();
+    let fixture: serde_json::Value =
+        serde_json::from_str(&server.fixture("similar_jobs_5001.json")).unwrap();
+    let job = fixture["results"][0].clone();
+    let first_page = json!({
+        "results": vec![job; 50],
+        "meta": {"count": 50, "repository": "try", "offset": 0},
+    });
+    server.mock(
+        "/api/project/try/jobs/5001/similar_jobs/?offset=0",
+        MockResponse::json(first_page.to_string()),
+    );
+
+    let output = cli(&server)
+        .args(["--json", "history", "5001", "--count", "100"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(stdout_json(&output)["total_jobs"], 60);
+    let offsets: Vec<String> = server
+        .requests()
+        .iter()
+        .filter(|r| r.path == "/api/project/try/jobs/5001/similar_jobs/")
+        .filter_map(|r| r.query_param("offset").map(str::to_string))
+        .collect();
+    assert_eq!(offsets, vec!["0", "50"]);
+}
@@ -3,13 +3,13 @@ use predicates::prelude::*;
 use std::process::Command;
 
 #[test]
-fn test_perf_flag_exists() {
+fn test_perf_subcommand_exists() {
     let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
-    cmd.arg("--help");
+    cmd.arg("perf").arg("--help");
 
     cmd.assert()
         .success()
-        .stdout(predicate::str::contains("--perf"));
+        .stdout(predicate::str::contains("Usage: treeherder-cli perf"));
 }
 
 #[test]
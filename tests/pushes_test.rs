@@ -0,0 +1,212 @@
+mod common;
+
+use common::*;
+use serde_json::json;
+
+const AUTHOR_PUSHES: &str = "/api/project/try/push/?author=dev@mozilla.com";
+
+/// Both fixture pushes, newest first, as listed for their author.
+fn author_server() -> MockServer {
+    let server = fixture_server();
+    server.mock(
+        AUTHOR_PUSHES,
+        MockResponse::json(
+            json!({"results": [
+                {"id": COMPARE_PUSH_ID, "revision": COMPARE_REVISION,
+                 "author": "dev@mozilla.com", "push_timestamp": 1760100000},
+                {"id": BASE_PUSH_ID, "revision": BASE_REVISION,
+                 "author": "dev@mozilla.com", "push_timestamp": 1760000000},
+            ]})
+            .to_string(),
+        ),
+    );
+    server
+}
+
+#[test]
+fn test_author_rollup_json() {
+    let server = author_server();
+    let output = cli(&server)
+        .args(["--json", "pushes", "--author", "dev@mozilla.com"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let json = stdout_json(&output);
+    assert_eq!(json["repo"], "try");
+    let pushes = json["pushes"].as_array().unwrap();
+    assert_eq!(pushes.len(), 2);
+
+    let newest = &pushes[0];
+    assert_eq!(newest["push_id"], COMPARE_PUSH_ID);
+    assert_eq!(newest["author"], "dev@mozilla.com");
+    assert_eq!(newest["status"], "failed");
+    assert_eq!(newest["color"], "orange");
+    assert_eq!(newest["failed_jobs"], 2);
+    let new_tests: Vec<&str> = newest["new_failures"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["test_name"].as_str().unwrap())
+        .collect();
+    assert!(
+        new_tests.contains(&"dom/media/test/test_old_failure.html"),
+        "{:?}",
+        new_tests
+    );
+
+    let oldest = &pushes[1];
+    assert_eq!(oldest["push_id"], BASE_PUSH_ID);
+    assert_eq!(oldest["color"], "red");
+    assert_eq!(oldest["failed_jobs"], 3);
+    assert_eq!(oldest["failing_tests"], 2);
+    assert!(oldest["new_failures"].is_null());
+
+    let push_request = server
+        .requests()
+        .into_iter()
+        .find(|r| r.path == "/api/project/try/push/")
+        .unwrap();
+    assert_eq!(push_request.query_param("count"), Some("10"));
+}
+
+#[test]
+fn test_author_rollup_markdown() {
+    let server = author_server();
+    let output = cli(&server)
+        .args(["pushes", "--author", "dev@mozilla.com", "--last", "2"])
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Treeherder Push Overview"), "{}", stdout);
+    assert!(stdout.contains("orange"), "{}", stdout);
+    assert!(stdout.contains("red"), "{}", stdout);
+    assert!(
+        stdout.contains(&format!("New failures in {}", COMPARE_REVISION)),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn test_date_and_revision_range() {
+    let server = fixture_server();
+    server.mock(
+        "/api/project/try/push/?startdate=2025-10-01",
+        MockResponse::json(json!({"results": []}).to_string()),
+    );
+
+    let output = cli(&server)
+        .args([
+            "pushes",
+            "--from-date",
+            "2025-10-01",
+            "--to-date",
+            "2025-10-07",
+        ])
+        .args(["--fromchange", &BASE_REVISION[..12]])
+        .args(["--tochange", &COMPARE_REVISION.to_uppercase()])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("No pushes found"), "{}", stdout);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    let request = &requests[0];
+    assert_eq!(request.query_param("enddate"), Some("2025-10-07"));
+    assert_eq!(
+        request.query_param("fromchange"),
+        Some(&BASE_REVISION[..12])
+    );
+    assert_eq!(request.query_param("tochange"), Some(COMPARE_REVISION));
+    assert_eq!(request.query_param("count"), Some("100"));
+}
+
+#[test]
+fn test_invalid_date() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .args(["pushes", "--from-date", "last week"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("YYYY-MM-DD"), "{}", stderr);
+}
+
+#[test]
+fn test_invalid_revision_range() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .args(["pushes", "--fromchange", "abc123"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("'abc123' is not a revision"), "{}", stderr);
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_rollup_keeps_pushes_that_could_not_be_fetched() {
+    let server = author_server();
+    server.mock_first(
+        &format!("/api/jobs/?push_id={}", BASE_PUSH_ID),
+        MockResponse::status(404),
+    );
+
+    let output = cli(&server)
+        .args(["--json", "pushes", "--author", "dev@mozilla.com"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let json = stdout_json(&output);
+    let pushes = json["pushes"].as_array().unwrap();
+    assert_eq!(pushes.len(), 2);
+
+    let newest = &pushes[0];
+    assert_eq!(newest["status"], "failed");
+    assert_eq!(newest["failed_jobs"], 2);
+    assert!(newest["new_failures"].is_null());
+    assert!(newest.get("error").is_none());
+
+    let oldest = &pushes[1];
+    assert_eq!(oldest["push_id"], BASE_PUSH_ID);
+    assert!(oldest["status"].is_null());
+    assert!(
+        oldest["error"].as_str().unwrap().contains("404"),
+        "{}",
+        oldest
+    );
+
+    server.mock_first(
+        &format!("/api/jobs/?push_id={}", BASE_PUSH_ID),
+        MockResponse::status(404),
+    );
+    let output = cli(&server)
+        .args([
+            "--fail-on-incomplete",
+            "pushes",
+            "--author",
+            "dev@mozilla.com",
+        ])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains(&format!("Could not fetch the jobs of {}", BASE_REVISION)),
+        "{}",
+        stdout
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Report is incomplete"), "{}", stderr);
+}
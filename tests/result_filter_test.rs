@@ -0,0 +1,145 @@
+mod common;
+
+use common::*;
+use serde_json::json;
+use treeherder_cli::models::{Job, JobResult, JobWithLogs};
+use treeherder_cli::output::format_markdown_summary;
+use treeherder_cli::{JobFilter, MatchFilter};
+
+fn job(id: u64, name: &str, result: &str) -> Job {
+    serde_json::from_value(json!({
+        "id": id,
+        "job_type_name": name,
+        "job_type_symbol": "X",
+        "platform": "linux",
+        "platform_option": "opt",
+        "result": result,
+        "state": "completed",
+        "failure_classification_id": 1,
+    }))
+    .unwrap()
+}
+
+fn push_jobs() -> Vec<Job> {
+    vec![
+        job(12, "test-xpcshell-1", "success"),
+        job(10, "test-xpcshell-1", "retry"),
+        job(13, "test-mochitest-1", "testfailed"),
+        job(11, "test-xpcshell-1", "retry"),
+        job(14, "source-test-lint", "exception"),
+        job(15, "test-reftest-1", "usercancel"),
+        job(16, "test-crashtest-1", "success"),
+    ]
+}
+
+fn ids(jobs: &[Job]) -> Vec<u64> {
+    jobs.iter().map(|j| j.id).collect()
+}
+
+#[test]
+fn test_failure_preset() {
+    let jobs = JobFilter::default().apply(push_jobs()).unwrap();
+    assert_eq!(ids(&jobs), vec![12, 13, 14, 15]);
+    assert_eq!(ids(&jobs[0].retries), vec![10, 11]);
+
+    // Only the jobs that failed outright.
+    let filter = JobFilter {
+        results: vec![
+            JobResult::TestFailed,
+            JobResult::Busted,
+            JobResult::Exception,
+        ],
+        ..JobFilter::default()
+    };
+    let jobs = filter.apply(push_jobs()).unwrap();
+    assert_eq!(ids(&jobs), vec![13, 14]);
+}
+
+#[test]
+fn test_retries_chained_under_final_attempt() {
+    let filter = JobFilter {
+        match_filter: MatchFilter::All,
+        ..JobFilter::default()
+    };
+    let jobs = filter.apply(push_jobs()).unwrap();
+
+    assert_eq!(ids(&jobs), vec![12, 13, 14, 15, 16]);
+    assert_eq!(ids(&jobs[0].retries), vec![10, 11]);
+    assert!(jobs[1].retries.is_empty());
+}
+
+#[test]
+fn test_result_filter() {
+    let filter = JobFilter {
+        results: vec![JobResult::Exception, JobResult::Success],
+        ..JobFilter::default()
+    };
+    let jobs = filter.apply(push_jobs()).unwrap();
+    assert_eq!(ids(&jobs), vec![12, 14, 16]);
+
+    let filter = JobFilter {
+        results: vec![JobResult::Retry],
+        ..JobFilter::default()
+    };
+    let jobs = filter.apply(push_jobs()).unwrap();
+    assert_eq!(ids(&jobs), vec![12]);
+}
+
+#[test]
+fn test_markdown_shows_earlier_attempts() {
+    let filter = JobFilter {
+        results: vec![JobResult::Retry],
+        ..JobFilter::default()
+    };
+    let jobs: Vec<JobWithLogs> = filter
+        .apply(push_jobs())
+        .unwrap()
+        .into_iter()
+        .map(|job| JobWithLogs {
+            job,
+            errors: vec![],
+            log_matches: vec![],
+            log_dir: None,
+            classification_notes: vec![],
+            classified_bugs: vec![],
+        })
+        .collect();
+
+    colored::control::set_override(false);
+    let markdown = format_markdown_summary("abc", 1, &jobs, &[], false, false);
+    assert!(
+        markdown.contains("Earlier attempts: 10 (retry) → 11 (retry)"),
+        "{}",
+        markdown
+    );
+}
+
+#[test]
+fn test_result_flag() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .args(["--json", "--result", "success,busted", BASE_REVISION])
+        .output()
+        .unwrap();
+
+    let mut ids: Vec<u64> = stdout_json(&output)["jobs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|j| j["job"]["id"].as_u64().unwrap())
+        .collect();
+    ids.sort();
+    assert_eq!(ids, vec![5003, 5005]);
+}
+
+#[test]
+fn test_result_flag_rejects_unknown_values() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .args(["--result", "flaky", BASE_REVISION])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("invalid value 'flaky'"));
+}
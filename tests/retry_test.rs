@@ -0,0 +1,153 @@
+mod common;
+
+use common::*;
+use std::time::{Duration, Instant};
+use treeherder_cli::{HttpClient, RetryPolicy};
+
+fn push_path() -> String {
+    format!("/api/project/try/push/?revision={}", BASE_REVISION)
+}
+
+#[test]
+fn test_retries_server_error() {
+    let server = fixture_server();
+    server.mock_first(&push_path(), MockResponse::status(503));
+
+    let output = cli(&server)
+        .arg("--json")
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    assert_eq!(server.hits("/api/project/try/push/"), 2);
+    assert_eq!(stdout_json(&output)["push_id"], BASE_PUSH_ID);
+}
+
+#[test]
+fn test_honours_retry_after() {
+    let server = fixture_server();
+    server.mock_first(
+        &format!("/api/jobs/?push_id={}", BASE_PUSH_ID),
+        MockResponse::status(429).header("Retry-After", "1"),
+    );
+
+    let started = Instant::now();
+    let output = cli(&server)
+        .arg("--json")
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    assert!(started.elapsed() >= Duration::from_secs(1));
+    assert_eq!(server.hits("/api/jobs/"), 2);
+}
+
+/// A client that waits at most a second between attempts and sends one
+/// request to a host at a time.
+fn capped_client() -> HttpClient {
+    let policy = RetryPolicy {
+        max_delay: Duration::from_secs(1),
+        ..RetryPolicy::default()
+    };
+    HttpClient::new(policy, 1)
+}
+
+#[tokio::test]
+async fn test_long_retry_after_is_capped() {
+    let server = fixture_server();
+    let path = format!("/api/jobs/?push_id={}", BASE_PUSH_ID);
+    server.mock_first(
+        &path,
+        MockResponse::status(429).header("Retry-After", "3600"),
+    );
+
+    let started = Instant::now();
+    capped_client()
+        .get_bytes(&format!("{}{}", server.url(), path))
+        .await
+        .unwrap();
+
+    assert!(started.elapsed() < Duration::from_secs(10));
+    assert_eq!(server.hits("/api/jobs/"), 2);
+}
+
+#[tokio::test]
+async fn test_retry_wait_does_not_block_host() {
+    let server = fixture_server();
+    let path = format!("/api/jobs/?push_id={}", BASE_PUSH_ID);
+    server.mock_first(
+        &path,
+        MockResponse::status(429).header("Retry-After", "3600"),
+    );
+    let client = capped_client();
+    let url = format!("{}{}", server.url(), path);
+
+    let started = Instant::now();
+    let waiting = client.get_bytes(&url);
+    let other = async {
+        // Let the first request get its 429 before this one is sent.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        client
+            .get_bytes(&format!("{}{}", server.url(), push_path()))
+            .await
+            .unwrap();
+        started.elapsed()
+    };
+    let (waited, other_done) = tokio::join!(waiting, other);
+
+    waited.unwrap();
+    assert!(other_done < Duration::from_millis(900), "{:?}", other_done);
+}
+
+#[test]
+fn test_gives_up_after_max_retries() {
+    let server = fixture_server();
+    server.mock_first(&push_path(), MockResponse::status(503));
+    server.mock_first(&push_path(), MockResponse::status(503));
+
+    let output = cli(&server)
+        .arg("--max-retries")
+        .arg("1")
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert_eq!(server.hits("/api/project/try/push/"), 2);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("failed after 2 attempts"), "{}", stderr);
+}
+
+#[test]
+fn test_client_error_is_not_retried() {
+    let server = fixture_server();
+    server.mock_first(&push_path(), MockResponse::status(404));
+
+    let output = cli(&server).arg(BASE_REVISION).output().unwrap();
+
+    assert!(!output.status.success());
+    assert_eq!(server.hits("/api/project/try/push/"), 1);
+}
+
+#[test]
+fn test_failed_job_is_reported() {
+    let server = fixture_server();
+    for _ in 0..2 {
+        server.mock_first("/api/project/try/jobs/5002/", MockResponse::status(500));
+    }
+
+    let output = cli(&server)
+        .arg("--json")
+        .arg("--max-retries")
+        .arg("1")
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let json = stdout_json(&output);
+    assert_eq!(json["jobs"].as_array().unwrap().len(), 2);
+    assert_eq!(json["fetch_errors"][0]["job_id"], 5002);
+}
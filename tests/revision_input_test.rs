@@ -0,0 +1,172 @@
+mod common;
+
+use common::*;
+use treeherder_cli::api::{parse_revision_input, RevisionInput, RevisionTarget};
+
+fn revision(revision: &str, repo: Option<&str>) -> RevisionInput {
+    RevisionInput {
+        target: RevisionTarget::Revision(revision.to_string()),
+        repo: repo.map(str::to_string),
+    }
+}
+
+#[test]
+fn test_bare_hashes() {
+    assert_eq!(
+        parse_revision_input(BASE_REVISION).unwrap(),
+        revision(BASE_REVISION, None)
+    );
+    assert_eq!(
+        parse_revision_input("A13B9FC22101").unwrap(),
+        revision("a13b9fc22101", None)
+    );
+
+    for invalid in [
+        "a13b9fc",
+        "not-a-revision-hash",
+        &format!("{}0", BASE_REVISION),
+    ] {
+        let error = parse_revision_input(invalid).unwrap_err().to_string();
+        assert!(error.contains("is not a revision"), "{}", error);
+    }
+}
+
+#[test]
+fn test_treeherder_urls() {
+    for url in [
+        format!(
+            "https://treeherder.mozilla.org/jobs?repo=autoland&revision={}",
+            BASE_REVISION
+        ),
+        format!(
+            "https://treeherder.mozilla.org/#/jobs?repo=autoland&revision={}",
+            BASE_REVISION
+        ),
+        format!(
+            "https://treeherder.mozilla.org/push-health/push?repo=autoland&revision={}",
+            BASE_REVISION
+        ),
+    ] {
+        assert_eq!(
+            parse_revision_input(&url).unwrap(),
+            revision(BASE_REVISION, Some("autoland")),
+            "{}",
+            url
+        );
+    }
+
+    assert_eq!(
+        parse_revision_input("https://treeherder.mozilla.org/jobs?repo=try&landoCommitID=12345")
+            .unwrap(),
+        RevisionInput {
+            target: RevisionTarget::LandoJob(12345),
+            repo: Some("try".to_string()),
+        }
+    );
+
+    let error = parse_revision_input("https://treeherder.mozilla.org/jobs?repo=try")
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("No revision found in URL"), "{}", error);
+}
+
+#[test]
+fn test_hg_urls() {
+    assert_eq!(
+        parse_revision_input(&format!(
+            "https://hg.mozilla.org/integration/autoland/rev/{}",
+            BASE_REVISION
+        ))
+        .unwrap(),
+        revision(BASE_REVISION, Some("autoland"))
+    );
+    assert_eq!(
+        parse_revision_input("https://hg.mozilla.org/try/pushloghtml?changeset=a13b9fc22101")
+            .unwrap(),
+        revision("a13b9fc22101", Some("try"))
+    );
+    assert_eq!(
+        parse_revision_input(&format!(
+            "https://hg.mozilla.org/mozilla-central/pushloghtml?fromchange=b2c3d4e5f678&tochange={}",
+            BASE_REVISION
+        ))
+        .unwrap(),
+        revision(BASE_REVISION, Some("mozilla-central"))
+    );
+}
+
+#[test]
+fn test_short_hash_expanded() {
+    let server = fixture_server();
+    server.mock_fixture(
+        "/api/project/try/push/?revision=a13b9fc22101",
+        "push_base.json",
+    );
+
+    let output = cli(&server)
+        .args(["--json", "a13b9fc22101"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let json = stdout_json(&output);
+    assert_eq!(json["revision"], BASE_REVISION);
+    assert_eq!(json["push_id"], BASE_PUSH_ID);
+}
+
+#[test]
+fn test_repo_taken_from_url() {
+    let server = fixture_server();
+    server.mock_fixture(
+        &format!("/api/project/autoland/push/?revision={}", BASE_REVISION),
+        "push_base.json",
+    );
+
+    let url = format!(
+        "https://treeherder.mozilla.org/jobs?repo=autoland&revision={}",
+        BASE_REVISION
+    );
+    let output = cli(&server).args(["--json", &url]).output().unwrap();
+
+    assert_eq!(stdout_json(&output)["push_id"], BASE_PUSH_ID);
+    assert!(server
+        .requests()
+        .iter()
+        .any(|r| r.path == "/api/project/autoland/push/"));
+    assert!(!server
+        .requests()
+        .iter()
+        .any(|r| r.path.starts_with("/api/project/try/")));
+}
+
+#[test]
+fn test_repo_conflicting_with_url() {
+    let server = fixture_server();
+    let url = format!(
+        "https://hg.mozilla.org/integration/autoland/rev/{}",
+        BASE_REVISION
+    );
+    let output = cli(&server)
+        .args(["summary", &url, "--repo", "try"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("The revision URL is in repository 'autoland', but --repo try was given"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_invalid_revision_rejected() {
+    let server = fixture_server();
+    let output = cli(&server).arg("main").output().unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("'main' is not a revision"), "{}", stderr);
+    assert!(server.requests().is_empty());
+}
@@ -1,16 +1,16 @@
 use std::process::Command;
 
 #[test]
-fn test_similar_history_flag_exists() {
+fn test_history_subcommand_exists() {
     let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
     cmd.arg("--help");
     let output = cmd.output().expect("Failed to execute command");
     let stdout = String::from_utf8_lossy(&output.stdout);
-    assert!(stdout.contains("--similar-history"));
+    assert!(stdout.contains("history"));
 }
 
 #[test]
-fn test_similar_history_help_text() {
+fn test_history_help_text() {
     let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
     cmd.arg("--help");
     let output = cmd.output().expect("Failed to execute command");
@@ -19,19 +19,29 @@ fn test_similar_history_help_text() {
 }
 
 #[test]
-fn test_similar_count_flag_exists() {
+fn test_history_count_flag_exists() {
     let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
-    cmd.arg("--help");
+    cmd.arg("history").arg("--help");
     let output = cmd.output().expect("Failed to execute command");
     let stdout = String::from_utf8_lossy(&output.stdout);
-    assert!(stdout.contains("--similar-count"));
+    assert!(stdout.contains("--count"));
 }
 
 #[test]
-fn test_similar_count_help_text() {
+fn test_history_count_help_text() {
     let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
-    cmd.arg("--help");
+    cmd.arg("history").arg("--help");
     let output = cmd.output().expect("Failed to execute command");
     let stdout = String::from_utf8_lossy(&output.stdout);
     assert!(stdout.contains("similar jobs to fetch"));
 }
+
+#[test]
+fn test_history_requires_job_id() {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
+    cmd.arg("history");
+    let output = cmd.output().expect("Failed to execute command");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("<JOB_ID>"));
+}
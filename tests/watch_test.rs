@@ -1,21 +1,31 @@
+mod common;
+
 use assert_cmd::assert::OutputAssertExt;
+use common::*;
 use predicates::prelude::*;
 use std::process::Command;
 
+fn watch_events(output: &std::process::Output) -> Vec<serde_json::Value> {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
 #[test]
-fn test_watch_flag_exists() {
+fn test_watch_subcommand_exists() {
     let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
     cmd.arg("--help");
 
     cmd.assert()
         .success()
-        .stdout(predicate::str::contains("--watch"));
+        .stdout(predicate::str::contains("watch"));
 }
 
 #[test]
 fn test_notify_flag_exists() {
     let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
-    cmd.arg("--help");
+    cmd.arg("watch").arg("--help");
 
     cmd.assert()
         .success()
@@ -23,7 +33,7 @@ fn test_notify_flag_exists() {
 }
 
 #[test]
-fn test_notify_requires_watch() {
+fn test_notify_only_on_watch() {
     let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
     cmd.arg("--notify")
         .arg("--repo")
@@ -32,20 +42,21 @@ fn test_notify_requires_watch() {
 
     cmd.assert()
         .failure()
-        .stderr(predicate::str::contains("--notify requires --watch"));
+        .stderr(predicate::str::contains("--notify"));
 }
 
 #[test]
-fn test_watch_incompatible_with_use_cache() {
+fn test_cache_rejects_watch_interval() {
     let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
-    cmd.arg("--watch")
-        .arg("--use-cache")
+    cmd.arg("cache")
         .arg("--cache-dir")
-        .arg("/tmp/test");
+        .arg("/tmp/test")
+        .arg("--interval")
+        .arg("10");
 
-    cmd.assert().failure().stderr(predicate::str::contains(
-        "--watch cannot be used with --use-cache",
-    ));
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--interval"));
 }
 
 #[test]
@@ -56,7 +67,7 @@ fn test_watch_mode_basic() {
     let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("treeherder-cli"));
     cmd.arg("--repo")
         .arg("mozilla-central")
-        .arg("--watch")
+        .arg("watch")
         .arg("d4c62df049fd"); // A stable old revision with completed jobs
 
     let output = cmd.output().unwrap();
@@ -70,3 +81,411 @@ fn test_watch_mode_basic() {
         );
     }
 }
+
+#[test]
+fn test_watch_streams_ndjson_events() {
+    let server = running_push_server();
+    let output = cli(&server)
+        .args(["--json", "watch", BASE_REVISION, "--interval", "0"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let events = watch_events(&output);
+    let summary: Vec<(String, u64)> = events
+        .iter()
+        .filter(|e| e["event"] != "push_complete")
+        .map(|e| {
+            (
+                e["event"].as_str().unwrap().to_string(),
+                e["job"]["id"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    let expected = [
+        ("job_started", 5001),
+        ("job_passed", 5003),
+        ("job_started", 5006),
+        ("job_failed", 5005),
+        ("job_started", 5002),
+        ("job_failed", 5001),
+        ("job_passed", 5006),
+        ("job_failed", 5002),
+    ];
+    assert_eq!(
+        summary,
+        expected
+            .iter()
+            .map(|(event, id)| (event.to_string(), *id))
+            .collect::<Vec<_>>()
+    );
+
+    let failed_5001 = &events[5];
+    assert_eq!(failed_5001["revision"], BASE_REVISION);
+    assert_eq!(failed_5001["push_id"], BASE_PUSH_ID);
+    assert_eq!(
+        failed_5001["errors"][0]["test"],
+        "dom/media/test/test_playback.html"
+    );
+
+    let complete = events.last().unwrap();
+    assert_eq!(complete["event"], "push_complete");
+    assert_eq!(complete["status"], "failed");
+    assert_eq!(complete["until"], "all-complete");
+    assert_eq!(complete["jobs"], 6);
+    assert_eq!(complete["failed"], 3);
+    assert_eq!(complete["passed"], 2);
+}
+
+#[test]
+fn test_watch_markdown_reports_failures_as_they_happen() {
+    let server = running_push_server();
+    let output = cli(&server)
+        .args(["watch", BASE_REVISION, "--interval", "0"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let failure = stdout
+        .find("✗ M(mda1) test-linux1804-64-qr/opt-mochitest-media-1")
+        .expect(&stdout);
+    let complete = stdout
+        .find("All 6 jobs of push 1001 completed: 3 failed, 2 passed")
+        .expect(&stdout);
+    let report = stdout.find("Failed Jobs (3 failures)").expect(&stdout);
+    assert!(failure < complete && complete < report, "{}", stdout);
+    assert!(
+        stdout.contains("dom/media/test/test_playback.html Timed out waiting for ended event"),
+        "{}",
+        stdout
+    );
+
+    // Failures fetched while watching are not fetched again for the report.
+    assert_eq!(server.hits("/logs/5001/errorsummary.log"), 1);
+}
+
+#[test]
+fn test_watch_until_first_failure() {
+    let server = running_push_server();
+    let output = cli(&server)
+        .args(["--json", "watch", BASE_REVISION, "--interval", "0"])
+        .args(["--until", "first-failure"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let events = watch_events(&output);
+    let complete = events.last().unwrap();
+    assert_eq!(complete["until"], "first-failure");
+    assert_eq!(complete["status"], "failed");
+    assert_eq!(complete["jobs"], 6);
+    assert_eq!(complete["completed"], 3);
+    assert_eq!(complete["failed"], 1);
+    // The lint job already failed in the first job list.
+    assert_eq!(server.hits("/api/jobs/"), 1);
+}
+
+#[test]
+fn test_watch_until_failure_count() {
+    let server = running_push_server();
+    let output = cli(&server)
+        .args(["--json", "watch", BASE_REVISION, "--interval", "0"])
+        .args(["--until", "failures>=2"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let complete = watch_events(&output).pop().unwrap();
+    assert_eq!(complete["until"], "failures>=2");
+    assert_eq!(complete["failed"], 2);
+    assert_eq!(server.hits("/api/jobs/"), 2);
+}
+
+#[test]
+fn test_watch_until_filtered_complete() {
+    let filters = [
+        "--filter",
+        "mochitest-media",
+        "--platform",
+        "linux.*",
+        "--interval",
+        "0",
+    ];
+
+    // By default the watch waits for every job of the push.
+    let server = running_push_server();
+    let output = cli(&server)
+        .args(["--json", "watch", BASE_REVISION])
+        .args(filters)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    assert_eq!(server.hits("/api/jobs/"), 3);
+
+    let server = running_push_server();
+    let output = cli(&server)
+        .args(["watch", BASE_REVISION, "--until", "filtered-complete"])
+        .args(filters)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains(
+            "All 1 filtered jobs of push 1001 completed (6 in the push): 1 failed, 0 passed"
+        ),
+        "{}",
+        stdout
+    );
+    assert_eq!(server.hits("/api/jobs/"), 2);
+}
+
+#[test]
+fn test_watch_until_filtered_complete_waits_for_matching_jobs() {
+    // The mochitest-media jobs are only scheduled after the first poll.
+    let server = fixture_server();
+    let mut jobs: serde_json::Value = serde_json::from_str(&jobs_snapshot(&[])).unwrap();
+    jobs["results"]
+        .as_array_mut()
+        .unwrap()
+        .retain(|row| row[0] != 5001 && row[0] != 5002);
+    server.mock_first(
+        &format!("/api/jobs/?push_id={}", BASE_PUSH_ID),
+        MockResponse::json(jobs.to_string()),
+    );
+
+    let output = cli(&server)
+        .args(["--json", "watch", BASE_REVISION, "--interval", "0"])
+        .args([
+            "--filter",
+            "mochitest-media",
+            "--until",
+            "filtered-complete",
+        ])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    assert_eq!(server.hits("/api/jobs/"), 2);
+    let events = watch_events(&output);
+    let complete = events.last().unwrap();
+    assert_eq!(complete["event"], "push_complete");
+    assert_eq!(complete["until"], "filtered-complete");
+    assert_eq!(complete["jobs"], 2);
+    assert_eq!(complete["total_jobs"], 6);
+    assert_eq!(complete["failed"], 2);
+}
+
+#[test]
+fn test_watch_until_tier1_complete() {
+    // The tier-2 web-platform-tests job of the base push never finishes.
+    let server = fixture_server();
+    let output = cli(&server)
+        .args(["watch", BASE_REVISION, "--interval", "0"])
+        .args(["--until", "tier1-complete"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains(
+            "Stopped watching push 1001 on tier1-complete: 5 of 6 jobs completed, \
+             3 failed, 1 passed"
+        ),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("Failed Jobs (3 failures)"), "{}", stdout);
+}
+
+#[test]
+fn test_watch_until_rejects_unknown_condition() {
+    let server = fixture_server();
+    for condition in ["never", "failures>=0"] {
+        let output = cli(&server)
+            .args(["watch", BASE_REVISION, "--until", condition])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(1), "{:?}", output);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Unknown watch condition"), "{}", stderr);
+    }
+    assert!(server.requests().is_empty());
+}
+
+#[test]
+fn test_watch_fetches_only_modified_jobs() {
+    let server = fixture_server();
+    // Only the web-platform-tests job changed since the first job list.
+    let mut modified: serde_json::Value =
+        serde_json::from_str(&jobs_snapshot(&[(5006, "completed", "success")])).unwrap();
+    modified["results"]
+        .as_array_mut()
+        .unwrap()
+        .retain(|row| row[0] == 5006);
+    let last = modified["results"][0].as_array().unwrap().len() - 1;
+    modified["results"][0][last] = "2025-10-09T09:30:00.000000".into();
+    server.mock(
+        &format!(
+            "/api/jobs/?push_id={}&last_modified__gt=2025-10-09T09:00:00.000000",
+            BASE_PUSH_ID
+        ),
+        MockResponse::json(modified.to_string()),
+    );
+
+    let output = cli(&server)
+        .args(["--json", "watch", BASE_REVISION, "--interval", "0"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let job_requests: Vec<RecordedRequest> = server
+        .requests()
+        .into_iter()
+        .filter(|r| r.path == "/api/jobs/")
+        .collect();
+    assert_eq!(job_requests.len(), 2);
+    let since = |r: &RecordedRequest| {
+        r.query
+            .iter()
+            .find(|(key, _)| key == "last_modified__gt")
+            .map(|(_, value)| value.clone())
+    };
+    assert_eq!(since(&job_requests[0]), None);
+    assert_eq!(
+        since(&job_requests[1]).as_deref(),
+        Some("2025-10-09T09:00:00.000000")
+    );
+
+    // The other jobs are kept from the first job list.
+    let events = watch_events(&output);
+    let complete = events.last().unwrap();
+    assert_eq!(complete["jobs"], 6);
+    assert_eq!(complete["passed"], 2);
+    assert_eq!(events[events.len() - 2]["event"], "job_passed");
+    assert_eq!(events[events.len() - 2]["job"]["id"], 5006);
+}
+
+#[test]
+fn test_poll_schedule_adapts_to_activity() {
+    use std::time::Duration;
+    use treeherder_cli::PollSchedule;
+
+    let within = |delay: Duration, secs: u64| {
+        let base = Duration::from_secs(secs);
+        assert!(
+            delay >= base.mul_f64(0.8) && delay <= base.mul_f64(1.2),
+            "{:?} is not about {:?}",
+            delay,
+            base
+        );
+    };
+    let mut schedule = PollSchedule::new(Duration::from_secs(10), Duration::from_secs(80));
+    // Jobs changing: poll fast.
+    within(schedule.next_delay(true, true), 10);
+    // Jobs running without changes: back off.
+    within(schedule.next_delay(false, true), 20);
+    within(schedule.next_delay(false, true), 40);
+    within(schedule.next_delay(false, true), 80);
+    within(schedule.next_delay(false, true), 80);
+    within(schedule.next_delay(true, true), 10);
+    // Nothing running: poll slowly right away.
+    within(schedule.next_delay(false, false), 80);
+
+    // The shortest interval never exceeds the longest.
+    let mut schedule = PollSchedule::new(Duration::from_secs(30), Duration::from_secs(0));
+    assert_eq!(schedule.next_delay(true, true), Duration::ZERO);
+}
+
+fn push_completes(events: &[serde_json::Value]) -> Vec<&serde_json::Value> {
+    events
+        .iter()
+        .filter(|e| e["event"] == "push_complete")
+        .collect()
+}
+
+#[test]
+fn test_watch_several_pushes() {
+    let server = running_push_server();
+    let output = cli(&server)
+        .args(["--json", "watch", BASE_REVISION, COMPARE_REVISION])
+        .args(["--interval", "0"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let events = watch_events(&output);
+    let mut completes = push_completes(&events);
+    completes.sort_by_key(|e| e["push_id"].as_u64());
+    assert_eq!(completes.len(), 2);
+    assert_eq!(completes[0]["push_id"], BASE_PUSH_ID);
+    assert_eq!(completes[0]["revision"], BASE_REVISION);
+    assert_eq!(completes[0]["failed"], 3);
+    assert_eq!(completes[1]["push_id"], COMPARE_PUSH_ID);
+    assert_eq!(completes[1]["revision"], COMPARE_REVISION);
+    assert_eq!(completes[1]["jobs"], 3);
+    assert_eq!(completes[1]["failed"], 2);
+    assert_eq!(completes[1]["passed"], 1);
+
+    // Each event is tagged with its push.
+    let failed_6002 = events
+        .iter()
+        .find(|e| e["event"] == "job_failed" && e["job"]["id"] == 6002)
+        .unwrap();
+    assert_eq!(failed_6002["push_id"], COMPARE_PUSH_ID);
+}
+
+#[test]
+fn test_watch_several_pushes_markdown() {
+    let server = running_push_server();
+    let output = cli(&server)
+        .args(["watch", BASE_REVISION, COMPARE_REVISION, "--interval", "0"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    for summary in [
+        "All 6 jobs of push 1001 completed: 3 failed, 2 passed",
+        "All 3 jobs of push 1002 completed: 2 failed, 1 passed",
+        BASE_REVISION,
+        COMPARE_REVISION,
+    ] {
+        assert!(stdout.contains(summary), "{}\n{}", summary, stdout);
+    }
+}
+
+#[test]
+fn test_watch_lando_job_with_revision() {
+    let server = running_push_server();
+    let output = cli(&server)
+        .args(["--json", "watch", COMPARE_REVISION, "--interval", "0"])
+        .args(["--lando-job-id", &LANDO_JOB_ID.to_string()])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let events = watch_events(&output);
+    let mut pushes: Vec<u64> = push_completes(&events)
+        .iter()
+        .map(|e| e["push_id"].as_u64().unwrap())
+        .collect();
+    pushes.sort();
+    // The Lando job landed the base revision.
+    assert_eq!(pushes, vec![BASE_PUSH_ID, COMPARE_PUSH_ID]);
+    assert!(server.hits(&format!("/landing_jobs/{}", LANDO_JOB_ID)) >= 1);
+}
+
+#[test]
+fn test_watch_unknown_revision_fails_before_watching() {
+    let server = running_push_server();
+    let output = cli(&server)
+        .args(["watch", BASE_REVISION, "0123456789ab", "--interval", "0"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    assert_eq!(server.hits("/api/jobs/"), 0);
+}
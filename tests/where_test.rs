@@ -0,0 +1,165 @@
+mod common;
+
+use common::*;
+use serde_json::json;
+use treeherder_cli::models::Job;
+use treeherder_cli::Expr;
+
+fn job(id: u64, platform: &str, result: &str, duration: Option<u64>) -> Job {
+    serde_json::from_value(json!({
+        "id": id,
+        "job_type_name": format!("test-{}/opt-mochitest-{}", platform, id),
+        "job_type_symbol": "M",
+        "platform": platform,
+        "platform_option": "opt",
+        "result": result,
+        "state": "completed",
+        "failure_classification_id": if id == 4 { 4 } else { 1 },
+        "duration": duration,
+    }))
+    .unwrap()
+}
+
+fn selected(expression: &str) -> Vec<u64> {
+    let expr = Expr::parse(expression).unwrap();
+    [
+        job(1, "linux1804-64", "testfailed", Some(2000)),
+        job(2, "windows11-64", "busted", Some(100)),
+        job(3, "linux1804-64", "success", None),
+        job(4, "macosx1470-64", "testfailed", Some(4000)),
+    ]
+    .iter()
+    .filter(|job| expr.matches(job))
+    .map(|job| job.id)
+    .collect()
+}
+
+#[test]
+fn test_comparisons() {
+    assert_eq!(selected("platform ~ \"linux.*\""), vec![1, 3]);
+    assert_eq!(selected("result in (testfailed, busted)"), vec![1, 2, 4]);
+    assert_eq!(selected("duration > 1800"), vec![1, 4]);
+    assert_eq!(selected("duration <= 100"), vec![2]);
+    assert_eq!(selected("result != success"), vec![1, 2, 4]);
+    assert_eq!(selected("classification = intermittent"), vec![4]);
+    assert_eq!(selected("name !~ 'windows'"), vec![1, 3, 4]);
+    assert_eq!(selected("id == 3"), vec![3]);
+}
+
+#[test]
+fn test_multi_word_classifications() {
+    let classified = |id: u64, classification_id: u64| -> Job {
+        let mut job = job(id, "linux1804-64", "testfailed", None);
+        job.failure_classification = Some(classification_id.into());
+        job
+    };
+    let jobs = [classified(1, 2), classified(2, 6), classified(3, 7)];
+    let selected = |expression: &str| -> Vec<u64> {
+        let expr = Expr::parse(expression).unwrap();
+        jobs.iter()
+            .filter(|job| expr.matches(job))
+            .map(|job| job.id)
+            .collect()
+    };
+
+    assert_eq!(selected("classification = fixed-by-commit"), vec![1]);
+    assert_eq!(selected("classification = \"fixed by commit\""), vec![1]);
+    assert_eq!(
+        selected("classification in (new-failure, autoclassified-intermittent)"),
+        vec![2, 3]
+    );
+    assert_eq!(selected("classification ~ intermittent"), vec![3]);
+}
+
+#[test]
+fn test_boolean_operators() {
+    assert_eq!(
+        selected("platform ~ \"linux.*\" and result in (testfailed, busted) and duration > 1800"),
+        vec![1]
+    );
+    assert_eq!(
+        selected("not (result = success or classification_id = 4)"),
+        vec![1, 2]
+    );
+    assert_eq!(
+        selected("result = busted or duration > 3000 and platform ~ mac"),
+        vec![2, 4]
+    );
+    assert_eq!(selected("NOT duration > 0"), vec![3]);
+}
+
+#[test]
+fn test_parse_errors() {
+    let error = |expression: &str| Expr::parse(expression).unwrap_err().to_string();
+
+    assert!(error("colour = red").contains("Unknown field 'colour'"));
+    assert!(error("duration > long").contains("Expected a number but found 'long'"));
+    assert_eq!(
+        error("platform > 3"),
+        "'>' can only be used on id, classification_id, duration, tier, retry_id, \
+         submit_timestamp, start_timestamp, end_timestamp"
+    );
+    assert!(Expr::parse("tier >= 2 and retry_id > 0 and end_timestamp < 1760000000").is_ok());
+    assert!(error("result in (success").contains("Expected ')'"));
+    assert!(error("platform ~ \"linux").contains("Unterminated string"));
+    assert!(error("result = success success").contains("Unexpected 'success'"));
+    assert!(error("platform ~ \"(\"").contains("regex"));
+}
+
+#[test]
+fn test_where_flag() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .args([
+            "--json",
+            BASE_REVISION,
+            "--where",
+            "platform ~ \"linux.*\" and duration > 800",
+        ])
+        .output()
+        .unwrap();
+
+    let mut ids: Vec<u64> = stdout_json(&output)["jobs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|j| j["job"]["id"].as_u64().unwrap())
+        .collect();
+    ids.sort();
+    assert_eq!(ids, vec![5001, 5003]);
+}
+
+#[test]
+fn test_where_flag_in_cache() {
+    let server = fixture_server();
+    let cache_dir = tempfile::tempdir().unwrap();
+    cli(&server)
+        .args(["--json", "logs", "--cache-dir"])
+        .arg(cache_dir.path())
+        .arg(BASE_REVISION)
+        .output()
+        .unwrap();
+
+    let output = cli(&server)
+        .args(["cache", "--cache-dir"])
+        .arg(cache_dir.path())
+        .args(["--where", "result = busted"])
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Jobs matching filter: 1"), "{}", stdout);
+}
+
+#[test]
+fn test_where_flag_rejects_invalid_expressions() {
+    let server = fixture_server();
+    let output = cli(&server)
+        .args([BASE_REVISION, "--where", "colour = red"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Unknown field 'colour'"), "{}", stderr);
+}